@@ -1,6 +1,6 @@
 use gpui::{
-    App, Application, Context, Div, Global, MenuItem, QuitMode, SharedString, Stateful, Tray,
-    Window, WindowOptions, actions, div, prelude::*,
+    App, Application, Context, Div, Global, MenuItem, MouseButton, QuitMode, SharedString,
+    Stateful, Tray, Window, WindowOptions, actions, div, prelude::*,
 };
 
 struct Example;
@@ -131,7 +131,12 @@ impl AppState {
                 .icon_as_template(cfg!(target_os = "macos"))
                 .title("Tray App")
                 .tooltip("This is a tray icon")
-                .menu(Self::build_menus),
+                .menu(Self::build_menus)
+                .on_click(|event, cx| {
+                    if event.button == MouseButton::Left {
+                        show_window(&ShowWindow, cx);
+                    }
+                }),
         }
     }
 
@@ -1,3 +1,4 @@
+use crate::{KeyContext, SharedString};
 use anyhow::{Context as _, Result};
 use collections::HashMap;
 pub use gpui_macros::Action;
@@ -410,6 +411,23 @@ impl ActionRegistry {
     }
 }
 
+/// An action captured while recording with [`App::start_action_recording`], in the form used to
+/// build it with [`App::build_action`] so that it can be stored and replayed with
+/// [`App::replay_actions`].
+///
+/// [`App::start_action_recording`]: crate::App::start_action_recording
+/// [`App::build_action`]: crate::App::build_action
+/// [`App::replay_actions`]: crate::App::replay_actions
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedAction {
+    /// The name of the action, see [`Action::name`].
+    pub name: SharedString,
+    /// The JSON input of the action, or `None` for an action without fields.
+    pub input: Option<serde_json::Value>,
+    /// The key contexts of the focused element and its ancestors when the action was dispatched.
+    pub context_stack: Vec<KeyContext>,
+}
+
 /// Generate a list of all the registered actions.
 /// Useful for transforming the list of available actions into a
 /// format suited for static analysis such as in validating keymaps, or
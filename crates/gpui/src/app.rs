@@ -1,9 +1,11 @@
 use std::{
     any::{TypeId, type_name},
     cell::{BorrowMutError, Ref, RefCell, RefMut},
+    iter,
     marker::PhantomData,
     mem,
     ops::{Deref, DerefMut},
+    panic::Location,
     path::{Path, PathBuf},
     rc::{Rc, Weak},
     sync::{Arc, atomic::Ordering::SeqCst},
@@ -36,13 +38,14 @@ use crate::InspectorElementRegistry;
 use crate::{
     Action, ActionBuildError, ActionRegistry, Any, AnyView, AnyWindowHandle, AppContext, Asset,
     AssetSource, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle, DispatchPhase, DisplayId,
-    EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global, KeyBinding, KeyContext,
-    Keymap, Keystroke, LayoutId, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, Point, Priority,
-    PromptBuilder, PromptButton, PromptHandle, PromptLevel, Render, RenderImage,
-    RenderablePromptHandle, Reservation, ScreenCaptureSource, SharedString, SubscriberSet,
-    Subscription, SvgRenderer, Task, TextSystem, Tray, Window, WindowAppearance, WindowHandle, WindowId,
-    WindowInvalidator,
+    EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global, GlobalHotkeyId, KeyBinding,
+    KeyContext, Keymap, Keystroke, LayoutId, Menu, MenuItem, Notification, NotificationEvent,
+    NotificationId, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay,
+    PlatformKeyboardLayout, PlatformKeyboardMapper, Point, Priority, PromptBuilder, PromptButton,
+    PromptHandle, PromptLevel, RecordedAction, Render, RenderImage, RenderablePromptHandle,
+    Reservation, ScreenCaptureSource, SharedString, SubscriberSet, Subscription, SvgRenderer, Task,
+    TextSystem, Tray, TrayEvent, TrayHandle, TrayId, Window, WindowAppearance, WindowHandle,
+    WindowId, WindowInvalidator, WindowStateStore,
     colors::{Colors, GlobalColors},
     current_platform, hash, init_app_menus,
 };
@@ -177,6 +180,24 @@ impl Application {
         self
     }
 
+    /// Ensures that only one instance of the application runs at a time, identified by a
+    /// reverse-DNS `app_id` such as `dev.zed.Zed`. When an instance is already running, the
+    /// arguments of this launch are forwarded to it and [`ForwardedToPrimary`] is returned, after
+    /// which the application should exit; the running instance passes them to its
+    /// [`Application::on_open_urls`] callback, with paths converted to `file://` URLs, and
+    /// activates its window.
+    ///
+    /// Call this before [`Application::run`]. Only implemented on Linux, where the instance
+    /// owns `app_id` on the D-Bus session bus, or a socket in `$XDG_RUNTIME_DIR` without one.
+    pub fn single_instance(self, app_id: &str) -> Result<Self, ForwardedToPrimary> {
+        let urls = std::env::args().skip(1).map(launch_arg_to_url).collect();
+        let platform = self.0.borrow().platform.clone();
+        if !platform.claim_single_instance(app_id, urls) {
+            return Err(ForwardedToPrimary);
+        }
+        Ok(self)
+    }
+
     /// Start the application. The provided callback will be called once the
     /// app is fully launched.
     pub fn run<F>(self, on_finish_launching: F)
@@ -237,6 +258,18 @@ impl Application {
     }
 }
 
+/// Paths given on the command line are forwarded as `file://` URLs, other arguments as is.
+fn launch_arg_to_url(arg: String) -> String {
+    let path = Path::new(&arg);
+    if !path.exists() {
+        return arg;
+    }
+    std::path::absolute(path)
+        .ok()
+        .and_then(|path| Url::from_file_path(path).ok())
+        .map_or(arg, |url| url.to_string())
+}
+
 type Handler = Box<dyn FnMut(&mut App) -> bool + 'static>;
 type Listener = Box<dyn FnMut(&dyn Any, &mut App) -> bool + 'static>;
 pub(crate) type KeystrokeObserver =
@@ -246,6 +279,12 @@ type WindowClosedHandler = Box<dyn FnMut(&mut App)>;
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut App) + 'static>;
 type NewEntityListener = Box<dyn FnMut(AnyEntity, &mut Option<&mut Window>, &mut App) + 'static>;
 
+/// Returned by [`Application::single_instance`] when the launch was forwarded to the instance
+/// of the application that is already running.
+#[derive(Debug, thiserror::Error)]
+#[error("the application is already running, the launch was forwarded to it")]
+pub struct ForwardedToPrimary;
+
 /// Defines when the application should automatically quit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum QuitMode {
@@ -604,6 +643,9 @@ pub struct App {
     pub(crate) window_handles: FxHashMap<WindowId, AnyWindowHandle>,
     pub(crate) focus_handles: Arc<FocusMap>,
     pub(crate) keymap: Rc<RefCell<Keymap>>,
+    pub(crate) pending_input_timeout: Duration,
+    action_recording: Option<Vec<RecordedAction>>,
+    replaying_actions: bool,
     pub(crate) keyboard_layout: Box<dyn PlatformKeyboardLayout>,
     pub(crate) keyboard_mapper: Rc<dyn PlatformKeyboardMapper>,
     pub(crate) global_action_listeners:
@@ -617,14 +659,23 @@ pub struct App {
     pub(crate) keystroke_observers: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keystroke_interceptors: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
+    pub(crate) clipboard_observers: SubscriberSet<(), Handler>,
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    pub(crate) primary_selection_observers: SubscriberSet<(), Handler>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
     pub(crate) restart_observers: SubscriberSet<(), Handler>,
     pub(crate) restart_path: Option<PathBuf>,
     pub(crate) window_closed_observers: SubscriberSet<(), WindowClosedHandler>,
+    pub(crate) window_state_store: WindowStateStore,
+    pub(crate) posted_notifications: FxHashMap<NotificationId, Notification>,
+    pub(crate) trays: FxHashMap<TrayId, Tray>,
+    pub(crate) default_tray: Option<TrayId>,
+    pub(crate) global_hotkeys: FxHashMap<GlobalHotkeyId, Box<dyn Action>>,
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
+    pub(crate) propagation_stopped_at: Option<&'static Location<'static>>,
     pub(crate) prompt_builder: Option<PromptBuilder>,
     pub(crate) window_invalidators_by_entity:
         FxHashMap<EntityId, FxHashMap<WindowId, WindowInvalidator>>,
@@ -682,6 +733,9 @@ impl App {
                 window_handles: FxHashMap::default(),
                 focus_handles: Arc::new(RwLock::new(SlotMap::with_key())),
                 keymap: Rc::new(RefCell::new(Keymap::default())),
+                pending_input_timeout: Duration::from_secs(1),
+                action_recording: None,
+                replaying_actions: false,
                 keyboard_layout,
                 keyboard_mapper,
                 global_action_listeners: FxHashMap::default(),
@@ -696,13 +750,22 @@ impl App {
                 keystroke_observers: SubscriberSet::new(),
                 keystroke_interceptors: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
+                clipboard_observers: SubscriberSet::new(),
+                #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+                primary_selection_observers: SubscriberSet::new(),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
                 restart_observers: SubscriberSet::new(),
                 restart_path: None,
                 window_closed_observers: SubscriberSet::new(),
+                window_state_store: WindowStateStore::new(platform.window_state_path()),
+                posted_notifications: FxHashMap::default(),
+                trays: FxHashMap::default(),
+                default_tray: None,
+                global_hotkeys: FxHashMap::default(),
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
+                propagation_stopped_at: None,
                 prompt_builder: Some(PromptBuilder::Default),
                 #[cfg(any(feature = "inspector", debug_assertions))]
                 inspector_renderer: None,
@@ -733,6 +796,65 @@ impl App {
             }
         }));
 
+        platform.on_clipboard_change(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.update(|cx| {
+                        cx.clipboard_observers
+                            .clone()
+                            .retain(&(), move |callback| (callback)(cx));
+                    });
+                }
+            }
+        }));
+
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        platform.on_primary_selection_change(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.update(|cx| {
+                        cx.primary_selection_observers
+                            .clone()
+                            .retain(&(), move |callback| (callback)(cx));
+                    });
+                }
+            }
+        }));
+
+        platform.on_notification_event(Box::new({
+            let app = Rc::downgrade(&app);
+            move |id, event| {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.update(|cx| cx.handle_notification_event(id, event));
+                }
+            }
+        }));
+
+        platform.on_tray_event(Box::new({
+            let app = Rc::downgrade(&app);
+            move |id, event| {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.update(|cx| cx.handle_tray_event(id, event));
+                }
+            }
+        }));
+
+        platform.on_global_hotkey(Box::new({
+            let app = Rc::downgrade(&app);
+            move |id| {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.update(|cx| cx.handle_global_hotkey(id));
+                }
+            }
+        }));
+
         platform.on_quit(Box::new({
             let cx = app.clone();
             move || {
@@ -755,6 +877,7 @@ impl App {
         self.windows.clear();
         self.window_handles.clear();
         self.flush_effects();
+        self.window_state_store.flush();
         self.quitting = true;
 
         let futures = futures::future::join_all(futures);
@@ -1104,6 +1227,51 @@ impl App {
         self.platform.read_from_clipboard()
     }
 
+    /// Reads the data of the given MIME type from the platform clipboard, such as a
+    /// [`crate::ClipboardEntry::Custom`] written by another instance of the application. Items
+    /// read with `read_from_clipboard` only contain custom formats this application wrote.
+    /// Only implemented on Linux for now.
+    pub fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.platform.read_custom_from_clipboard(mime)
+    }
+
+    /// Invokes a handler when the contents of the platform clipboard change, whether this or
+    /// another application wrote them. Only fired on X11, Wayland and the test platform, not on
+    /// macOS or Windows. Wayland compositors report changes made by other applications once a
+    /// window of this one is focused.
+    pub fn observe_clipboard<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.clipboard_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Invokes a handler when the contents of the primary selection buffer change, whether this or
+    /// another application wrote them. Only available on Linux.
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    pub fn observe_primary_selection<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.primary_selection_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Writes credentials to the platform keychain.
     pub fn write_credentials(
         &self,
@@ -1774,6 +1942,19 @@ impl App {
         self.keymap.clone()
     }
 
+    /// Set how long to wait for the rest of a multi-stroke binding like `ctrl-k ctrl-c` before
+    /// dispatching the keystrokes typed so far on their own. Defaults to a second, and can be
+    /// overridden per binding with [`KeyBinding::with_pending_timeout`].
+    pub fn set_pending_input_timeout(&mut self, timeout: Duration) {
+        self.pending_input_timeout = timeout;
+    }
+
+    /// Get how long to wait for the rest of a multi-stroke binding, see
+    /// [`Self::set_pending_input_timeout`].
+    pub fn pending_input_timeout(&self) -> Duration {
+        self.pending_input_timeout
+    }
+
     /// Register a global handler for actions invoked via the keyboard. These handlers are run at
     /// the end of the bubble phase for actions, and so will only be invoked if there are no other
     /// handlers or if they called `cx.propagate()`.
@@ -1793,8 +1974,10 @@ impl App {
     /// event handlers with a lower z-index (mouse) or higher in the tree (keyboard). This is
     /// the opposite of [`Self::propagate`]. It's also possible to cancel a call to [`Self::propagate`] by
     /// calling this method before effects are flushed.
+    #[track_caller]
     pub fn stop_propagation(&mut self) {
         self.propagate_event = false;
+        self.propagation_stopped_at = Some(Location::caller());
     }
 
     /// Action handlers stop propagation by default during the bubble phase of action dispatch
@@ -1803,6 +1986,7 @@ impl App {
     /// this method before effects are flushed.
     pub fn propagate(&mut self) {
         self.propagate_event = true;
+        self.propagation_stopped_at = None;
     }
 
     /// Build an action from some arbitrary data, typically a keymap entry.
@@ -1936,13 +2120,31 @@ impl App {
     }
 
     /// Sets the system tray icon and menu for the application.
+    ///
+    /// This replaces the tray icon previously set with this method, use [`App::add_tray`]
+    /// to show several tray icons.
     pub fn set_tray(&mut self, tray: Tray) {
-        let mut tray = tray;
-        let menu_items = if let Some(build) = tray.menu_builder.as_ref() {
-            Some(build(self))
-        } else {
-            None
+        let id = *self.default_tray.get_or_insert_with(TrayId::next);
+        self.trays.insert(id, tray);
+        self.refresh_tray(id);
+    }
+
+    /// Adds a system tray icon, returning a handle that can be used to update or remove it.
+    ///
+    /// Each tray icon has its own menu and callbacks. On macOS and Windows only a single
+    /// tray icon is currently supported, so the most recently updated one is shown.
+    pub fn add_tray(&mut self, tray: Tray) -> TrayHandle {
+        let id = TrayId::next();
+        self.trays.insert(id, tray);
+        self.refresh_tray(id);
+        TrayHandle { id }
+    }
+
+    pub(crate) fn refresh_tray(&mut self, id: TrayId) {
+        let Some(mut tray) = self.trays.get(&id).cloned() else {
+            return;
         };
+        let menu_items = tray.menu_builder.clone().map(|build| build(self));
 
         match tray.render_icon(self) {
             Err(err) => {
@@ -1951,7 +2153,121 @@ impl App {
             Ok(_) => {}
         }
         self.platform
-            .set_tray(tray, menu_items, &self.keymap.borrow())
+            .set_tray(id, tray, menu_items, &self.keymap.borrow())
+    }
+
+    pub(crate) fn remove_tray(&mut self, id: TrayId) {
+        if self.trays.remove(&id).is_some() {
+            if self.default_tray == Some(id) {
+                self.default_tray = None;
+            }
+            self.platform.remove_tray(id);
+        }
+    }
+
+    fn handle_tray_event(&mut self, id: TrayId, event: TrayEvent) {
+        let Some(tray) = self.trays.get(&id) else {
+            return;
+        };
+        match event {
+            TrayEvent::Click(event) => {
+                if let Some(on_click) = tray.on_click.clone() {
+                    on_click(&event, self);
+                }
+            }
+            TrayEvent::DoubleClick(event) => {
+                if let Some(on_double_click) = tray.on_double_click.clone() {
+                    on_double_click(&event, self);
+                }
+            }
+            TrayEvent::Scroll(event) => {
+                if let Some(on_scroll) = tray.on_scroll.clone() {
+                    on_scroll(&event, self);
+                }
+            }
+        }
+    }
+
+    /// Posts a system notification, returning an identifier that can be used to close it.
+    ///
+    /// Notifications are currently only supported on Linux, elsewhere this does nothing.
+    pub fn show_notification(&mut self, notification: Notification) -> NotificationId {
+        let id = NotificationId::next();
+        let mut notification = notification;
+        if let Err(err) = notification.render_icon(self) {
+            log::warn!("Ignored invalid notification icon: {}", err);
+        }
+        // Callbacks are kept until the notification is dismissed, which platforms also report
+        // when posting it fails.
+        if self.platform.show_notification(id, notification.clone()) {
+            self.posted_notifications.insert(id, notification);
+        }
+        id
+    }
+
+    /// Closes a notification posted with [`Self::show_notification`].
+    pub fn close_notification(&mut self, id: NotificationId) {
+        if self.posted_notifications.contains_key(&id) {
+            self.platform.close_notification(id);
+        }
+    }
+
+    fn handle_notification_event(&mut self, id: NotificationId, event: NotificationEvent) {
+        match event {
+            NotificationEvent::ActionInvoked(action) => {
+                let on_action = self
+                    .posted_notifications
+                    .get(&id)
+                    .and_then(|notification| notification.on_action.clone());
+                if let Some(on_action) = on_action {
+                    on_action(&action, self);
+                }
+            }
+            NotificationEvent::Dismissed(reason) => {
+                let on_dismiss = self
+                    .posted_notifications
+                    .remove(&id)
+                    .and_then(|notification| notification.on_dismiss);
+                if let Some(on_dismiss) = on_dismiss {
+                    on_dismiss(reason, self);
+                }
+            }
+        }
+    }
+
+    /// Registers a system-wide hotkey, which dispatches the action with [`App::dispatch_action`]
+    /// even while none of the application's windows are focused.
+    ///
+    /// The keystroke is written like those of a [`KeyBinding`], e.g. `ctrl-shift-space`. Some
+    /// platforms ask the user to confirm the hotkey, or let them assign a different one.
+    ///
+    /// Only supported on Linux, returns an error on other platforms, or when the key can't be
+    /// grabbed on X11, e.g. because another application already did.
+    pub fn register_global_hotkey(
+        &mut self,
+        keystroke: &str,
+        action: impl Action,
+    ) -> Result<GlobalHotkeyId> {
+        let keystroke = Keystroke::parse(keystroke)?;
+        let id = GlobalHotkeyId::next();
+        self.platform
+            .register_global_hotkey(id, &keystroke, action.name())?;
+        self.global_hotkeys.insert(id, action.boxed_clone());
+        Ok(id)
+    }
+
+    /// Unregisters a hotkey registered with [`Self::register_global_hotkey`].
+    pub fn unregister_global_hotkey(&mut self, id: GlobalHotkeyId) {
+        if self.global_hotkeys.remove(&id).is_some() {
+            self.platform.unregister_global_hotkey(id);
+        }
+    }
+
+    fn handle_global_hotkey(&mut self, id: GlobalHotkeyId) {
+        if let Some(action) = self.global_hotkeys.get(&id) {
+            let action = action.boxed_clone();
+            self.dispatch_action(action.as_ref());
+        }
     }
 
     /// macOS only: controls whether the app shows in the Dock. No-op on other platforms.
@@ -1972,6 +2288,26 @@ impl App {
         self.platform.add_recent_document(path);
     }
 
+    /// Shows a count on the application's icon in the dock or launcher, such as the number of
+    /// unread messages, or removes it when `None`. Only implemented on Linux for now.
+    pub fn set_badge_count(&self, count: Option<u64>) {
+        self.platform.set_badge_count(count);
+    }
+
+    /// Shows the progress of a long-running job, between 0 and 1, as a progress bar on the
+    /// application's icon in the dock or launcher, or removes it when `None`.
+    /// Only implemented on Linux for now.
+    pub fn set_progress(&self, progress: Option<f32>) {
+        self.platform.set_progress(progress);
+    }
+
+    /// Asks the desktop to draw the user's attention to the application, for instance by
+    /// highlighting its icon, until called again with `false` or the application is focused.
+    /// Only implemented on Linux for now.
+    pub fn request_user_attention(&self, urgent: bool) {
+        self.platform.request_user_attention(urgent);
+    }
+
     /// Updates the jump list with the updated list of recent paths for the application, only used on Windows for now.
     /// Note that this also sets the dock menu on Windows.
     pub fn update_jump_list(
@@ -1992,10 +2328,89 @@ impl App {
                 })
                 .log_err();
         } else {
+            if self.is_recording_actions() {
+                self.record_action(action, None, Vec::new());
+            }
             self.dispatch_global_action(action);
         }
     }
 
+    /// Start recording the actions dispatched in the app, e.g. to let users record a macro,
+    /// discarding any recording in progress. Actions dispatched from code rather than a key
+    /// binding are only recorded if they have no fields, as their JSON input isn't known.
+    pub fn start_action_recording(&mut self) {
+        self.action_recording = Some(Vec::new());
+    }
+
+    /// Stop recording actions, returning the actions dispatched since recording started.
+    pub fn stop_action_recording(&mut self) -> Vec<RecordedAction> {
+        self.action_recording.take().unwrap_or_default()
+    }
+
+    /// Whether actions are being recorded, see [`Self::start_action_recording`]. Actions
+    /// dispatched by [`Self::replay_actions`] are never recorded.
+    pub fn is_recording_actions(&self) -> bool {
+        self.action_recording.is_some() && !self.replaying_actions
+    }
+
+    /// Dispatch the given actions to the focused element of the active window, or to the global
+    /// action handlers if there's no active window, repeating the sequence `times` times. Nothing
+    /// is dispatched if any of the actions can't be built.
+    pub fn replay_actions(
+        &mut self,
+        actions: &[RecordedAction],
+        times: usize,
+    ) -> std::result::Result<(), ActionBuildError> {
+        let actions = actions
+            .iter()
+            .map(|recorded| self.build_action(&recorded.name, recorded.input.clone()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let window = self.active_window();
+
+        self.defer(move |cx| {
+            cx.replaying_actions = true;
+            for action in iter::repeat_n(&actions, times).flatten() {
+                if let Some(window) = window {
+                    window
+                        .update(cx, |_, window, cx| {
+                            window.dispatch_action_on_focus(action.as_ref(), cx)
+                        })
+                        .log_err();
+                } else {
+                    cx.dispatch_global_action(action.as_ref());
+                }
+            }
+            cx.replaying_actions = false;
+        });
+        Ok(())
+    }
+
+    pub(crate) fn record_action(
+        &mut self,
+        action: &dyn Action,
+        action_input: Option<&SharedString>,
+        context_stack: Vec<KeyContext>,
+    ) {
+        let input = action_input.and_then(|input| serde_json::from_str(input).log_err());
+        // Without the input the action was built from, it can only be rebuilt if it has no fields.
+        if input.is_none()
+            && !self
+                .build_action(action.name(), None)
+                .is_ok_and(|built| built.partial_eq(action))
+        {
+            log::warn!("not recording {}, as its input is unknown", action.name());
+            return;
+        }
+
+        if let Some(recording) = &mut self.action_recording {
+            recording.push(RecordedAction {
+                name: action.name().into(),
+                input,
+                context_stack,
+            });
+        }
+    }
+
     fn dispatch_global_action(&mut self, action: &dyn Action) {
         self.propagate_event = true;
 
@@ -2529,7 +2944,128 @@ impl<'a, T> Drop for GpuiBorrow<'a, T> {
 mod test {
     use std::{cell::RefCell, rc::Rc};
 
-    use crate::{AppContext, TestAppContext};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use crate::{
+        self as gpui, Action, AppContext, Context, FocusHandle, InteractiveElement, IntoElement,
+        KeyContext, KeymapFile, Keystroke, RecordedAction, Render, TestAppContext, Window, div,
+    };
+
+    actions!(test_only, [DeleteLine]);
+
+    #[derive(Clone, PartialEq, Deserialize, schemars::JsonSchema, Action)]
+    #[action(namespace = test_only)]
+    struct Insert {
+        text: String,
+    }
+
+    struct Editor {
+        focus_handle: FocusHandle,
+        log: Vec<String>,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .key_context("Editor")
+                .track_focus(&self.focus_handle)
+                .on_action(
+                    cx.listener(|this, action: &Insert, _, _| this.log.push(action.text.clone())),
+                )
+                .on_action(cx.listener(|this, _: &DeleteLine, _, _| this.log.push("delete".into())))
+        }
+    }
+
+    #[gpui::test]
+    fn test_action_recording(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            let source = r#"[{
+                "context": "Editor",
+                "bindings": { "ctrl-i": ["test_only::Insert", { "text": "a" }] }
+            }]"#;
+            let keymap = KeymapFile::load(source, cx);
+            cx.bind_keys(keymap.bindings);
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| Editor {
+                    focus_handle: cx.focus_handle(),
+                    log: Vec::new(),
+                })
+            })
+            .unwrap()
+        });
+        window
+            .update(cx, |editor, window, cx| {
+                window.activate_window();
+                window.focus(&editor.focus_handle);
+                cx.start_action_recording();
+            })
+            .unwrap();
+
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-i").unwrap());
+        window
+            .update(cx, |_, window, cx| {
+                window.dispatch_action(Box::new(DeleteLine), cx);
+                // Not recorded, as its input can't be known.
+                window.dispatch_action(Box::new(Insert { text: "b".into() }), cx);
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        let recording = cx.update(|cx| cx.stop_action_recording());
+        let context_stack = vec![KeyContext::parse("Editor").unwrap()];
+        assert_eq!(
+            recording,
+            [
+                RecordedAction {
+                    name: "test_only::Insert".into(),
+                    input: Some(json!({ "text": "a" })),
+                    context_stack: context_stack.clone(),
+                },
+                RecordedAction {
+                    name: "test_only::DeleteLine".into(),
+                    input: None,
+                    context_stack,
+                },
+            ]
+        );
+
+        cx.update(|cx| cx.replay_actions(&recording, 2)).unwrap();
+        cx.run_until_parked();
+        window
+            .update(cx, |editor, _, _| {
+                assert_eq!(
+                    editor.log,
+                    ["a", "delete", "b", "a", "delete", "a", "delete"]
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_launcher_state(cx: &mut TestAppContext) {
+        assert_eq!(cx.badge_count(), None);
+        assert_eq!(cx.progress(), None);
+        assert!(!cx.user_attention_requested());
+
+        cx.update(|cx| {
+            cx.set_badge_count(Some(3));
+            cx.set_progress(Some(0.5));
+            cx.request_user_attention(true);
+        });
+        assert_eq!(cx.badge_count(), Some(3));
+        assert_eq!(cx.progress(), Some(0.5));
+        assert!(cx.user_attention_requested());
+
+        cx.update(|cx| {
+            cx.set_badge_count(None);
+            cx.set_progress(None);
+            cx.request_user_attention(false);
+        });
+        assert_eq!(cx.badge_count(), None);
+        assert_eq!(cx.progress(), None);
+        assert!(!cx.user_attention_requested());
+    }
 
     #[test]
     fn test_gpui_borrow() {
@@ -509,7 +509,8 @@ impl<'a, T: 'static> Context<'a, T> {
         )
     }
 
-    /// Register a callback to be invoked when the window's pending input changes.
+    /// Register a callback to be invoked when the window's pending input changes, which can be
+    /// read with [`Window::pending_input`].
     pub fn observe_pending_input(
         &self,
         window: &mut Window,
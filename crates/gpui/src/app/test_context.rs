@@ -1,11 +1,12 @@
 use crate::{
     Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace,
-    BackgroundExecutor, BorrowAppContext, Bounds, Capslock, ClipboardItem, DrawPhase, Drawable,
-    Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    Platform, Point, Render, Result, Size, Task, TestDispatcher, TestPlatform,
-    TestScreenCaptureSource, TestWindow, TextSystem, VisualContext, Window, WindowBounds,
-    WindowHandle, WindowOptions, app::GpuiMode,
+    BackgroundExecutor, BorrowAppContext, Bounds, Capslock, ClipboardItem, DragAction, DragResult,
+    DrawPhase, Drawable, Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent,
+    Keystroke, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent,
+    MouseUpEvent, Notification, NotificationDismissReason, NotificationEvent, NotificationId,
+    Pixels, Platform, Point, Render, Result, Size, Task, TestDispatcher, TestPlatform,
+    TestScreenCaptureSource, TestWindow, TextSystem, Tray, TrayEvent, TrayId, VisualContext,
+    Window, WindowBounds, WindowHandle, WindowOptions, app::GpuiMode,
 };
 use anyhow::{anyhow, bail};
 use futures::{Stream, StreamExt, channel::oneshot};
@@ -336,6 +337,75 @@ impl TestAppContext {
         self.test_platform.opened_url.borrow().clone()
     }
 
+    /// The notifications that have been posted with cx.show_notification() and not yet dismissed.
+    pub fn posted_notifications(&self) -> Vec<(NotificationId, Notification)> {
+        self.test_platform.notifications.borrow().clone()
+    }
+
+    /// Simulates the user clicking the action with the given id on a posted notification.
+    #[track_caller]
+    pub fn simulate_notification_action(&self, id: NotificationId, action: &str) {
+        self.test_platform.simulate_notification_event(
+            id,
+            NotificationEvent::ActionInvoked(action.to_string().into()),
+        );
+    }
+
+    /// Simulates a posted notification being dismissed.
+    #[track_caller]
+    pub fn simulate_notification_dismissed(
+        &self,
+        id: NotificationId,
+        reason: NotificationDismissReason,
+    ) {
+        self.test_platform
+            .simulate_notification_event(id, NotificationEvent::Dismissed(reason));
+    }
+
+    /// The tray icons that are currently shown, in the order they were added.
+    pub fn trays(&self) -> Vec<(TrayId, Tray)> {
+        self.test_platform.trays.borrow().clone()
+    }
+
+    /// Simulates the user interacting with a tray icon shown with cx.set_tray() or cx.add_tray().
+    #[track_caller]
+    pub fn simulate_tray_event(&self, tray: TrayId, event: TrayEvent) {
+        self.test_platform.simulate_tray_event(tray, event);
+    }
+
+    /// The keystrokes of the hotkeys registered with cx.register_global_hotkey(), in the order
+    /// they were registered.
+    pub fn global_hotkeys(&self) -> Vec<Keystroke> {
+        self.test_platform
+            .global_hotkeys
+            .borrow()
+            .iter()
+            .map(|(_, keystroke)| keystroke.clone())
+            .collect()
+    }
+
+    /// Simulates the user pressing a hotkey registered with cx.register_global_hotkey().
+    #[track_caller]
+    pub fn simulate_global_hotkey(&self, keystroke: &str) {
+        let keystroke = Keystroke::parse(keystroke).unwrap();
+        self.test_platform.simulate_global_hotkey(&keystroke);
+    }
+
+    /// The badge count last set with cx.set_badge_count().
+    pub fn badge_count(&self) -> Option<u64> {
+        self.test_platform.badge_count.get()
+    }
+
+    /// The progress last set with cx.set_progress().
+    pub fn progress(&self) -> Option<f32> {
+        self.test_platform.progress.get()
+    }
+
+    /// Whether the application is requesting the user's attention, see cx.request_user_attention().
+    pub fn user_attention_requested(&self) -> bool {
+        self.test_platform.user_attention_requested.get()
+    }
+
     /// Simulates the user resizing the window to the new size.
     pub fn simulate_window_resize(&self, window_handle: AnyWindowHandle, size: Size<Pixels>) {
         self.test_window(window_handle).simulate_resize(size);
@@ -822,6 +892,31 @@ impl VisualTestContext {
         self.simulate_window_resize(self.window, size)
     }
 
+    /// The data and actions of the native drag in progress, see `Window::start_native_drag`.
+    pub fn native_drag(&self) -> Option<(ClipboardItem, Vec<DragAction>)> {
+        let window = self.cx.test_window(self.window);
+        let state = window.0.lock();
+        let (item, actions, _) = state.native_drag.as_ref()?;
+        Some((item.clone(), actions.clone()))
+    }
+
+    /// The reasons of the idle inhibitions held on the window, see `Window::inhibit_idle`.
+    pub fn idle_inhibit_reasons(&self) -> Vec<String> {
+        let window = self.cx.test_window(self.window);
+        let state = window.0.lock();
+        state.idle_inhibit_reasons.clone()
+    }
+
+    /// Simulates the native drag in progress being dropped or cancelled.
+    #[track_caller]
+    pub fn simulate_native_drag_end(&self, result: DragResult) {
+        let window = self.cx.test_window(self.window);
+        let Some((_, _, tx)) = window.0.lock().native_drag.take() else {
+            panic!("no native drag in progress");
+        };
+        tx.send(result).ok();
+    }
+
     /// debug_bounds returns the bounds of the element with the given selector.
     pub fn debug_bounds(&mut self, selector: &'static str) -> Option<Bounds<Pixels>> {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
@@ -17,13 +17,13 @@
 
 use crate::{
     AbsoluteLength, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent,
-    DispatchPhase, Display, Element, ElementId, Entity, FocusHandle, Global, GlobalElementId,
-    Hitbox, HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero, KeyContext,
-    KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId, ModifiersChangedEvent,
-    MouseButton, MouseClickEvent, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Overflow,
-    ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, Visibility, Window, WindowControlArea, point, px,
-    size,
+    Direction, DispatchPhase, Display, Element, ElementId, Entity, ExternalDrop, ExternalPaths,
+    FocusHandle, FocusNeighbors, Global, GlobalElementId, Hitbox, HitboxBehavior, HitboxId,
+    InspectorElementId, IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent, KeyboardButton,
+    KeyboardClickEvent, LayoutId, ModifiersChangedEvent, MouseButton, MouseClickEvent,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, NativeDrag, Overflow, ParentElement, Pixels,
+    Point, Render, ScrollWheelEvent, SharedString, Size, Style, StyleRefinement, Styled, Task,
+    TooltipId, Visibility, Window, WindowControlArea, point, px, size,
 };
 use collections::HashMap;
 use refineable::Refineable;
@@ -74,7 +74,8 @@ impl<T: 'static> DragMoveEvent<T> {
     pub fn drag<'b>(&self, cx: &'b App) -> &'b T {
         cx.active_drag
             .as_ref()
-            .and_then(|drag| drag.value.downcast_ref::<T>())
+            .and_then(|drag| dragged_value(drag.value.as_ref(), TypeId::of::<T>()))
+            .and_then(|value| value.downcast_ref::<T>())
             .expect("DragMoveEvent is only valid when the stored active drag is of the same type.")
     }
 
@@ -84,6 +85,21 @@ impl<T: 'static> DragMoveEvent<T> {
     }
 }
 
+/// The dragged value as seen by drag and drop listeners for the given type: drops from other
+/// applications carrying files are also seen as [`ExternalPaths`].
+fn dragged_value(value: &dyn Any, type_id: TypeId) -> Option<&dyn Any> {
+    if value.type_id() == type_id {
+        Some(value)
+    } else if type_id == TypeId::of::<ExternalPaths>() {
+        value
+            .downcast_ref::<ExternalDrop>()
+            .filter(|drop| !drop.paths.0.is_empty())
+            .map(|drop| &drop.paths as &dyn Any)
+    } else {
+        None
+    }
+}
+
 impl Interactivity {
     /// Create an `Interactivity`, capturing the caller location in debug mode.
     #[cfg(any(feature = "inspector", debug_assertions))]
@@ -289,7 +305,7 @@ impl Interactivity {
             .push(Box::new(move |event, phase, hitbox, window, cx| {
                 if phase == DispatchPhase::Capture
                     && let Some(drag) = &cx.active_drag
-                    && drag.value.as_ref().type_id() == TypeId::of::<T>()
+                    && dragged_value(drag.value.as_ref(), TypeId::of::<T>()).is_some()
                 {
                     (listener)(
                         &DragMoveEvent {
@@ -517,6 +533,24 @@ impl Interactivity {
         ));
     }
 
+    /// On drag initiation, this callback will be used to create a drag of data out of the window,
+    /// to be dropped into another application. See [`Window::start_native_drag`].
+    /// The imperative API equivalent to [`StatefulInteractiveElement::on_native_drag`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_native_drag(
+        &mut self,
+        listener: impl Fn(Point<Pixels>, &mut Window, &mut App) -> NativeDrag + 'static,
+    ) where
+        Self: Sized,
+    {
+        debug_assert!(
+            self.native_drag_listener.is_none(),
+            "calling on_native_drag more than once on the same element is not supported"
+        );
+        self.native_drag_listener = Some(Box::new(listener));
+    }
+
     /// Bind the given callback on the hover start and end events of this element. Note that the boolean
     /// passed to the callback is true when the hover starts and false when it ends.
     /// The imperative API equivalent to [`StatefulInteractiveElement::on_hover`].
@@ -653,6 +687,28 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Designate this div as a focus trap, such as for the content of a modal. While it is painted,
+    /// [`Window::focus_next`] and [`Window::focus_prev`] cycle through the tab stops inside it, and
+    /// focus moves into it from anywhere else. When it is no longer painted, focus is restored to
+    /// the element that was focused when it first appeared. This should be used in conjunction
+    /// with `track_focus`, whose handle identifies the trap between frames.
+    fn focus_trap(mut self) -> Self {
+        self.interactivity().focus_trap = true;
+        self
+    }
+
+    /// Set the element to focus from this one with [`Window::focus_in_direction`], instead of the
+    /// nearest tab stop in that direction. Has no effect unless this element tracks focus.
+    fn focus_neighbor(mut self, direction: Direction, neighbor: &FocusHandle) -> Self {
+        self.interactivity()
+            .focus_neighbors
+            .retain(|(neighbor_direction, _)| *neighbor_direction != direction);
+        self.interactivity()
+            .focus_neighbors
+            .push((direction, neighbor.clone()));
+        self
+    }
+
     /// Set the keymap context for this element. This will be used to determine
     /// which action to dispatch from the keymap.
     fn key_context<C, E>(mut self, key_context: C) -> Self
@@ -1155,6 +1211,23 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// On drag initiation, this callback will be used to create a drag of data out of the window,
+    /// to be dropped into another application. See [`Window::start_native_drag`].
+    /// The callback also has access to the offset of triggering click from the origin of parent element.
+    /// The fluent API equivalent to [`Interactivity::on_native_drag`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_native_drag(
+        mut self,
+        listener: impl Fn(Point<Pixels>, &mut Window, &mut App) -> NativeDrag + 'static,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.interactivity().on_native_drag(listener);
+        self
+    }
+
     /// Bind the given callback on the hover start and end events of this element. Note that the boolean
     /// passed to the callback is true when the hover starts and false when it ends.
     /// The fluent API equivalent to [`Interactivity::on_hover`].
@@ -1209,6 +1282,9 @@ pub(crate) type ClickListener = Rc<dyn Fn(&ClickEvent, &mut Window, &mut App) +
 pub(crate) type DragListener =
     Box<dyn Fn(&dyn Any, Point<Pixels>, &mut Window, &mut App) -> AnyView + 'static>;
 
+pub(crate) type NativeDragListener =
+    Box<dyn Fn(Point<Pixels>, &mut Window, &mut App) -> NativeDrag + 'static>;
+
 type DropListener = Box<dyn Fn(&dyn Any, &mut Window, &mut App) + 'static>;
 
 type CanDropPredicate = Box<dyn Fn(&dyn Any, &mut Window, &mut App) -> bool + 'static>;
@@ -1531,6 +1607,7 @@ pub struct Interactivity {
     pub(crate) can_drop_predicate: Option<CanDropPredicate>,
     pub(crate) click_listeners: Vec<ClickListener>,
     pub(crate) drag_listener: Option<(Arc<dyn Any>, DragListener)>,
+    pub(crate) native_drag_listener: Option<NativeDragListener>,
     pub(crate) hover_listener: Option<Box<dyn Fn(&bool, &mut Window, &mut App)>>,
     pub(crate) tooltip_builder: Option<TooltipBuilder>,
     pub(crate) window_control: Option<WindowControlArea>,
@@ -1538,6 +1615,8 @@ pub struct Interactivity {
     pub(crate) tab_index: Option<isize>,
     pub(crate) tab_group: bool,
     pub(crate) tab_stop: bool,
+    pub(crate) focus_neighbors: FocusNeighbors,
+    pub(crate) focus_trap: bool,
 
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub(crate) source_location: Option<&'static core::panic::Location<'static>>,
@@ -1719,6 +1798,7 @@ impl Interactivity {
             || !self.click_listeners.is_empty()
             || !self.scroll_wheel_listeners.is_empty()
             || self.drag_listener.is_some()
+            || self.native_drag_listener.is_some()
             || !self.drop_listeners.is_empty()
             || self.tooltip_builder.is_some()
             || window.is_inspector_picking(cx)
@@ -1827,8 +1907,18 @@ impl Interactivity {
                 if self.tab_group {
                     tab_group = self.tab_index;
                 }
+                if self.focus_trap {
+                    let trap_id = self.tracked_focus_handle.as_ref().map(|handle| handle.id);
+                    window.next_frame.tab_stops.begin_trap(trap_id);
+                }
                 if let Some(focus_handle) = &self.tracked_focus_handle {
                     window.next_frame.tab_stops.insert(focus_handle);
+                    let visible_bounds = bounds.intersect(&window.content_mask().bounds);
+                    window.next_frame.tab_stops.place(
+                        focus_handle.id,
+                        visible_bounds,
+                        self.focus_neighbors.clone(),
+                    );
                 }
 
                 window.with_element_opacity(style.opacity, |window| {
@@ -1896,6 +1986,10 @@ impl Interactivity {
                     });
                 });
 
+                if self.focus_trap {
+                    window.next_frame.tab_stops.end_group();
+                }
+
                 ((), element_state)
             },
         );
@@ -2095,6 +2189,7 @@ impl Interactivity {
         let drag_cursor_style = self.base_style.as_ref().mouse_cursor;
 
         let mut drag_listener = mem::take(&mut self.drag_listener);
+        let mut native_drag_listener = mem::take(&mut self.native_drag_listener);
         let drop_listeners = mem::take(&mut self.drop_listeners);
         let click_listeners = mem::take(&mut self.click_listeners);
         let can_drop_predicate = mem::take(&mut self.can_drop_predicate);
@@ -2107,24 +2202,25 @@ impl Interactivity {
                         && phase == DispatchPhase::Bubble
                         && hitbox.is_hovered(window)
                     {
-                        let drag_state_type = drag.value.as_ref().type_id();
+                        let dragged = drag.value.clone();
                         for (drop_state_type, listener) in &drop_listeners {
-                            if *drop_state_type == drag_state_type {
-                                let drag = cx
-                                    .active_drag
+                            if let Some(value) = dragged_value(dragged.as_ref(), *drop_state_type) {
+                                cx.active_drag
                                     .take()
                                     .expect("checked for type drag state type above");
 
                                 let mut can_drop = true;
                                 if let Some(predicate) = &can_drop_predicate {
-                                    can_drop = predicate(drag.value.as_ref(), window, cx);
+                                    can_drop = predicate(value, window, cx);
                                 }
 
                                 if can_drop {
-                                    listener(drag.value.as_ref(), window, cx);
+                                    listener(value, window, cx);
                                     window.refresh();
                                     cx.stop_propagation();
                                 }
+                                // A drop with files matches listeners for both of its types
+                                break;
                             }
                         }
                     }
@@ -2133,7 +2229,10 @@ impl Interactivity {
         }
 
         if let Some(element_state) = element_state {
-            if !click_listeners.is_empty() || drag_listener.is_some() {
+            if !click_listeners.is_empty()
+                || drag_listener.is_some()
+                || native_drag_listener.is_some()
+            {
                 let pending_mouse_down = element_state
                     .pending_mouse_down
                     .get_or_insert_with(Default::default)
@@ -2170,18 +2269,23 @@ impl Interactivity {
                         if let Some(mouse_down) = pending_mouse_down.clone()
                             && !cx.has_active_drag()
                             && (event.position - mouse_down.position).magnitude() > DRAG_THRESHOLD
-                            && let Some((drag_value, drag_listener)) = drag_listener.take()
+                            && (drag_listener.is_some() || native_drag_listener.is_some())
                         {
                             *clicked_state.borrow_mut() = ElementClickedState::default();
                             let cursor_offset = event.position - hitbox.origin;
-                            let drag =
-                                (drag_listener)(drag_value.as_ref(), cursor_offset, window, cx);
-                            cx.active_drag = Some(AnyDrag {
-                                view: drag,
-                                value: drag_value,
-                                cursor_offset,
-                                cursor_style: drag_cursor_style,
-                            });
+                            if let Some((drag_value, drag_listener)) = drag_listener.take() {
+                                let drag =
+                                    (drag_listener)(drag_value.as_ref(), cursor_offset, window, cx);
+                                cx.active_drag = Some(AnyDrag {
+                                    view: drag,
+                                    value: drag_value,
+                                    cursor_offset,
+                                    cursor_style: drag_cursor_style,
+                                });
+                            } else if let Some(native_drag_listener) = native_drag_listener.take() {
+                                let drag = (native_drag_listener)(cursor_offset, window, cx);
+                                window.start_native_drag(drag, cx);
+                            }
                             pending_mouse_down.take();
                             window.refresh();
                             cx.stop_propagation();
@@ -2531,27 +2635,28 @@ impl Interactivity {
             }
 
             if let Some(drag) = cx.active_drag.take() {
-                let mut can_drop = true;
-                if let Some(can_drop_predicate) = &self.can_drop_predicate {
-                    can_drop = can_drop_predicate(drag.value.as_ref(), window, cx);
-                }
-
-                if can_drop {
-                    for (state_type, group_drag_style) in &self.group_drag_over_styles {
-                        if let Some(group_hitbox_id) =
-                            GroupHitboxes::get(&group_drag_style.group, cx)
-                            && *state_type == drag.value.as_ref().type_id()
-                            && group_hitbox_id.is_hovered(window)
-                        {
-                            style.refine(&group_drag_style.style);
-                        }
+                let can_drop = |value: &dyn Any, window: &mut Window, cx: &mut App| {
+                    self.can_drop_predicate
+                        .as_ref()
+                        .is_none_or(|predicate| predicate(value, window, cx))
+                };
+
+                for (state_type, group_drag_style) in &self.group_drag_over_styles {
+                    if let Some(group_hitbox_id) = GroupHitboxes::get(&group_drag_style.group, cx)
+                        && let Some(value) = dragged_value(drag.value.as_ref(), *state_type)
+                        && group_hitbox_id.is_hovered(window)
+                        && can_drop(value, window, cx)
+                    {
+                        style.refine(&group_drag_style.style);
                     }
+                }
 
-                    for (state_type, build_drag_over_style) in &self.drag_over_styles {
-                        if *state_type == drag.value.as_ref().type_id() && hitbox.is_hovered(window)
-                        {
-                            style.refine(&build_drag_over_style(drag.value.as_ref(), window, cx));
-                        }
+                for (state_type, build_drag_over_style) in &self.drag_over_styles {
+                    if let Some(value) = dragged_value(drag.value.as_ref(), *state_type)
+                        && hitbox.is_hovered(window)
+                        && can_drop(value, window, cx)
+                    {
+                        style.refine(&build_drag_over_style(value, window, cx));
                     }
                 }
 
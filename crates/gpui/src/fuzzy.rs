@@ -0,0 +1,535 @@
+use std::{
+    cmp::Ordering,
+    ops::Range,
+    sync::{
+        Arc,
+        atomic::{self, AtomicBool},
+    },
+};
+
+use util::rel_path::RelPath;
+
+use crate::{BackgroundExecutor, SharedString};
+
+const MATCH_SCORE: f64 = 1.0;
+const CONSECUTIVE_BONUS: f64 = 0.75;
+const WORD_START_BONUS: f64 = 0.6;
+const COMPONENT_START_BONUS: f64 = 0.8;
+const FILE_NAME_BONUS: f64 = 0.2;
+const GAP_PENALTY: f64 = 0.05;
+const MAX_CHAR_SCORE: f64 =
+    MATCH_SCORE + CONSECUTIVE_BONUS + COMPONENT_START_BONUS + FILE_NAME_BONUS;
+
+/// A string to match with [`match_strings`], e.g. an action name from
+/// [`App::all_action_names`](crate::App::all_action_names) in a command palette.
+#[derive(Clone, Debug)]
+pub struct StringMatchCandidate {
+    /// An identifier for the candidate, returned with its match.
+    pub id: usize,
+    /// The string to match the query against.
+    pub string: SharedString,
+}
+
+impl StringMatchCandidate {
+    /// Create a candidate with the given id and string.
+    pub fn new(id: usize, string: impl Into<SharedString>) -> Self {
+        Self {
+            id,
+            string: string.into(),
+        }
+    }
+}
+
+/// A candidate matched by [`match_strings`].
+#[derive(Clone, Debug)]
+pub struct StringMatch {
+    /// The id of the matched candidate.
+    pub candidate_id: usize,
+    /// How well the candidate matched, between 0 and 1.
+    pub score: f64,
+    /// The byte offsets of the characters matching the query.
+    pub positions: Vec<usize>,
+    /// The matched string.
+    pub string: SharedString,
+}
+
+impl StringMatch {
+    /// Returns the byte ranges of the matched characters, e.g. to highlight them with
+    /// [`StyledText::with_highlights`](crate::StyledText::with_highlights).
+    pub fn ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        match_ranges(&self.string, &self.positions)
+    }
+}
+
+impl PartialEq for StringMatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for StringMatch {}
+
+impl PartialOrd for StringMatch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Better matches are greater: those with higher scores, then those with shorter strings.
+impl Ord for StringMatch {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.string.len().cmp(&self.string.len()))
+            .then_with(|| other.candidate_id.cmp(&self.candidate_id))
+    }
+}
+
+/// A path to match with [`match_paths`], e.g. a file in a picker.
+#[derive(Clone, Debug)]
+pub struct PathMatchCandidate {
+    /// An identifier for the candidate, returned with its match.
+    pub id: usize,
+    /// The path to match the query against.
+    pub path: Arc<RelPath>,
+}
+
+/// A candidate matched by [`match_paths`].
+#[derive(Clone, Debug)]
+pub struct PathMatch {
+    /// The id of the matched candidate.
+    pub candidate_id: usize,
+    /// How well the candidate matched, between 0 and 1.
+    pub score: f64,
+    /// The byte offsets of the characters matching the query, in the path's
+    /// [unix form](RelPath::as_unix_str).
+    pub positions: Vec<usize>,
+    /// The matched path.
+    pub path: Arc<RelPath>,
+}
+
+impl PathMatch {
+    /// Returns the byte ranges of the matched characters in the path's
+    /// [unix form](RelPath::as_unix_str), e.g. to highlight them with
+    /// [`StyledText::with_highlights`](crate::StyledText::with_highlights).
+    pub fn ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        match_ranges(self.path.as_unix_str(), &self.positions)
+    }
+}
+
+impl PartialEq for PathMatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for PathMatch {}
+
+impl PartialOrd for PathMatch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Better matches are greater: those with higher scores, then those with shorter paths.
+impl Ord for PathMatch {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.path.len().cmp(&self.path.len()))
+            .then_with(|| other.candidate_id.cmp(&self.candidate_id))
+    }
+}
+
+/// Fuzzy match the query against the candidates on the background executor, returning the best
+/// `max_results` matches, best first.
+///
+/// The query's characters have to appear in the candidate in order, scoring higher when they're
+/// contiguous or at the start of words, including camel case humps. The query is matched case
+/// insensitively unless it contains an uppercase character. Setting the cancel flag stops the
+/// matching early, returning no matches.
+pub async fn match_strings(
+    candidates: &[StringMatchCandidate],
+    query: &str,
+    max_results: usize,
+    cancel_flag: &AtomicBool,
+    executor: &BackgroundExecutor,
+) -> Vec<StringMatch> {
+    let query = Query::new(query);
+    match_candidates(
+        candidates,
+        max_results,
+        cancel_flag,
+        executor,
+        |matcher, candidate| {
+            let (score, positions) = matcher.match_string(&query, &candidate.string, None)?;
+            Some(StringMatch {
+                candidate_id: candidate.id,
+                score,
+                positions,
+                string: candidate.string.clone(),
+            })
+        },
+    )
+    .await
+}
+
+/// Fuzzy match the query against the paths like [`match_strings`], additionally scoring matches
+/// at the start of path components and in the file name higher.
+pub async fn match_paths(
+    candidates: &[PathMatchCandidate],
+    query: &str,
+    max_results: usize,
+    cancel_flag: &AtomicBool,
+    executor: &BackgroundExecutor,
+) -> Vec<PathMatch> {
+    let query = Query::new(query);
+    match_candidates(
+        candidates,
+        max_results,
+        cancel_flag,
+        executor,
+        |matcher, candidate| {
+            let path = candidate.path.as_unix_str();
+            let file_name_start = path.len() - candidate.path.file_name().unwrap_or("").len();
+            let (score, positions) = matcher.match_string(&query, path, Some(file_name_start))?;
+            Some(PathMatch {
+                candidate_id: candidate.id,
+                score,
+                positions,
+                path: candidate.path.clone(),
+            })
+        },
+    )
+    .await
+}
+
+/// Matches the candidates in a segment per CPU, keeping the best matches of each.
+async fn match_candidates<C: Sync, M: Ord + Send>(
+    candidates: &[C],
+    max_results: usize,
+    cancel_flag: &AtomicBool,
+    executor: &BackgroundExecutor,
+    match_candidate: impl Fn(&mut Matcher, &C) -> Option<M> + Sync,
+) -> Vec<M> {
+    if candidates.is_empty() || max_results == 0 {
+        return Vec::new();
+    }
+
+    let segment_size = candidates.len().div_ceil(executor.num_cpus());
+    let segments = candidates.chunks(segment_size).collect::<Vec<_>>();
+    let mut segment_results = segments.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+    executor
+        .scoped(|scope| {
+            for (segment, results) in segments.iter().zip(&mut segment_results) {
+                let match_candidate = &match_candidate;
+                scope.spawn(async move {
+                    let mut matcher = Matcher::default();
+                    for candidate in segment.iter() {
+                        if cancel_flag.load(atomic::Ordering::Relaxed) {
+                            return;
+                        }
+                        results.extend(match_candidate(&mut matcher, candidate));
+                    }
+                    sort_and_truncate(results, max_results);
+                });
+            }
+        })
+        .await;
+
+    if cancel_flag.load(atomic::Ordering::Relaxed) {
+        return Vec::new();
+    }
+    let mut results = segment_results.into_iter().flatten().collect::<Vec<_>>();
+    sort_and_truncate(&mut results, max_results);
+    results
+}
+
+fn sort_and_truncate<M: Ord>(results: &mut Vec<M>, max_results: usize) {
+    results.sort_unstable_by(|a, b| b.cmp(a));
+    results.truncate(max_results);
+}
+
+fn match_ranges<'a>(
+    string: &'a str,
+    positions: &'a [usize],
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    let mut positions = positions.iter().peekable();
+    std::iter::from_fn(move || {
+        let start = *positions.next()?;
+        let mut end = start + char_len(string, start);
+        while let Some(&&next) = positions.peek()
+            && next == end
+        {
+            end += char_len(string, next);
+            positions.next();
+        }
+        Some(start..end)
+    })
+}
+
+fn char_len(string: &str, offset: usize) -> usize {
+    string[offset..].chars().next().map_or(0, char::len_utf8)
+}
+
+struct Query {
+    chars: Vec<char>,
+    lowercase_chars: Vec<char>,
+    case_sensitive: bool,
+}
+
+impl Query {
+    fn new(query: &str) -> Self {
+        let chars = query.chars().collect::<Vec<_>>();
+        Self {
+            lowercase_chars: chars.iter().map(|&c| to_lowercase(c)).collect(),
+            case_sensitive: chars.iter().any(|c| c.is_uppercase()),
+            chars,
+        }
+    }
+
+    fn matches(&self, query_ix: usize, c: char, lowercase_c: char) -> bool {
+        if self.case_sensitive {
+            self.chars[query_ix] == c
+        } else {
+            self.lowercase_chars[query_ix] == lowercase_c
+        }
+    }
+}
+
+fn to_lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The buffers for matching candidates, reused between them.
+#[derive(Default)]
+struct Matcher {
+    chars: Vec<(usize, char, char)>,
+    bonuses: Vec<f64>,
+    scores: Vec<f64>,
+    previous: Vec<usize>,
+}
+
+impl Matcher {
+    /// Finds the best way to match the query in the string, returning its score and the byte
+    /// offsets of the matched characters. Paths pass where their file name starts, to score the
+    /// start of components and matches in the file name higher.
+    fn match_string(
+        &mut self,
+        query: &Query,
+        string: &str,
+        file_name_start: Option<usize>,
+    ) -> Option<(f64, Vec<usize>)> {
+        if query.chars.is_empty() {
+            return Some((0., Vec::new()));
+        }
+
+        self.chars.clear();
+        self.chars.extend(
+            string
+                .char_indices()
+                .map(|(ix, c)| (ix, c, to_lowercase(c))),
+        );
+        if !self.is_subsequence(query) {
+            return None;
+        }
+
+        self.bonuses.clear();
+        let mut previous_char = None;
+        for &(offset, c, _) in &self.chars {
+            let mut bonus = match previous_char {
+                None | Some('/') if file_name_start.is_some() => COMPONENT_START_BONUS,
+                None => WORD_START_BONUS,
+                Some(previous) if !previous.is_alphanumeric() && c.is_alphanumeric() => {
+                    WORD_START_BONUS
+                }
+                Some(previous) if previous.is_lowercase() && c.is_uppercase() => WORD_START_BONUS,
+                _ => 0.,
+            };
+            if file_name_start.is_some_and(|start| offset >= start) {
+                bonus += FILE_NAME_BONUS;
+            }
+            self.bonuses.push(bonus);
+            previous_char = Some(c);
+        }
+
+        // scores[i * len + j] is the best score for matching the first i + 1 query characters
+        // with the last one at character j, and previous[i * len + j] where the one before it is.
+        let len = self.chars.len();
+        self.scores.clear();
+        self.scores
+            .resize(query.chars.len() * len, f64::NEG_INFINITY);
+        self.previous.clear();
+        self.previous.resize(query.chars.len() * len, 0);
+
+        for (j, &(_, c, lowercase_c)) in self.chars.iter().enumerate() {
+            if query.matches(0, c, lowercase_c) {
+                self.scores[j] = MATCH_SCORE + self.bonuses[j];
+            }
+        }
+        for i in 1..query.chars.len() {
+            let (done, rest) = self.scores.split_at_mut(i * len);
+            let previous_scores = &done[(i - 1) * len..];
+            let scores = &mut rest[..len];
+            // The best previous score for a gapped match, adjusted so that the gap penalty only
+            // depends on the current position: score - (j - k - 1) * GAP = best - (j - 1) * GAP.
+            let mut best_gapped = (f64::NEG_INFINITY, 0);
+            for j in i..len {
+                if j >= 2 {
+                    let k = j - 2;
+                    let adjusted = previous_scores[k] + k as f64 * GAP_PENALTY;
+                    if adjusted > best_gapped.0 {
+                        best_gapped = (adjusted, k);
+                    }
+                }
+
+                let (_, c, lowercase_c) = self.chars[j];
+                if !query.matches(i, c, lowercase_c) {
+                    continue;
+                }
+                let gapped = best_gapped.0 - (j - 1) as f64 * GAP_PENALTY;
+                let consecutive = previous_scores[j - 1] + CONSECUTIVE_BONUS;
+                let (score, previous) = if consecutive >= gapped {
+                    (consecutive, j - 1)
+                } else {
+                    (gapped, best_gapped.1)
+                };
+                if score.is_finite() {
+                    scores[j] = score + MATCH_SCORE + self.bonuses[j];
+                    self.previous[i * len + j] = previous;
+                }
+            }
+        }
+
+        let last_row = (query.chars.len() - 1) * len;
+        let (mut j, score) = self.scores[last_row..]
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        if !score.is_finite() {
+            return None;
+        }
+
+        let mut positions = vec![0; query.chars.len()];
+        for i in (0..query.chars.len()).rev() {
+            positions[i] = self.chars[j].0;
+            j = self.previous[i * len + j];
+        }
+        let score = score / (query.chars.len() as f64 * MAX_CHAR_SCORE);
+        Some((score.clamp(0., 1.), positions))
+    }
+
+    fn is_subsequence(&self, query: &Query) -> bool {
+        let mut chars = self.chars.iter();
+        (0..query.chars.len())
+            .all(|i| chars.any(|&(_, c, lowercase_c)| query.matches(i, c, lowercase_c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestAppContext;
+
+    #[gpui::test]
+    async fn test_match_strings(cx: &mut TestAppContext) {
+        let candidates = [
+            "Save All",
+            "Save",
+            "Select All",
+            "Toggle Sidebar",
+            "Übersicht",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, string)| StringMatchCandidate::new(id, string))
+        .collect::<Vec<_>>();
+        let cancel_flag = AtomicBool::new(false);
+        let executor = cx.executor();
+        let strings = |matches: &[StringMatch]| {
+            matches
+                .iter()
+                .map(|m| m.string.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let matches = match_strings(&candidates, "sa", 10, &cancel_flag, &executor).await;
+        assert_eq!(
+            strings(&matches),
+            ["Save", "Save All", "Select All", "Toggle Sidebar"]
+        );
+        assert_eq!(matches[0].positions, [0, 1]);
+        assert_eq!(matches[0].ranges().collect::<Vec<_>>(), [0..2]);
+        assert_eq!(matches[2].positions, [0, 7]);
+        assert_eq!(matches[2].ranges().collect::<Vec<_>>(), [0..1, 7..8]);
+
+        // Uppercase characters in the query make it case sensitive.
+        let matches = match_strings(&candidates, "SA", 10, &cancel_flag, &executor).await;
+        assert_eq!(strings(&matches), ["Save All", "Select All"]);
+
+        let matches = match_strings(&candidates, "tsb", 1, &cancel_flag, &executor).await;
+        assert_eq!(strings(&matches), ["Toggle Sidebar"]);
+        assert_eq!(matches[0].positions, [0, 7, 11]);
+
+        let matches = match_strings(&candidates, "übs", 10, &cancel_flag, &executor).await;
+        assert_eq!(strings(&matches), ["Übersicht"]);
+        assert_eq!(matches[0].ranges().collect::<Vec<_>>(), [0..3, 5..6]);
+
+        let matches = match_strings(&candidates, "", 2, &cancel_flag, &executor).await;
+        assert_eq!(strings(&matches), ["Save", "Save All"]);
+
+        cancel_flag.store(true, atomic::Ordering::Relaxed);
+        let matches = match_strings(&candidates, "sa", 10, &cancel_flag, &executor).await;
+        assert!(matches.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_match_paths(cx: &mut TestAppContext) {
+        let candidates = [
+            "src/editor/mod.rs",
+            "crates/editor/src/element.rs",
+            "src/model.rs",
+            "editor/src/mode.rs",
+            "model/src/lib.rs",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, path)| PathMatchCandidate {
+            id,
+            path: Arc::from(RelPath::unix(path).unwrap()),
+        })
+        .collect::<Vec<_>>();
+        let cancel_flag = AtomicBool::new(false);
+        let executor = cx.executor();
+        let paths = |matches: &[PathMatch]| {
+            matches
+                .iter()
+                .map(|m| m.path.as_unix_str().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Matches in the file name beat those in its directories.
+        let matches = match_paths(&candidates, "mod", 10, &cancel_flag, &executor).await;
+        assert_eq!(
+            paths(&matches),
+            [
+                "src/model.rs",
+                "src/editor/mod.rs",
+                "editor/src/mode.rs",
+                "model/src/lib.rs"
+            ]
+        );
+
+        // Matches at the start of components beat those within them.
+        let matches = match_paths(&candidates, "edel", 10, &cancel_flag, &executor).await;
+        assert_eq!(paths(&matches), ["crates/editor/src/element.rs"]);
+        assert_eq!(matches[0].ranges().collect::<Vec<_>>(), [7..9, 18..20]);
+
+        let matches = match_paths(&candidates, "e/s/m", 10, &cancel_flag, &executor).await;
+        assert_eq!(
+            paths(&matches),
+            ["editor/src/mode.rs", "crates/editor/src/element.rs"]
+        );
+    }
+}
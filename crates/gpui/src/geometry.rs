@@ -39,6 +39,29 @@ impl Axis {
     }
 }
 
+/// Direction in a 2D cartesian space, e.g. for moving focus with arrow keys.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Direction {
+    /// Towards the top
+    Up,
+    /// Towards the bottom
+    Down,
+    /// Towards the left
+    Left,
+    /// Towards the right
+    Right,
+}
+
+impl Direction {
+    /// The axis this direction is along.
+    pub fn axis(self) -> Axis {
+        match self {
+            Direction::Up | Direction::Down => Axis::Vertical,
+            Direction::Left | Direction::Right => Axis::Horizontal,
+        }
+    }
+}
+
 /// A trait for accessing the given unit along a certain axis.
 pub trait Along {
     /// The unit associated with this type
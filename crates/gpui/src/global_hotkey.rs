@@ -0,0 +1,62 @@
+use std::sync::atomic::{AtomicU64, Ordering::SeqCst};
+
+/// A unique identifier for a hotkey registered with [`App::register_global_hotkey`].
+///
+/// [`App::register_global_hotkey`]: crate::App::register_global_hotkey
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct GlobalHotkeyId(pub u64);
+
+impl GlobalHotkeyId {
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, SeqCst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{Keystroke, TestAppContext};
+
+    actions!(test_only, [QuickCapture, ShowWindow]);
+
+    #[gpui::test]
+    fn test_global_hotkey(cx: &mut TestAppContext) {
+        let dispatched = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            cx.on_action({
+                let dispatched = dispatched.clone();
+                move |_: &QuickCapture, _| dispatched.borrow_mut().push("quick capture")
+            });
+            cx.on_action({
+                let dispatched = dispatched.clone();
+                move |_: &ShowWindow, _| dispatched.borrow_mut().push("show window")
+            });
+        });
+
+        let quick_capture = cx
+            .update(|cx| cx.register_global_hotkey("ctrl-shift-space", QuickCapture))
+            .unwrap();
+        cx.update(|cx| cx.register_global_hotkey("super-g", ShowWindow))
+            .unwrap();
+        assert!(
+            cx.update(|cx| cx.register_global_hotkey("ctrl-a-b", ShowWindow))
+                .is_err()
+        );
+        assert_eq!(
+            cx.global_hotkeys(),
+            [
+                Keystroke::parse("ctrl-shift-space").unwrap(),
+                Keystroke::parse("super-g").unwrap()
+            ]
+        );
+
+        cx.simulate_global_hotkey("ctrl-shift-space");
+        cx.simulate_global_hotkey("super-g");
+        assert_eq!(*dispatched.borrow(), ["quick capture", "show window"]);
+
+        cx.update(|cx| cx.unregister_global_hotkey(quick_capture));
+        assert_eq!(cx.global_hotkeys(), [Keystroke::parse("super-g").unwrap()]);
+    }
+}
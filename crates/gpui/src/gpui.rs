@@ -20,13 +20,17 @@ pub mod colors;
 mod element;
 mod elements;
 mod executor;
+mod fuzzy;
 mod geometry;
 mod global;
+mod global_hotkey;
 mod input;
 mod inspector;
 mod interactive;
 mod key_dispatch;
 mod keymap;
+mod native_drag;
+mod notification;
 mod path_builder;
 mod platform;
 pub mod prelude;
@@ -80,15 +84,23 @@ pub use ctor::ctor;
 pub use element::*;
 pub use elements::*;
 pub use executor::*;
+pub use fuzzy::*;
 pub use geometry::*;
 pub use global::*;
+pub use global_hotkey::*;
 pub use gpui_macros::{AppContext, IntoElement, Render, VisualContext, register_action, test};
 pub use http_client;
 pub use input::*;
 pub use inspector::*;
 pub use interactive::*;
 use key_dispatch::*;
+pub use key_dispatch::{
+    DISPATCH_TRACE_LOG_TARGET, DispatchListenerKind, DispatchTrace, DispatchTraceListener,
+    DispatchTraceNode,
+};
 pub use keymap::*;
+pub use native_drag::*;
+pub use notification::*;
 pub use path_builder::*;
 pub use platform::*;
 pub use profiler::*;
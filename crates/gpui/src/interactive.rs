@@ -1,6 +1,6 @@
 use crate::{
-    Bounds, Capslock, Context, Empty, IntoElement, Keystroke, Modifiers, Pixels, Point, Render,
-    Window, point, seal::Sealed,
+    Bounds, Capslock, Context, Empty, Image, IntoElement, Keystroke, Modifiers, Pixels, Point,
+    Render, Window, point, seal::Sealed,
 };
 use smallvec::SmallVec;
 use std::{any::Any, fmt::Debug, ops::Deref, path::PathBuf};
@@ -508,6 +508,33 @@ impl Deref for MouseExitEvent {
     }
 }
 
+/// An unaccelerated relative mouse motion event from the platform, only generated while the
+/// pointer is locked or confined, see [`Window::lock_pointer`](crate::Window::lock_pointer).
+#[derive(Clone, Debug, Default)]
+pub struct RawMouseMotionEvent {
+    /// The motion of the mouse since the previous event, without pointer acceleration.
+    pub delta: Point<Pixels>,
+
+    /// The modifiers that were held down when the mouse was moved.
+    pub modifiers: Modifiers,
+}
+
+impl Sealed for RawMouseMotionEvent {}
+impl InputEvent for RawMouseMotionEvent {
+    fn to_platform_input(self) -> PlatformInput {
+        PlatformInput::RawMouseMotion(self)
+    }
+}
+impl MouseEvent for RawMouseMotionEvent {}
+
+impl Deref for RawMouseMotionEvent {
+    type Target = Modifiers;
+
+    fn deref(&self) -> &Self::Target {
+        &self.modifiers
+    }
+}
+
 /// A collection of paths from the platform, such as from a file drop.
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct ExternalPaths(pub(crate) SmallVec<[PathBuf; 2]>);
@@ -526,27 +553,122 @@ impl Render for ExternalPaths {
     }
 }
 
-/// A file drop event from the platform, generated when files are dragged and dropped onto the window.
+/// The kinds of data a drop from another application may carry, see [`ExternalDrop::contains`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ExternalDropKind {
+    /// Files, dragged from a file manager.
+    Paths,
+    /// Plain text, such as a selection in another application.
+    Text,
+    /// Links, such as from the address bar of a browser.
+    Urls,
+    /// An HTML fragment, such as a selection in a browser.
+    Html,
+    /// An image.
+    Image,
+}
+
+/// Data dragged from another application onto a window: files, text, links, HTML or an image,
+/// and often several of them at once, e.g. a link from a browser also carries its text.
+///
+/// Elements receive it with `on_drop::<ExternalDrop>`, and can filter it by content with
+/// `can_drop`. Drops carrying files are also delivered to `on_drop::<ExternalPaths>` listeners,
+/// whose `can_drop` predicate is given the [`ExternalPaths`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExternalDrop {
+    pub(crate) paths: ExternalPaths,
+    pub(crate) text: Option<String>,
+    pub(crate) urls: Vec<String>,
+    pub(crate) html: Option<String>,
+    pub(crate) image: Option<Image>,
+}
+
+impl ExternalDrop {
+    /// The dropped files.
+    pub fn paths(&self) -> &[PathBuf] {
+        self.paths.paths()
+    }
+
+    /// The dropped text.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// The dropped links, other than to local files.
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// The dropped HTML fragment.
+    pub fn html(&self) -> Option<&str> {
+        self.html.as_deref()
+    }
+
+    /// The dropped image.
+    pub fn image(&self) -> Option<&Image> {
+        self.image.as_ref()
+    }
+
+    /// Whether the drop carries data of the given kind.
+    pub fn contains(&self, kind: ExternalDropKind) -> bool {
+        match kind {
+            ExternalDropKind::Paths => !self.paths.0.is_empty(),
+            ExternalDropKind::Text => self.text.is_some(),
+            ExternalDropKind::Urls => !self.urls.is_empty(),
+            ExternalDropKind::Html => self.html.is_some(),
+            ExternalDropKind::Image => self.image.is_some(),
+        }
+    }
+
+    /// Whether the drop carries no data the application understands.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl From<ExternalPaths> for ExternalDrop {
+    fn from(paths: ExternalPaths) -> Self {
+        Self {
+            paths,
+            ..Default::default()
+        }
+    }
+}
+
+impl Render for ExternalDrop {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        // the platform will render the dragged data
+        Empty
+    }
+}
+
+/// A drop event from the platform, generated when files or other data are dragged from another
+/// application and dropped onto the window.
 #[derive(Debug, Clone)]
 pub enum FileDropEvent {
-    /// The files have entered the window.
+    /// The data has entered the window.
+    ///
+    /// Platforms that only read the dragged data once it is dropped send this again, with the
+    /// data, right before [`FileDropEvent::Submit`].
     Entered {
         /// The position of the mouse relative to the window.
         position: Point<Pixels>,
         /// The paths of the files that are being dragged.
         paths: ExternalPaths,
+        /// The other data that is being dragged, such as text, links, HTML or an image.
+        data: ExternalDrop,
     },
-    /// The files are being dragged over the window
+    /// The data is being dragged over the window
     Pending {
         /// The position of the mouse relative to the window.
         position: Point<Pixels>,
     },
-    /// The files have been dropped onto the window.
+    /// The data has been dropped onto the window.
     Submit {
         /// The position of the mouse relative to the window.
         position: Point<Pixels>,
     },
-    /// The user has stopped dragging the files over the window.
+    /// The user has stopped dragging the data over the window.
     Exited,
 }
 
@@ -575,9 +697,11 @@ pub enum PlatformInput {
     MouseMove(MouseMoveEvent),
     /// The mouse exited the window.
     MouseExited(MouseExitEvent),
+    /// The mouse was moved while the pointer is locked or confined.
+    RawMouseMotion(RawMouseMotionEvent),
     /// The scroll wheel was used.
     ScrollWheel(ScrollWheelEvent),
-    /// Files were dragged and dropped onto the window.
+    /// Files or other data were dragged and dropped onto the window.
     FileDrop(FileDropEvent),
 }
 
@@ -591,6 +715,7 @@ impl PlatformInput {
             PlatformInput::MouseUp(event) => Some(event),
             PlatformInput::MouseMove(event) => Some(event),
             PlatformInput::MouseExited(event) => Some(event),
+            PlatformInput::RawMouseMotion(event) => Some(event),
             PlatformInput::ScrollWheel(event) => Some(event),
             PlatformInput::FileDrop(event) => Some(event),
         }
@@ -605,6 +730,7 @@ impl PlatformInput {
             PlatformInput::MouseUp(_) => None,
             PlatformInput::MouseMove(_) => None,
             PlatformInput::MouseExited(_) => None,
+            PlatformInput::RawMouseMotion(_) => None,
             PlatformInput::ScrollWheel(_) => None,
             PlatformInput::FileDrop(_) => None,
         }
@@ -613,10 +739,14 @@ impl PlatformInput {
 
 #[cfg(test)]
 mod test {
+    use std::{path::PathBuf, time::Duration};
 
     use crate::{
-        self as gpui, AppContext as _, Context, FocusHandle, InteractiveElement, IntoElement,
-        KeyBinding, Keystroke, ParentElement, Render, TestAppContext, Window, div,
+        self as gpui, AppContext as _, Context, DispatchListenerKind, DispatchPhase, ExternalDrop,
+        ExternalDropKind, ExternalPaths, FileDropEvent, FocusHandle, InteractiveElement,
+        IntoElement, KeyBinding, KeyContext, Keystroke, Modifiers, MouseButton, ParentElement,
+        PointerConstraint, RawMouseMotionEvent, Render, Styled as _, TestAppContext, Window,
+        canvas, deferred, div, point, px,
     };
 
     struct TestView {
@@ -683,4 +813,356 @@ mod test {
             })
             .unwrap();
     }
+
+    #[gpui::test]
+    fn test_dispatch_tracing(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| TestView {
+                    saw_key_down: false,
+                    saw_action: false,
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+
+        cx.update(|cx| {
+            cx.bind_keys(vec![KeyBinding::new("ctrl-g", TestAction, Some("parent"))]);
+        });
+
+        window
+            .update(cx, |test_view, window, _cx| {
+                window.focus(&test_view.focus_handle);
+                window.set_dispatch_tracing(true);
+            })
+            .unwrap();
+
+        cx.dispatch_keystroke(*window, Keystroke::parse("a").unwrap());
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-g").unwrap());
+
+        let traces = window
+            .update(cx, |_, window, _| window.take_dispatch_traces())
+            .unwrap();
+        assert_eq!(traces.len(), 2);
+
+        // The key listener on "parent" stops the unbound keystroke.
+        let trace = &traces[0];
+        assert_eq!(trace.input.len(), 1);
+        assert_eq!(trace.input[0].key, "a");
+        assert!(trace.bindings.is_empty());
+        assert!(!trace.pending);
+        let context_stack = &trace.path.last().unwrap().context_stack;
+        assert_eq!(
+            context_stack,
+            &[
+                KeyContext::parse("parent").unwrap(),
+                KeyContext::parse("nested").unwrap()
+            ]
+        );
+        let phases = trace
+            .listeners
+            .iter()
+            .map(|listener| (listener.kind, listener.phase, listener.stopped_propagation))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            phases,
+            [
+                (DispatchListenerKind::Key, DispatchPhase::Capture, false),
+                (DispatchListenerKind::Key, DispatchPhase::Bubble, true),
+            ]
+        );
+        let stopped_at = trace.listeners[1].stopped_at.unwrap();
+        assert!(stopped_at.file().ends_with("interactive.rs"));
+
+        // The bound action stops propagation by default in the bubble phase.
+        let trace = &traces[1];
+        assert_eq!(trace.bindings.len(), 1);
+        assert_eq!(trace.bindings[0].action().name(), "test_only::TestAction");
+        let phases = trace
+            .listeners
+            .iter()
+            .map(|listener| (listener.kind, listener.phase, listener.stopped_propagation))
+            .collect::<Vec<_>>();
+        let action = DispatchListenerKind::Action("test_only::TestAction");
+        assert_eq!(
+            phases,
+            [
+                (action, DispatchPhase::Capture, false),
+                (action, DispatchPhase::Bubble, true),
+            ]
+        );
+        assert_eq!(trace.listeners[1].stopped_at, None);
+        assert_eq!(trace.listeners[1].node, trace.listeners[0].node);
+    }
+
+    #[gpui::test]
+    fn test_pending_input(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| TestView {
+                    saw_key_down: false,
+                    saw_action: false,
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+
+        cx.update(|cx| {
+            cx.set_pending_input_timeout(Duration::from_millis(500));
+            cx.bind_keys(vec![
+                KeyBinding::new("ctrl-k", TestAction, Some("parent")),
+                KeyBinding::new("ctrl-k ctrl-c", TestAction, Some("parent"))
+                    .with_pending_timeout(Duration::from_secs(3)),
+                KeyBinding::new("ctrl-k ctrl-u", TestAction, Some("parent")),
+                KeyBinding::new("ctrl-k ctrl-x", TestAction, Some("other")),
+            ]);
+        });
+        window
+            .update(cx, |test_view, window, _| {
+                window.focus(&test_view.focus_handle)
+            })
+            .unwrap();
+
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-k").unwrap());
+        let pending = window
+            .update(cx, |_, window, _| window.pending_input())
+            .unwrap()
+            .unwrap();
+        assert_eq!(pending.keystrokes, [Keystroke::parse("ctrl-k").unwrap()]);
+        let bindings = pending
+            .bindings
+            .iter()
+            .map(|binding| {
+                binding
+                    .keystrokes()
+                    .iter()
+                    .map(|keystroke| keystroke.inner().unparse())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bindings, ["ctrl-k ctrl-u", "ctrl-k ctrl-c"]);
+        // The longest timeout of the bindings that could complete the input is used
+        assert_eq!(pending.timeout, Some(Duration::from_secs(3)));
+
+        cx.executor().advance_clock(Duration::from_secs(2));
+        window
+            .update(cx, |test_view, window, _| {
+                assert!(window.has_pending_keystrokes());
+                assert!(!test_view.saw_action);
+            })
+            .unwrap();
+
+        cx.executor().advance_clock(Duration::from_secs(1));
+        window
+            .update(cx, |test_view, window, _| {
+                assert!(window.pending_input().is_none());
+                assert!(test_view.saw_action);
+            })
+            .unwrap();
+    }
+
+    struct ModalView {
+        editor: FocusHandle,
+        modal: Option<[FocusHandle; 3]>,
+    }
+
+    impl Render for ModalView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .child(div().track_focus(&self.editor.clone().tab_stop(true)))
+                .children(self.modal.as_ref().map(|[modal, ok, cancel]| {
+                    deferred(
+                        div()
+                            .track_focus(modal)
+                            .focus_trap()
+                            .child(div().track_focus(&ok.clone().tab_stop(true)))
+                            .child(div().track_focus(&cancel.clone().tab_stop(true))),
+                    )
+                }))
+        }
+    }
+
+    #[gpui::test]
+    fn test_focus_trap(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, cx| ModalView {
+            editor: cx.focus_handle(),
+            modal: None,
+        });
+        let focused = |cx: &mut gpui::VisualTestContext| {
+            cx.update(|window, cx| window.focused(cx).map(|handle| handle.id))
+        };
+
+        let editor = view.read_with(cx, |view, _| view.editor.clone());
+        cx.update(|window, _| window.focus(&editor));
+        cx.run_until_parked();
+
+        let [modal, ok, cancel] = std::array::from_fn(|_| cx.update(|_, cx| cx.focus_handle()));
+        view.update_in(cx, |view, window, cx| {
+            view.modal = Some([modal.clone(), ok.clone(), cancel.clone()]);
+            window.focus(&modal);
+            cx.notify();
+        });
+        cx.run_until_parked();
+
+        // Tab cycles through the modal without reaching the editor behind it.
+        for expected in [&ok, &cancel, &ok] {
+            cx.update(|window, _| window.focus_next());
+            cx.run_until_parked();
+            assert_eq!(focused(cx), Some(expected.id));
+        }
+        cx.update(|window, _| window.focus_prev());
+        cx.run_until_parked();
+        assert_eq!(focused(cx), Some(cancel.id));
+
+        // Dismissing the modal restores focus to the editor.
+        view.update(cx, |view, cx| {
+            view.modal = None;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert_eq!(focused(cx), Some(editor.id));
+        cx.update(|window, _| window.focus_next());
+        assert_eq!(focused(cx), Some(editor.id));
+    }
+
+    #[derive(Default)]
+    struct DropView {
+        dropped_text: Vec<String>,
+        dropped_paths: Vec<ExternalPaths>,
+    }
+
+    impl Render for DropView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .flex()
+                .flex_col()
+                .child(
+                    div()
+                        .id("text")
+                        .size(px(50.))
+                        .can_drop(|value, _, _| {
+                            value
+                                .downcast_ref::<ExternalDrop>()
+                                .is_some_and(|drop| drop.contains(ExternalDropKind::Text))
+                        })
+                        .on_drop(cx.listener(|this, drop: &ExternalDrop, _, _| {
+                            this.dropped_text.push(drop.text().unwrap().to_string())
+                        })),
+                )
+                .child(
+                    div()
+                        .id("files")
+                        .size(px(50.))
+                        .can_drop(|value, _, _| {
+                            value.downcast_ref::<ExternalPaths>().is_some_and(|paths| {
+                                paths
+                                    .paths()
+                                    .iter()
+                                    .all(|path| path.extension().is_some_and(|ext| ext == "txt"))
+                            })
+                        })
+                        .on_drop(cx.listener(|this, paths: &ExternalPaths, _, _| {
+                            this.dropped_paths.push(paths.clone())
+                        })),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_external_drop(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| DropView::default());
+        let drop_at = |cx: &mut gpui::VisualTestContext, position, data: ExternalDrop| {
+            let position = point(px(10.), px(position));
+            cx.simulate_event(FileDropEvent::Entered {
+                position,
+                paths: data.paths.clone(),
+                data,
+            });
+            cx.simulate_event(FileDropEvent::Submit { position });
+        };
+        let text = ExternalDrop {
+            text: Some("hello".to_string()),
+            ..Default::default()
+        };
+        let paths = ExternalDrop::from(ExternalPaths(vec![PathBuf::from("/tmp/a.txt")].into()));
+        let image = ExternalDrop::from(ExternalPaths(vec![PathBuf::from("/tmp/b.png")].into()));
+
+        drop_at(cx, 10., text.clone());
+        // Text isn't delivered to listeners for files
+        drop_at(cx, 60., text.clone());
+        // Files aren't accepted by the element filtering for text
+        drop_at(cx, 10., paths.clone());
+        drop_at(cx, 60., paths.clone());
+        // The element filtering for text files is given the paths
+        drop_at(cx, 60., image);
+
+        // Data read once it's dropped replaces what was known when the drag entered
+        let position = point(px(10.), px(10.));
+        cx.simulate_event(FileDropEvent::Entered {
+            position,
+            paths: ExternalPaths::default(),
+            data: ExternalDrop::default(),
+        });
+        drop_at(cx, 10., text);
+
+        view.update(cx, |view, _| {
+            assert_eq!(view.dropped_text, ["hello", "hello"]);
+            assert_eq!(view.dropped_paths, [paths.paths]);
+        });
+    }
+
+    #[derive(Default)]
+    struct ScrubView {
+        value: f32,
+    }
+
+    impl Render for ScrubView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            let view = cx.entity();
+            div()
+                .size_full()
+                .on_mouse_down(MouseButton::Left, |_, window, _| window.lock_pointer())
+                .on_mouse_up(MouseButton::Left, |_, window, _| window.release_pointer())
+                .child(
+                    canvas(
+                        |_, _, _| {},
+                        move |_, _, window, _| {
+                            window.on_mouse_event(
+                                move |event: &RawMouseMotionEvent, phase, _, cx| {
+                                    if phase == DispatchPhase::Bubble {
+                                        view.update(cx, |view, _| view.value += event.delta.x.0);
+                                    }
+                                },
+                            );
+                        },
+                    )
+                    .size_full(),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_raw_mouse_motion(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| ScrubView::default());
+        let position = point(px(10.), px(10.));
+        let pointer_constraint =
+            |cx: &mut gpui::VisualTestContext| cx.update(|window, _| window.pointer_constraint());
+
+        cx.simulate_mouse_down(position, MouseButton::Left, Modifiers::none());
+        assert_eq!(pointer_constraint(cx), Some(PointerConstraint::Locked));
+        for delta in [5., -2.] {
+            cx.simulate_event(RawMouseMotionEvent {
+                delta: point(px(delta), px(1.)),
+                modifiers: Modifiers::none(),
+            });
+        }
+        cx.simulate_mouse_up(position, MouseButton::Left, Modifiers::none());
+        assert_eq!(pointer_constraint(cx), None);
+
+        view.update(cx, |view, _| assert_eq!(view.value, 3.));
+    }
 }
@@ -60,14 +60,24 @@ use std::{
     cell::RefCell,
     mem,
     ops::Range,
+    panic::Location,
     rc::Rc,
 };
 
+/// The log target that dispatch traces are logged to at the trace level, see [`DispatchTrace`].
+pub const DISPATCH_TRACE_LOG_TARGET: &str = "gpui::dispatch";
+
 /// ID of a node within `DispatchTree`. Note that these are **not** stable between frames, and so a
 /// `DispatchNodeId` should only be used with the `DispatchTree` that provided it.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub(crate) struct DispatchNodeId(usize);
 
+impl DispatchNodeId {
+    pub(crate) fn index(self) -> usize {
+        self.0
+    }
+}
+
 pub(crate) struct DispatchTree {
     node_stack: Vec<DispatchNodeId>,
     pub(crate) context_stack: Vec<KeyContext>,
@@ -127,6 +137,64 @@ pub(crate) struct DispatchResult {
     pub(crate) context_stack: Vec<KeyContext>,
 }
 
+/// A record of how a keystroke or action was dispatched in a window, for finding out why an action
+/// didn't run.
+///
+/// Traces are recorded while [`Window::set_dispatch_tracing`] is enabled, and logged to the
+/// [`DISPATCH_TRACE_LOG_TARGET`] while it's enabled at the trace level.
+#[derive(Clone, Debug, Default)]
+pub struct DispatchTrace {
+    /// The keystrokes matched against the keymap, including those pending from earlier
+    /// keystrokes. This is empty for actions dispatched without a keystroke.
+    pub input: Vec<Keystroke>,
+    /// The bindings matching the input, in precedence order.
+    pub bindings: Vec<KeyBinding>,
+    /// Whether the input was kept pending, waiting for the rest of a longer binding.
+    pub pending: bool,
+    /// The nodes of the dispatch path, from the root to the focused node.
+    pub path: Vec<DispatchTraceNode>,
+    /// The listeners that were invoked, in order.
+    pub listeners: Vec<DispatchTraceListener>,
+}
+
+/// A node on the dispatch path of a [`DispatchTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DispatchTraceNode {
+    /// The index of the node in the window's dispatch tree, which is only valid for the frame the
+    /// trace was recorded in.
+    pub index: usize,
+    /// The key contexts of the node and its ancestors that bindings are matched against.
+    pub context_stack: Vec<KeyContext>,
+}
+
+/// A listener invoked while dispatching, as recorded by a [`DispatchTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DispatchTraceListener {
+    /// What the listener was invoked for.
+    pub kind: DispatchListenerKind,
+    /// The phase the listener was invoked in.
+    pub phase: DispatchPhase,
+    /// The index of the node the listener is registered on, or `None` for the global action
+    /// listeners registered with [`App::on_action`].
+    pub node: Option<usize>,
+    /// Whether the listener stopped propagation, and with it the dispatch.
+    pub stopped_propagation: bool,
+    /// Where the listener called [`App::stop_propagation`]. Action listeners in the bubble phase
+    /// stop propagation unless they call [`App::propagate`], in which case this is `None`.
+    pub stopped_at: Option<&'static Location<'static>>,
+}
+
+/// The kind of a [`DispatchTraceListener`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DispatchListenerKind {
+    /// A key down or key up listener.
+    Key,
+    /// A modifiers changed listener.
+    ModifiersChanged,
+    /// A listener for the action with the given name.
+    Action(&'static str),
+}
+
 type KeyListener = Rc<dyn Fn(&dyn Any, DispatchPhase, &mut Window, &mut App)>;
 type ModifiersChangedListener = Rc<dyn Fn(&ModifiersChangedEvent, &mut Window, &mut App)>;
 
@@ -450,11 +518,7 @@ impl DispatchTree {
         input: &[Keystroke],
         dispatch_path: &SmallVec<[DispatchNodeId; 32]>,
     ) -> (SmallVec<[KeyBinding; 1]>, bool, Vec<KeyContext>) {
-        let context_stack: Vec<KeyContext> = dispatch_path
-            .iter()
-            .filter_map(|node_id| self.node(*node_id).context.clone())
-            .collect();
-
+        let context_stack = self.context_stack_for_path(dispatch_path);
         let (bindings, partial) = self
             .keymap
             .borrow()
@@ -549,6 +613,30 @@ impl DispatchTree {
         (input, to_replay)
     }
 
+    pub fn context_stack_for_path(&self, dispatch_path: &[DispatchNodeId]) -> Vec<KeyContext> {
+        dispatch_path
+            .iter()
+            .filter_map(|node_id| self.node(*node_id).context.clone())
+            .collect()
+    }
+
+    /// Returns the trace nodes for the dispatch path, with their context stacks.
+    pub fn trace_path(&self, dispatch_path: &[DispatchNodeId]) -> Vec<DispatchTraceNode> {
+        let mut context_stack = Vec::new();
+        dispatch_path
+            .iter()
+            .map(|node_id| {
+                if let Some(context) = self.node(*node_id).context.clone() {
+                    context_stack.push(context);
+                }
+                DispatchTraceNode {
+                    index: node_id.0,
+                    context_stack: context_stack.clone(),
+                }
+            })
+            .collect()
+    }
+
     pub fn dispatch_path(&self, target: DispatchNodeId) -> SmallVec<[DispatchNodeId; 32]> {
         let mut dispatch_path: SmallVec<[DispatchNodeId; 32]> = SmallVec::new();
         let mut current_node_id = Some(target);
@@ -1,13 +1,15 @@
 mod binding;
 mod context;
+mod file;
 
 pub use binding::*;
 pub use context::*;
+pub use file::*;
 
 use crate::{Action, AsKeystroke, Keystroke, is_no_action};
-use collections::{HashMap, HashSet};
+use collections::HashMap;
 use smallvec::SmallVec;
-use std::any::TypeId;
+use std::{any::TypeId, rc::Rc};
 
 /// An opaque identifier of which version of the keymap is currently active.
 /// The keymap's version is changed whenever bindings are added or removed.
@@ -27,6 +29,38 @@ pub struct Keymap {
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct BindingIndex(usize);
 
+/// A problem with the bindings of a keymap, found by [`Keymap::analyze`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KeymapIssue {
+    /// The binding has the same keystrokes, context and action as a binding added later, so it
+    /// has no effect.
+    Duplicate {
+        /// The binding that has no effect.
+        binding: BindingIndex,
+        /// The binding it duplicates.
+        duplicate_of: BindingIndex,
+    },
+    /// The keystrokes of the binding are a prefix of those of a binding added later that can be
+    /// active in the same context, so the binding only dispatches once the pending input times
+    /// out after a second.
+    PrefixConflict {
+        /// The binding with the shorter keystrokes.
+        binding: BindingIndex,
+        /// The binding whose keystrokes start with those of the shorter binding.
+        longer: BindingIndex,
+    },
+    /// A binding added later has the same keystrokes and a context matching wherever this binding's
+    /// does, so this binding can never be reached. Also reported when the later binding has a
+    /// prefix of the keystrokes and can be active in the same context, as it dispatches before
+    /// the rest of this binding's keystrokes are typed.
+    Shadowed {
+        /// The binding that can't be reached.
+        binding: BindingIndex,
+        /// The binding that takes precedence.
+        shadowed_by: BindingIndex,
+    },
+}
+
 impl Keymap {
     /// Create a new keymap with the given bindings.
     pub fn new(bindings: Vec<KeyBinding>) -> Self {
@@ -71,6 +105,11 @@ impl Keymap {
         self.bindings.iter()
     }
 
+    /// Get the binding at the given index, as reported by [`Self::analyze`].
+    pub fn binding(&self, index: BindingIndex) -> &KeyBinding {
+        &self.bindings[index.0]
+    }
+
     /// Iterate over all bindings for the given action, in the order they were added. For display,
     /// the last binding should take precedence.
     pub fn bindings_for_action<'a>(
@@ -146,6 +185,29 @@ impl Keymap {
         input: &[impl AsKeystroke],
         context_stack: &[KeyContext],
     ) -> (SmallVec<[KeyBinding; 1]>, bool) {
+        let (bindings, pending) = self.match_input(input, context_stack);
+        (bindings, !pending.is_empty())
+    }
+
+    /// Returns the bindings that could still match the given input if it was longer, such as
+    /// `ctrl-k ctrl-c` for the input `ctrl-k`, in precedence order like
+    /// [`Self::bindings_for_input`]. These are the bindings that keep the input pending.
+    pub fn pending_bindings_for_input(
+        &self,
+        input: &[impl AsKeystroke],
+        context_stack: &[KeyContext],
+    ) -> Vec<KeyBinding> {
+        let (_, pending) = self.match_input(input, context_stack);
+        pending.into_iter().rev().cloned().collect()
+    }
+
+    /// Returns the bindings matching the input in precedence order, and the bindings that could
+    /// match longer input in the order they were added.
+    fn match_input(
+        &self,
+        input: &[impl AsKeystroke],
+        context_stack: &[KeyContext],
+    ) -> (SmallVec<[KeyBinding; 1]>, Vec<&KeyBinding>) {
         let mut matched_bindings = SmallVec::<[(usize, BindingIndex, &KeyBinding); 1]>::new();
         let mut pending_bindings = SmallVec::<[(BindingIndex, &KeyBinding); 1]>::new();
 
@@ -190,22 +252,100 @@ impl Keymap {
             first_binding_index.get_or_insert(ix);
         }
 
-        let mut pending = HashSet::default();
+        let mut pending = Vec::<&KeyBinding>::new();
         for (ix, binding) in pending_bindings.into_iter().rev() {
             if let Some(binding_ix) = first_binding_index
                 && binding_ix > ix
             {
                 continue;
             }
+            // Later bindings of the same keystrokes replace or disable earlier ones
+            pending.retain(|pending| pending.keystrokes != binding.keystrokes);
+            if !is_no_action(&*binding.action) {
+                pending.push(binding);
+            }
+        }
+
+        (bindings, pending)
+    }
+
+    /// Reports bindings that are duplicated, that can never be reached, or that conflict with a
+    /// longer binding starting with the same keystrokes, in the order of the bindings.
+    ///
+    /// Bindings that unbind keystrokes with [`NoAction`](crate::NoAction) are deliberately
+    /// overriding others, so they're neither reported nor reported as shadowing.
+    pub fn analyze(&self) -> Vec<KeymapIssue> {
+        let mut issues = Vec::new();
+        for (ix, binding) in self.bindings.iter().enumerate() {
             if is_no_action(&*binding.action) {
-                pending.remove(&&binding.keystrokes);
                 continue;
             }
-            pending.insert(&binding.keystrokes);
-        }
+            let binding_ix = BindingIndex(ix);
+
+            let later_bindings = || {
+                self.bindings[ix + 1..]
+                    .iter()
+                    .enumerate()
+                    .map(|(later_ix, later)| (BindingIndex(ix + 1 + later_ix), later))
+                    .filter(|(_, later)| !is_no_action(&*later.action))
+            };
 
-        (bindings, !pending.is_empty())
+            // Later bindings of the same keystrokes take precedence wherever their context matches
+            let overriding = later_bindings()
+                .filter(|(_, later)| later.keystrokes == binding.keystrokes)
+                .find(
+                    |(_, later)| match (&later.context_predicate, &binding.context_predicate) {
+                        (None, _) => true,
+                        (Some(_), None) => false,
+                        (Some(later), Some(predicate)) => later.is_superset(predicate),
+                    },
+                );
+            if let Some((later_ix, later)) = overriding {
+                let is_duplicate = later.context_predicate == binding.context_predicate
+                    && later.action.partial_eq(&*binding.action);
+                issues.push(if is_duplicate {
+                    KeymapIssue::Duplicate {
+                        binding: binding_ix,
+                        duplicate_of: later_ix,
+                    }
+                } else {
+                    KeymapIssue::Shadowed {
+                        binding: binding_ix,
+                        shadowed_by: later_ix,
+                    }
+                });
+                continue;
+            }
+
+            // Later bindings of a prefix of the keystrokes dispatch before the rest are typed
+            let prefix = later_bindings().find(|(_, later)| {
+                later.keystrokes.len() < binding.keystrokes.len()
+                    && binding.keystrokes.starts_with(&later.keystrokes)
+                    && contexts_overlap(&binding.context_predicate, &later.context_predicate)
+            });
+            if let Some((later_ix, _)) = prefix {
+                issues.push(KeymapIssue::Shadowed {
+                    binding: binding_ix,
+                    shadowed_by: later_ix,
+                });
+                continue;
+            }
+
+            let longer = later_bindings().find(|(_, later)| {
+                later.keystrokes.len() > binding.keystrokes.len()
+                    && later.keystrokes.starts_with(&binding.keystrokes)
+                    && contexts_overlap(&binding.context_predicate, &later.context_predicate)
+            });
+            if let Some((longer_ix, _)) = longer {
+                issues.push(KeymapIssue::PrefixConflict {
+                    binding: binding_ix,
+                    longer: longer_ix,
+                });
+            }
+        }
+        issues
     }
+
     /// Check if the given binding is enabled, given a certain key context.
     /// Returns the deepest depth at which the binding matches, or None if it doesn't match.
     fn binding_enabled(&self, binding: &KeyBinding, contexts: &[KeyContext]) -> Option<usize> {
@@ -217,6 +357,18 @@ impl Keymap {
     }
 }
 
+/// Whether the bindings can be active at the same time, assuming that distinct predicates are
+/// mutually exclusive unless one includes the other.
+fn contexts_overlap(
+    a: &Option<Rc<KeyBindingContextPredicate>>,
+    b: &Option<Rc<KeyBindingContextPredicate>>,
+) -> bool {
+    match (a, b) {
+        (None, _) | (_, None) => true,
+        (Some(a), Some(b)) => a.is_superset(b) || b.is_superset(a),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -712,4 +864,83 @@ mod tests {
         assert!(result[0].action.partial_eq(&ActionBeta {}));
         assert!(result[1].action.partial_eq(&ActionAlpha {}));
     }
+
+    #[test]
+    fn test_pending_bindings_for_input() {
+        let keymap = Keymap::new(vec![
+            KeyBinding::new("ctrl-k ctrl-c", ActionAlpha {}, None),
+            KeyBinding::new("ctrl-k ctrl-u", ActionBeta {}, Some("editor")),
+            KeyBinding::new("ctrl-k ctrl-x", ActionGamma {}, Some("terminal")),
+            KeyBinding::new("ctrl-k ctrl-u", ActionDelta {}, Some("editor")),
+            KeyBinding::new("ctrl-k ctrl-c", NoAction {}, Some("editor")),
+        ]);
+
+        let pending = keymap.pending_bindings_for_input(
+            &[Keystroke::parse("ctrl-k").unwrap()],
+            &[KeyContext::parse("editor").unwrap()],
+        );
+        assert_eq!(pending.len(), 1);
+        assert!(pending[0].action.partial_eq(&ActionDelta {}));
+
+        let pending = keymap.pending_bindings_for_input(
+            &[Keystroke::parse("ctrl-k").unwrap()],
+            &[KeyContext::parse("workspace").unwrap()],
+        );
+        assert_eq!(pending.len(), 1);
+        assert!(pending[0].action.partial_eq(&ActionAlpha {}));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let keymap = Keymap::new(vec![
+            KeyBinding::new("ctrl-a", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("Editor && mode == full")),
+            KeyBinding::new("ctrl-k", ActionGamma {}, Some("Editor")),
+            KeyBinding::new("ctrl-k ctrl-u", ActionDelta {}, Some("Editor")),
+            KeyBinding::new("ctrl-x", ActionAlpha {}, Some("Terminal")),
+            KeyBinding::new("ctrl-x ctrl-c", ActionBeta {}, Some("Editor")),
+            KeyBinding::new("ctrl-w", ActionGamma {}, Some("Pane")),
+            KeyBinding::new("ctrl-w", NoAction {}, None),
+            // Overrides the bindings above
+            KeyBinding::new("ctrl-a", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new("ctrl-b", ActionDelta {}, Some("Editor")),
+        ]);
+
+        assert_eq!(
+            keymap.analyze(),
+            [
+                KeymapIssue::Duplicate {
+                    binding: BindingIndex(0),
+                    duplicate_of: BindingIndex(8),
+                },
+                KeymapIssue::Shadowed {
+                    binding: BindingIndex(1),
+                    shadowed_by: BindingIndex(9),
+                },
+                KeymapIssue::PrefixConflict {
+                    binding: BindingIndex(2),
+                    longer: BindingIndex(3),
+                },
+            ]
+        );
+        assert!(
+            keymap
+                .binding(BindingIndex(9))
+                .action
+                .partial_eq(&ActionDelta {})
+        );
+
+        // A shorter binding added later dispatches before the longer one can be typed
+        let keymap = Keymap::new(vec![
+            KeyBinding::new("ctrl-k ctrl-u", ActionDelta {}, Some("Editor")),
+            KeyBinding::new("ctrl-k", ActionGamma {}, Some("Editor")),
+        ]);
+        assert_eq!(
+            keymap.analyze(),
+            [KeymapIssue::Shadowed {
+                binding: BindingIndex(0),
+                shadowed_by: BindingIndex(1),
+            }]
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
 use crate::{
     Action, AsKeystroke, DummyKeyboardMapper, InvalidKeystrokeError, KeyBindingContextPredicate,
@@ -14,6 +14,7 @@ pub struct KeyBinding {
     pub(crate) meta: Option<KeyBindingMetaIndex>,
     /// The json input string used when building the keybinding, if any
     pub(crate) action_input: Option<SharedString>,
+    pub(crate) pending_timeout: Option<Duration>,
 }
 
 impl Clone for KeyBinding {
@@ -24,6 +25,7 @@ impl Clone for KeyBinding {
             context_predicate: self.context_predicate.clone(),
             meta: self.meta,
             action_input: self.action_input.clone(),
+            pending_timeout: self.pending_timeout,
         }
     }
 }
@@ -71,6 +73,7 @@ impl KeyBinding {
             context_predicate,
             meta: None,
             action_input,
+            pending_timeout: None,
         })
     }
 
@@ -85,6 +88,15 @@ impl KeyBinding {
         self.meta = Some(meta);
     }
 
+    /// Set how long to wait for the rest of this binding's keystrokes before dispatching the
+    /// pending input on its own, instead of [`App::pending_input_timeout`].
+    ///
+    /// [`App::pending_input_timeout`]: crate::App::pending_input_timeout
+    pub fn with_pending_timeout(mut self, timeout: Duration) -> Self {
+        self.pending_timeout = Some(timeout);
+        self
+    }
+
     /// Check if the given keystrokes match this binding.
     pub fn match_keystrokes(&self, typed: &[impl AsKeystroke]) -> Option<bool> {
         if self.keystrokes.len() < typed.len() {
@@ -120,6 +132,11 @@ impl KeyBinding {
         self.meta
     }
 
+    /// Get how long to wait for the rest of this binding's keystrokes, if set
+    pub fn pending_timeout(&self) -> Option<Duration> {
+        self.pending_timeout
+    }
+
     /// Get the action input associated with the action for this binding
     pub fn action_input(&self) -> Option<SharedString> {
         self.action_input.clone()
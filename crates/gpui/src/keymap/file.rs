@@ -0,0 +1,456 @@
+use std::{fmt, rc::Rc};
+
+use collections::HashMap;
+use serde::{
+    Deserialize, Deserializer,
+    de::{MapAccess, Visitor},
+};
+use serde_json::{Value, value::RawValue};
+
+use crate::{Action, App, KeyBinding, KeyBindingContextPredicate, NoAction, SharedString};
+
+/// The key bindings loaded from a JSON keymap file.
+///
+/// A keymap file is a list of sections, each binding keystrokes to actions in an optional context:
+///
+/// ```json
+/// [
+///   {
+///     "bindings": { "ctrl-n": "workspace::NewFile" }
+///   },
+///   {
+///     "context": "Editor && mode == full",
+///     "use_key_equivalents": true,
+///     "bindings": {
+///       "ctrl-k ctrl-u": "editor::ConvertToUpperCase",
+///       "alt-enter": ["editor::SelectAllMatches", { "replace_newest": true }],
+///       "ctrl-w": null
+///     }
+///   }
+/// ]
+/// ```
+///
+/// An action is bound by its name, or by its name and arguments. Binding `null` unbinds the
+/// keystrokes, see [`NoAction`].
+#[derive(Debug, Default)]
+pub struct KeymapFile {
+    /// The bindings that were loaded, in the order they appear in the file.
+    pub bindings: Vec<KeyBinding>,
+    /// The errors of the sections and bindings that couldn't be loaded.
+    pub errors: Vec<KeymapFileError>,
+}
+
+/// An error in a keymap file, at the position of the value it's about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeymapFileError {
+    /// The line of the error, starting at 1.
+    pub line: usize,
+    /// The column of the error in characters, starting at 1.
+    pub column: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for KeymapFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for KeymapFileError {}
+
+impl KeymapFile {
+    /// Loads the bindings of a keymap file, building their actions with [`App::build_action`]
+    /// after validating their arguments against the [`App::action_schemas`].
+    ///
+    /// Bindings with errors are skipped, so that the rest of the file still takes effect.
+    pub fn load(source: &str, cx: &App) -> Self {
+        let mut generator = schemars::SchemaGenerator::default();
+        let mut loader = KeymapLoader {
+            source,
+            cx,
+            schemas: cx.action_schemas(&mut generator).into_iter().collect(),
+            file: KeymapFile::default(),
+        };
+        match serde_json::from_str::<Vec<&RawValue>>(source) {
+            Ok(sections) => {
+                for section in sections {
+                    loader.load_section(section);
+                }
+            }
+            Err(error) => {
+                let error = loader.json_error(0, error);
+                loader.file.errors.push(error);
+            }
+        }
+        loader.file
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeymapSection<'a> {
+    #[serde(default, borrow)]
+    context: Option<&'a RawValue>,
+    #[serde(default)]
+    use_key_equivalents: bool,
+    #[serde(borrow)]
+    bindings: &'a RawValue,
+}
+
+/// The entries of a JSON object in order, with the raw keys and values to find their positions.
+struct BindingEntries<'a>(Vec<(&'a RawValue, &'a RawValue)>);
+
+impl<'de> Deserialize<'de> for BindingEntries<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = BindingEntries<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map from keystrokes to actions")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(BindingEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+struct KeymapLoader<'a> {
+    source: &'a str,
+    cx: &'a App,
+    schemas: HashMap<&'static str, Option<schemars::Schema>>,
+    file: KeymapFile,
+}
+
+impl KeymapLoader<'_> {
+    fn load_section(&mut self, section: &RawValue) {
+        let offset = self.offset_of(section);
+        let section = match serde_json::from_str::<KeymapSection>(section.get()) {
+            Ok(section) => section,
+            Err(error) => {
+                let error = self.json_error(offset, error);
+                self.file.errors.push(error);
+                return;
+            }
+        };
+
+        let context_predicate = match section.context {
+            Some(context) => match self.parse_context(context) {
+                Ok(predicate) => predicate,
+                Err(message) => {
+                    let error = self.error_at(self.offset_of(context), message);
+                    self.file.errors.push(error);
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        let offset = self.offset_of(section.bindings);
+        let entries = match serde_json::from_str::<BindingEntries>(section.bindings.get()) {
+            Ok(entries) => entries.0,
+            Err(error) => {
+                let error = self.json_error(offset, error);
+                self.file.errors.push(error);
+                return;
+            }
+        };
+
+        for (keystrokes, action) in entries {
+            let (action, action_input) = match self.build_action(action) {
+                Ok(action) => action,
+                Err(message) => {
+                    let error = self.error_at(self.offset_of(action), message);
+                    self.file.errors.push(error);
+                    continue;
+                }
+            };
+            // Object keys are always strings
+            let source = serde_json::from_str::<String>(keystrokes.get()).unwrap();
+            match KeyBinding::load(
+                &source,
+                action,
+                context_predicate.clone(),
+                section.use_key_equivalents,
+                action_input,
+                self.cx.keyboard_mapper().as_ref(),
+            ) {
+                Ok(binding) => self.file.bindings.push(binding),
+                Err(error) => {
+                    let error = self.error_at(self.offset_of(keystrokes), error.to_string());
+                    self.file.errors.push(error);
+                }
+            }
+        }
+    }
+
+    fn parse_context(
+        &self,
+        context: &RawValue,
+    ) -> Result<Option<Rc<KeyBindingContextPredicate>>, String> {
+        let context = serde_json::from_str::<Option<String>>(context.get())
+            .map_err(|_| "expected a context predicate, like \"Editor && mode == full\"")?;
+        match context {
+            Some(context) if !context.trim().is_empty() => {
+                KeyBindingContextPredicate::parse(&context)
+                    .map(|predicate| Some(Rc::new(predicate)))
+                    .map_err(|error| format!("invalid context \"{context}\": {error}"))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn build_action(
+        &self,
+        action: &RawValue,
+    ) -> Result<(Box<dyn Action>, Option<SharedString>), String> {
+        let (name, arguments) = match serde_json::from_str::<Value>(action.get()) {
+            Ok(Value::Null) => return Ok((Box::new(NoAction), None)),
+            Ok(Value::String(name)) => (name, None),
+            Ok(Value::Array(items)) => match <[Value; 2]>::try_from(items) {
+                Ok([Value::String(name), arguments]) => (name, Some(arguments)),
+                _ => {
+                    return Err("expected an action name and its arguments, like \
+                        [\"editor::SelectNext\", { \"replace_newest\": true }]"
+                        .into());
+                }
+            },
+            _ => {
+                return Err(
+                    "expected an action name, an action name and its arguments, or null".into(),
+                );
+            }
+        };
+
+        if let Some(arguments) = &arguments
+            && let Some(schema) = self.schemas.get(name.as_str())
+        {
+            match schema {
+                Some(schema) => validate_arguments(schema, arguments)
+                    .map_err(|message| format!("invalid arguments for {name}: {message}"))?,
+                None => return Err(format!("{name} doesn't take any arguments")),
+            }
+        }
+
+        let action_input = arguments
+            .as_ref()
+            .map(|arguments| arguments.to_string().into());
+        let action = self
+            .cx
+            .build_action(&name, arguments)
+            .map_err(|error| error.to_string())?;
+        Ok((action, action_input))
+    }
+
+    fn offset_of(&self, value: &RawValue) -> usize {
+        value.get().as_ptr() as usize - self.source.as_ptr() as usize
+    }
+
+    fn error_at(&self, offset: usize, message: String) -> KeymapFileError {
+        let before = &self.source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |ix| ix + 1);
+        KeymapFileError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        }
+    }
+
+    /// Converts an error parsing the JSON at the offset, whose position is relative to it.
+    fn json_error(&self, offset: usize, error: serde_json::Error) -> KeymapFileError {
+        let message = error.to_string();
+        let location = format!(" at line {} column {}", error.line(), error.column());
+        let message = message
+            .strip_suffix(&location)
+            .unwrap_or(&message)
+            .to_string();
+
+        // The column counts bytes, and is 0 at the end of the input
+        let mut error_offset = offset;
+        for line in self.source[offset..]
+            .split_inclusive('\n')
+            .take(error.line() - 1)
+        {
+            error_offset += line.len();
+        }
+        error_offset = (error_offset + error.column().saturating_sub(1)).min(self.source.len());
+        while !self.source.is_char_boundary(error_offset) {
+            error_offset -= 1;
+        }
+        self.error_at(error_offset, message)
+    }
+}
+
+/// Checks the arguments against the properties of an object schema. Other mismatches are reported
+/// when the action is built.
+fn validate_arguments(schema: &schemars::Schema, arguments: &Value) -> Result<(), String> {
+    if schema.get("type").and_then(Value::as_str) != Some("object") {
+        return Ok(());
+    }
+    let Some(arguments) = arguments.as_object() else {
+        return Err("expected an object".into());
+    };
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    if schema.get("additionalProperties") == Some(&Value::Bool(false))
+        && let Some(unknown) = arguments
+            .keys()
+            .find(|key| properties.is_none_or(|properties| !properties.contains_key(*key)))
+    {
+        let expected = properties
+            .map(|properties| properties.keys().map(|key| format!("\"{key}\"")))
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        return Err(if expected.is_empty() {
+            format!("unknown argument \"{unknown}\"")
+        } else {
+            format!(
+                "unknown argument \"{unknown}\", expected one of {}",
+                expected.join(", ")
+            )
+        });
+    }
+
+    let required = schema.get("required").and_then(Value::as_array);
+    if let Some(missing) = required
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .find(|key| !arguments.contains_key(*key))
+    {
+        return Err(format!("missing argument \"{missing}\""));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::{self as gpui, Action, KeymapFile, NoAction, TestAppContext};
+
+    actions!(test_only, [NewFile, Close]);
+
+    #[derive(Clone, PartialEq, Deserialize, schemars::JsonSchema, Action)]
+    #[action(namespace = test_only)]
+    #[serde(deny_unknown_fields)]
+    struct SelectNext {
+        replace_newest: bool,
+    }
+
+    #[gpui::test]
+    fn test_load_keymap_file(cx: &mut TestAppContext) {
+        let source = r#"[
+            {
+                "bindings": { "ctrl-n": "test_only::NewFile" }
+            },
+            {
+                "context": "Editor && mode == full",
+                "bindings": {
+                    "ctrl-d": ["test_only::SelectNext", { "replace_newest": true }],
+                    "ctrl-w": null,
+                    "ctrl-a-b": "test_only::Close",
+                    "ctrl-e": "test_only::Missing",
+                    "ctrl-f": ["test_only::Close", {}],
+                    "ctrl-g": ["test_only::SelectNext", { "replace": true }],
+                    "ctrl-h": ["test_only::SelectNext", {}]
+                }
+            },
+            { "context": "Editor &&", "bindings": {} },
+            { "bindings": { "ctrl-q": "test_only::Close" }, "extra": 1 }
+        ]"#;
+        let file = cx.update(|cx| KeymapFile::load(source, cx));
+
+        let bindings = file
+            .bindings
+            .iter()
+            .map(|binding| {
+                (
+                    binding.keystrokes()[0].inner().unparse(),
+                    binding.action().name(),
+                    binding.predicate().map(|predicate| predicate.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bindings,
+            [
+                ("ctrl-n".to_string(), NewFile.name(), None),
+                (
+                    "ctrl-d".to_string(),
+                    "test_only::SelectNext",
+                    Some("(Editor && mode == full)".to_string())
+                ),
+                (
+                    "ctrl-w".to_string(),
+                    NoAction.name(),
+                    Some("(Editor && mode == full)".to_string())
+                ),
+            ]
+        );
+        assert!(file.bindings[1].action().partial_eq(&SelectNext {
+            replace_newest: true
+        }));
+
+        let errors = file
+            .errors
+            .iter()
+            .map(|error| (error.line, error.column, error.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (
+                    10,
+                    21,
+                    "Invalid keystroke \"ctrl-a-b\". Expected a sequence of modifiers (`ctrl`, \
+                     `alt`, `shift`, `fn`, `cmd`, `super`, or `win`) followed by a key, separated \
+                     by `-`."
+                ),
+                (11, 31, "Didn't find an action named \"test_only::Missing\""),
+                (12, 31, "test_only::Close doesn't take any arguments"),
+                (
+                    13,
+                    31,
+                    "invalid arguments for test_only::SelectNext: unknown argument \"replace\", \
+                     expected one of \"replace_newest\""
+                ),
+                (
+                    14,
+                    31,
+                    "invalid arguments for test_only::SelectNext: missing argument \
+                     \"replace_newest\""
+                ),
+                (17, 26, "invalid context \"Editor &&\": unexpected end"),
+                (
+                    18,
+                    67,
+                    "unknown field `extra`, expected one of `context`, `use_key_equivalents`, \
+                     `bindings`"
+                ),
+            ]
+        );
+
+        let file = cx.update(|cx| KeymapFile::load("[{ \"bindings\": }]", cx));
+        assert!(file.bindings.is_empty());
+        assert_eq!(
+            file.errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            ["1:16: expected value"]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{App, ClipboardItem, Window};
+
+/// What the drop target of a [`NativeDrag`] may do with the dragged data.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DragAction {
+    /// The target copies the data.
+    Copy,
+    /// The target moves the data, the application should delete its copy when the drag finishes.
+    Move,
+    /// The target links to the data, e.g. creates a shortcut to the dragged file.
+    Link,
+}
+
+/// How a [`NativeDrag`] ended.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DragResult {
+    /// The data was dropped and the target performed the given action.
+    Dropped(DragAction),
+    /// The drag was cancelled, or the data was dropped where it isn't accepted.
+    Cancelled,
+}
+
+/// A drag of data out of the application, to a file manager, a browser or any other application
+/// accepting drops, started with [`Window::start_native_drag`] or
+/// [`crate::StatefulInteractiveElement::on_native_drag`].
+pub struct NativeDrag {
+    /// The dragged data. Paths are offered as a list of `file://` URLs, the other entries as
+    /// they are written to the clipboard.
+    pub item: ClipboardItem,
+    /// The actions the drop target may perform, in order of preference.
+    pub actions: Vec<DragAction>,
+    pub(crate) on_finish: Option<Box<dyn FnOnce(DragResult, &mut Window, &mut App)>>,
+}
+
+impl NativeDrag {
+    /// Create a drag of the given data, which the drop target may copy.
+    pub fn new(item: ClipboardItem) -> Self {
+        Self {
+            item,
+            actions: vec![DragAction::Copy],
+            on_finish: None,
+        }
+    }
+
+    /// Set the actions the drop target may perform, in order of preference. Defaults to
+    /// [`DragAction::Copy`].
+    pub fn actions(mut self, actions: impl IntoIterator<Item = DragAction>) -> Self {
+        self.actions = actions.into_iter().collect();
+        self
+    }
+
+    /// Set the callback invoked once the data is dropped or the drag is cancelled.
+    pub fn on_finish(
+        mut self,
+        callback: impl FnOnce(DragResult, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_finish = Some(Box::new(callback));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, path::PathBuf, rc::Rc};
+
+    use crate::{
+        ClipboardItem, Context, DragAction, DragResult, InteractiveElement as _, IntoElement,
+        Modifiers, MouseButton, NativeDrag, ParentElement as _, Render, StatefulInteractiveElement,
+        Styled as _, TestAppContext, Window, div, point, px,
+    };
+
+    struct DragSource {
+        result: Rc<Cell<Option<DragResult>>>,
+    }
+
+    impl Render for DragSource {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let result = self.result.clone();
+            div()
+                .size_full()
+                .child(
+                    div()
+                        .id("file")
+                        .size(px(100.))
+                        .on_native_drag(move |_, _, _| {
+                            let result = result.clone();
+                            let item = ClipboardItem::new_paths([PathBuf::from("/tmp/a.txt")]);
+                            NativeDrag::new(item)
+                                .actions([DragAction::Move, DragAction::Copy])
+                                .on_finish(move |drag_result, _, _| result.set(Some(drag_result)))
+                        }),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_native_drag(cx: &mut TestAppContext) {
+        let result = Rc::new(Cell::new(None));
+        let (_, cx) = cx.add_window_view(|_, _| DragSource {
+            result: result.clone(),
+        });
+
+        cx.simulate_mouse_down(
+            point(px(10.), px(10.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(cx.native_drag(), None);
+        cx.simulate_mouse_move(
+            point(px(50.), px(50.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(
+            cx.native_drag(),
+            Some((
+                ClipboardItem::new_paths([PathBuf::from("/tmp/a.txt")]),
+                vec![DragAction::Move, DragAction::Copy]
+            ))
+        );
+
+        cx.simulate_native_drag_end(DragResult::Dropped(DragAction::Move));
+        cx.run_until_parked();
+        assert_eq!(result.get(), Some(DragResult::Dropped(DragAction::Move)));
+        assert_eq!(cx.native_drag(), None);
+    }
+}
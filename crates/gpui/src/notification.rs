@@ -0,0 +1,227 @@
+use crate::{App, SharedString};
+use anyhow::Result;
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering::SeqCst},
+};
+
+/// A unique identifier for a notification posted with [`App::show_notification`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NotificationId(pub u64);
+
+impl NotificationId {
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, SeqCst))
+    }
+}
+
+/// How urgent a notification is, platforms may use this to decide how the notification is presented.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum NotificationUrgency {
+    /// Informational notifications that can be shown unobtrusively.
+    Low,
+    /// The default urgency.
+    #[default]
+    Normal,
+    /// Notifications that should not be dismissed until the user sees them.
+    Critical,
+}
+
+/// A button shown on a notification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotificationAction {
+    /// The identifier passed to the [`Notification::on_action`] callback when this action is clicked.
+    pub id: SharedString,
+    /// The label of the button.
+    pub label: SharedString,
+}
+
+/// Why a notification was dismissed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NotificationDismissReason {
+    /// The notification timed out.
+    Expired,
+    /// The user dismissed the notification.
+    DismissedByUser,
+    /// The notification was closed with [`App::close_notification`].
+    Closed,
+    /// The platform didn't report a reason.
+    Undefined,
+}
+
+/// An event reported by the platform for a posted notification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NotificationEvent {
+    /// The action with the given identifier was clicked.
+    ActionInvoked(SharedString),
+    /// The notification was dismissed and will not report further events.
+    Dismissed(NotificationDismissReason),
+}
+
+/// A system notification.
+#[derive(Clone)]
+pub struct Notification {
+    /// The title of the notification.
+    pub title: SharedString,
+    /// The body text of the notification.
+    pub body: Option<SharedString>,
+    /// The icon shown next to the notification.
+    pub icon: Option<Rc<gpui::Image>>,
+    pub(crate) icon_data: Option<NotificationIconData>,
+    /// The urgency of the notification.
+    pub urgency: NotificationUrgency,
+    /// The buttons shown on the notification.
+    pub actions: Vec<NotificationAction>,
+    pub(crate) on_action: Option<Rc<dyn Fn(&SharedString, &mut App)>>,
+    pub(crate) on_dismiss: Option<Rc<dyn Fn(NotificationDismissReason, &mut App)>>,
+}
+
+impl Notification {
+    pub(crate) fn render_icon(&mut self, cx: &App) -> Result<()> {
+        if let Some(icon) = &self.icon {
+            let image = icon.to_image_data(cx.svg_renderer())?;
+            let bytes = image.as_bytes(0).unwrap_or_default();
+            let size = image.size(0);
+
+            self.icon_data = Some(NotificationIconData {
+                data: Rc::new(bytes.to_vec()),
+                width: size.width.0 as u32,
+                height: size.height.0 as u32,
+            })
+        }
+        Ok(())
+    }
+}
+
+/// The rendered icon of a notification, in BGRA format.
+#[derive(Clone)]
+#[allow(unused)]
+pub(crate) struct NotificationIconData {
+    pub(crate) data: Rc<Vec<u8>>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl Notification {
+    /// Create a new notification with the given title.
+    pub fn new(title: impl Into<SharedString>) -> Self {
+        Self {
+            title: title.into(),
+            body: None,
+            icon: None,
+            icon_data: None,
+            urgency: NotificationUrgency::default(),
+            actions: Vec::new(),
+            on_action: None,
+            on_dismiss: None,
+        }
+    }
+
+    /// Set the body text, defaults to None.
+    pub fn body(mut self, body: impl Into<SharedString>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set the icon image, defaults to None.
+    pub fn icon(mut self, icon: impl Into<gpui::Image>) -> Self {
+        self.icon = Some(Rc::new(icon.into()));
+        self
+    }
+
+    /// Set the urgency, defaults to [`NotificationUrgency::Normal`].
+    pub fn urgency(mut self, urgency: NotificationUrgency) -> Self {
+        self.urgency = urgency;
+        self
+    }
+
+    /// Add a button to the notification.
+    pub fn action(mut self, id: impl Into<SharedString>, label: impl Into<SharedString>) -> Self {
+        self.actions.push(NotificationAction {
+            id: id.into(),
+            label: label.into(),
+        });
+        self
+    }
+
+    /// Set the callback invoked with the action's identifier when one of its buttons is clicked.
+    pub fn on_action(mut self, callback: impl Fn(&SharedString, &mut App) + 'static) -> Self {
+        self.on_action = Some(Rc::new(callback));
+        self
+    }
+
+    /// Set the callback invoked when the notification is dismissed.
+    pub fn on_dismiss(
+        mut self,
+        callback: impl Fn(NotificationDismissReason, &mut App) + 'static,
+    ) -> Self {
+        self.on_dismiss = Some(Rc::new(callback));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        Notification, NotificationDismissReason, NotificationUrgency, SharedString, TestAppContext,
+    };
+
+    #[gpui::test]
+    fn test_notification_callbacks(cx: &mut TestAppContext) {
+        let actions = Rc::new(RefCell::new(Vec::<SharedString>::new()));
+        let dismissals = Rc::new(RefCell::new(Vec::new()));
+
+        let id = cx.update(|cx| {
+            cx.show_notification(
+                Notification::new("Build finished")
+                    .body("All tests passed")
+                    .urgency(NotificationUrgency::Low)
+                    .action("open", "Open")
+                    .on_action({
+                        let actions = actions.clone();
+                        move |action, _| actions.borrow_mut().push(action.clone())
+                    })
+                    .on_dismiss({
+                        let dismissals = dismissals.clone();
+                        move |reason, _| dismissals.borrow_mut().push(reason)
+                    }),
+            )
+        });
+
+        let posted = cx.posted_notifications();
+        assert_eq!(posted.len(), 1);
+        assert_eq!(posted[0].0, id);
+        assert_eq!(posted[0].1.title, "Build finished");
+
+        cx.simulate_notification_action(id, "open");
+        assert_eq!(*actions.borrow(), ["open"]);
+
+        cx.simulate_notification_dismissed(id, NotificationDismissReason::DismissedByUser);
+        assert_eq!(
+            *dismissals.borrow(),
+            [NotificationDismissReason::DismissedByUser]
+        );
+        assert!(cx.posted_notifications().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_close_notification(cx: &mut TestAppContext) {
+        let dismissals = Rc::new(RefCell::new(Vec::new()));
+
+        let id = cx.update(|cx| {
+            cx.show_notification(Notification::new("Downloading").on_dismiss({
+                let dismissals = dismissals.clone();
+                move |reason, _| dismissals.borrow_mut().push(reason)
+            }))
+        });
+
+        cx.update(|cx| cx.close_notification(id));
+        cx.run_until_parked();
+
+        assert_eq!(*dismissals.borrow(), [NotificationDismissReason::Closed]);
+        assert!(cx.posted_notifications().is_empty());
+    }
+}
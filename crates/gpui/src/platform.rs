@@ -37,12 +37,13 @@ pub(crate) mod scap_screen_capture;
 
 use crate::{
     Action, AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds,
-    DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Font, FontId, FontMetrics, FontRun,
-    ForegroundExecutor, GlyphId, GpuSpecs, ImageSource, Keymap, LineLayout, Pixels, PlatformInput,
+    DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, DragAction, DragResult, Font, FontId,
+    FontMetrics, FontRun, ForegroundExecutor, GlobalHotkeyId, GlyphId, GpuSpecs, ImageSource,
+    Keymap, LineLayout, Notification, NotificationEvent, NotificationId, Pixels, PlatformInput,
     Point, Priority, RealtimePriority, RenderGlyphParams, RenderImage, RenderImageParams,
-    RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString, Size, SvgRenderer,
-    SystemWindowTab, Task, TaskLabel, TaskTiming, ThreadTaskTimings, Tray, Window, WindowControlArea,
-    hash, point, px, size,
+    RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString, Size, Subscription, SvgRenderer,
+    SystemWindowTab, Task, TaskLabel, TaskTiming, ThreadTaskTimings, Tray, TrayEvent, TrayId,
+    Window, WindowControlArea, default_window_state_path, hash, point, px, size,
 };
 use anyhow::Result;
 use async_task::Runnable;
@@ -240,11 +241,43 @@ pub(crate) trait Platform: 'static {
     }
 
     fn set_dock_menu(&self, menu: Vec<MenuItem>, keymap: &Keymap);
-    fn set_tray(&self, tray: Tray, menu: Option<Vec<MenuItem>>, keymap: &Keymap);
+    fn set_tray(&self, id: TrayId, tray: Tray, menu: Option<Vec<MenuItem>>, keymap: &Keymap);
+    fn remove_tray(&self, _id: TrayId) {}
+    fn on_tray_event(&self, _callback: Box<dyn FnMut(TrayId, TrayEvent)>) {}
+    /// Returns whether the notification was posted, in which case it's dismissed with an event.
+    fn show_notification(&self, _id: NotificationId, _notification: Notification) -> bool {
+        false
+    }
+    fn close_notification(&self, _id: NotificationId) {}
+    fn on_notification_event(&self, _callback: Box<dyn FnMut(NotificationId, NotificationEvent)>) {}
+    fn register_global_hotkey(
+        &self,
+        _id: GlobalHotkeyId,
+        _keystroke: &Keystroke,
+        _name: &str,
+    ) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Global hotkeys are not supported on this platform"
+        ))
+    }
+    fn unregister_global_hotkey(&self, _id: GlobalHotkeyId) {}
+    fn on_global_hotkey(&self, _callback: Box<dyn FnMut(GlobalHotkeyId)>) {}
     fn perform_dock_menu_action(&self, _action: usize) {}
     /// macOS only: controls whether the app shows in the Dock. No-op on other platforms.
     fn set_shows_in_dock(&self, _show: bool) {}
     fn add_recent_document(&self, _path: &Path) {}
+    fn set_badge_count(&self, _count: Option<u64>) {}
+    fn set_progress(&self, _progress: Option<f32>) {}
+    fn request_user_attention(&self, _urgent: bool) {}
+    /// Makes this process the single instance of the application, or forwards the URLs to the
+    /// instance that is already running. Returns false when this process should exit.
+    fn claim_single_instance(&self, _app_id: &str, _urls: Vec<String>) -> bool {
+        true
+    }
+    /// The file that the geometry of windows opened with a restore key is persisted in.
+    fn window_state_path(&self) -> Option<PathBuf> {
+        default_window_state_path()
+    }
     fn update_jump_list(
         &self,
         _menus: Vec<MenuItem>,
@@ -271,6 +304,12 @@ pub(crate) trait Platform: 'static {
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     fn read_from_primary(&self) -> Option<ClipboardItem>;
     fn read_from_clipboard(&self) -> Option<ClipboardItem>;
+    fn read_custom_from_clipboard(&self, _mime: &str) -> Option<Vec<u8>> {
+        None
+    }
+    fn on_clipboard_change(&self, _callback: Box<dyn FnMut()>) {}
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn on_primary_selection_change(&self, _callback: Box<dyn FnMut()>) {}
 
     fn write_credentials(&self, url: &str, username: &str, password: &[u8]) -> Task<Result<()>>;
     fn read_credentials(&self, url: &str) -> Task<Result<Option<(String, Vec<u8>)>>>;
@@ -491,6 +530,17 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
         detail: Option<&str>,
         answers: &[PromptButton],
     ) -> Option<oneshot::Receiver<usize>>;
+    fn start_drag(
+        &self,
+        _item: ClipboardItem,
+        _actions: &[DragAction],
+    ) -> Option<oneshot::Receiver<DragResult>> {
+        None
+    }
+    fn inhibit_idle(&self, _reason: &str) -> Subscription {
+        Subscription::new(|| {})
+    }
+    fn set_pointer_constraint(&self, _constraint: Option<PointerConstraint>) {}
     fn activate(&self);
     fn is_active(&self) -> bool;
     fn is_hovered(&self) -> bool;
@@ -1209,6 +1259,12 @@ pub struct WindowOptions {
 
     /// Tab group name, allows opening the window as a native tab on macOS 10.12+. Windows with the same tabbing identifier will be grouped together.
     pub tabbing_identifier: Option<String>,
+
+    /// Key under which the bounds, display and scale factor of the window are persisted across
+    /// launches. A window opened with a saved key is restored onto the same display, clamped into
+    /// its visible bounds, taking precedence over `window_bounds` and `display_id`, which are
+    /// used instead when that display is gone.
+    pub restore_key: Option<SharedString>,
 }
 
 /// The variables that can be configured when creating a new window
@@ -1261,7 +1317,7 @@ pub(crate) struct WindowParams {
 }
 
 /// Represents the status of how a window should be opened.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WindowBounds {
     /// Indicates that the window should open in a windowed state with the given bounds.
     Windowed(Bounds<Pixels>),
@@ -1316,6 +1372,7 @@ impl Default for WindowOptions {
             window_min_size: None,
             window_decorations: None,
             tabbing_identifier: None,
+            restore_key: None,
         }
     }
 }
@@ -1575,13 +1632,24 @@ pub enum CursorStyle {
     None,
 }
 
+/// How the pointer is constrained to a window, see [`Window::lock_pointer`] and
+/// [`Window::confine_pointer`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PointerConstraint {
+    /// The pointer is locked in place
+    Locked,
+
+    /// The pointer is confined to the bounds, relative to the window
+    Confined(Bounds<Pixels>),
+}
+
 /// A clipboard item that should be copied to the clipboard
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ClipboardItem {
     entries: Vec<ClipboardEntry>,
 }
 
-/// Either a ClipboardString or a ClipboardImage
+/// One representation of the contents of a [`ClipboardItem`]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ClipboardEntry {
     /// A string entry
@@ -1590,6 +1658,16 @@ pub enum ClipboardEntry {
     Image(Image),
     /// A file entry
     ExternalPaths(crate::ExternalPaths),
+    /// An HTML fragment, offered as `text/html` to keep formatting when pasted into other applications
+    Html(String),
+    /// Data in any other format, such as `text/rtf` or an application-specific
+    /// `application/x-...` type to round-trip values between instances of the application
+    Custom {
+        /// The MIME type of the data
+        mime: String,
+        /// The raw bytes of the data
+        bytes: Vec<u8>,
+    },
 }
 
 impl ClipboardItem {
@@ -1619,6 +1697,15 @@ impl ClipboardItem {
         }
     }
 
+    /// Create a new ClipboardItem::ExternalPaths with the given paths
+    pub fn new_paths(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            entries: vec![ClipboardEntry::ExternalPaths(crate::ExternalPaths(
+                paths.into_iter().collect(),
+            ))],
+        }
+    }
+
     /// Create a new ClipboardItem::Image with the given image with no associated metadata
     pub fn new_image(image: &Image) -> Self {
         Self {
@@ -1626,6 +1713,13 @@ impl ClipboardItem {
         }
     }
 
+    /// Adds another representation of the same contents to the item, e.g. HTML alongside plain
+    /// text. Applications reading the clipboard pick the richest representation they support.
+    pub fn with_entry(mut self, entry: impl Into<ClipboardEntry>) -> Self {
+        self.entries.push(entry.into());
+        self
+    }
+
     /// Concatenates together all the ClipboardString entries in the item.
     /// Returns None if there were no ClipboardString entries.
     pub fn text(&self) -> Option<String> {
@@ -1655,6 +1749,25 @@ impl ClipboardItem {
         }
     }
 
+    /// Returns the first HTML entry of the item.
+    pub fn html(&self) -> Option<&str> {
+        self.entries.iter().find_map(|entry| match entry {
+            ClipboardEntry::Html(html) => Some(html.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns the bytes of the first custom entry of the item with the given MIME type.
+    pub fn custom(&self, mime: &str) -> Option<&[u8]> {
+        self.entries.iter().find_map(|entry| match entry {
+            ClipboardEntry::Custom {
+                mime: entry_mime,
+                bytes,
+            } if entry_mime == mime => Some(bytes.as_slice()),
+            _ => None,
+        })
+    }
+
     /// If this item is one ClipboardEntry::String, returns its metadata.
     #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     pub fn metadata(&self) -> Option<&String> {
@@ -1,7 +1,9 @@
 mod dispatcher;
+mod freedesktop;
 mod headless;
 mod keyboard;
 mod platform;
+mod single_instance;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 mod text_system;
 #[cfg(feature = "wayland")]
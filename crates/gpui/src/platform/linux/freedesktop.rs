@@ -0,0 +1,436 @@
+//! Desktop integration through the freedesktop.org specifications: desktop entries registered as
+//! URL scheme handlers, and the recently used files shared between applications.
+
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use http_client::Url;
+
+const XBEL_TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+</xbel>
+"#;
+
+/// The base directories of the XDG Base Directory specification written to.
+#[derive(Debug, Clone)]
+pub(crate) struct XdgDirs {
+    pub data_home: PathBuf,
+    pub config_home: PathBuf,
+}
+
+impl XdgDirs {
+    pub fn from_env() -> Self {
+        let home = util::paths::home_dir();
+        Self {
+            data_home: env_dir("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local/share")),
+            config_home: env_dir("XDG_CONFIG_HOME").unwrap_or_else(|| home.join(".config")),
+        }
+    }
+
+    pub fn applications_dir(&self) -> PathBuf {
+        self.data_home.join("applications")
+    }
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    // Relative paths are invalid according to the specification and must be ignored
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// The name of the application, taken from its executable.
+pub(crate) fn app_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "gpui".to_string())
+}
+
+/// The id of the desktop entry the application was launched from, falling back to one named
+/// after the executable.
+pub(crate) fn desktop_file_id() -> String {
+    env::var_os("GIO_LAUNCHED_DESKTOP_FILE")
+        .and_then(|path| Some(Path::new(&path).file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| format!("{}.desktop", app_name()))
+}
+
+/// Registers the executable as the default handler of a URL scheme, with a desktop entry
+/// listing the scheme in its `MimeType` key and an entry in `mimeapps.list`.
+///
+/// Returns the path of the desktop entry, whose directory needs its MIME cache updated.
+pub(crate) fn register_url_scheme(
+    dirs: &XdgDirs,
+    app_name: &str,
+    executable: &Path,
+    scheme: &str,
+) -> Result<PathBuf> {
+    anyhow::ensure!(is_valid_scheme(scheme), "invalid URL scheme {scheme:?}");
+
+    let applications_dir = dirs.applications_dir();
+    fs::create_dir_all(&applications_dir)
+        .with_context(|| format!("creating {applications_dir:?}"))?;
+    let file_name = format!("{app_name}-{scheme}-handler.desktop");
+    let mime_type = format!("x-scheme-handler/{scheme}");
+    let desktop_entry = applications_dir.join(&file_name);
+    let contents = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={app_name}\n\
+         Exec={} %u\n\
+         NoDisplay=true\n\
+         MimeType={mime_type};\n",
+        desktop_exec_arg(executable),
+    );
+    fs::write(&desktop_entry, contents).with_context(|| format!("writing {desktop_entry:?}"))?;
+
+    let mimeapps = dirs.config_home.join("mimeapps.list");
+    let contents = read_optional(&mimeapps)?.unwrap_or_default();
+    let contents = set_ini_value(
+        &contents,
+        "Default Applications",
+        &mime_type,
+        &format!("{file_name};"),
+    );
+    fs::create_dir_all(&dirs.config_home)
+        .with_context(|| format!("creating {:?}", dirs.config_home))?;
+    fs::write(&mimeapps, contents).with_context(|| format!("writing {mimeapps:?}"))?;
+
+    Ok(desktop_entry)
+}
+
+/// Adds a file to the recently used files, stored in the XBEL format in
+/// `$XDG_DATA_HOME/recently-used.xbel`.
+pub(crate) fn add_recent_document(
+    dirs: &XdgDirs,
+    app_name: &str,
+    path: &Path,
+    now: SystemTime,
+) -> Result<()> {
+    let uri = Url::from_file_path(path)
+        .map_err(|()| anyhow!("recent document path is not absolute: {path:?}"))?;
+    let href = escape_xml(uri.as_str());
+    let app_name = escape_xml(app_name);
+    let timestamp = format_timestamp(now);
+    let application = format!(
+        "<bookmark:application name=\"{app_name}\" exec=\"&apos;{app_name} %u&apos;\" \
+         modified=\"{timestamp}\" count=\"1\"/>"
+    );
+
+    // Documents are added from background threads, don't let them overwrite each other
+    static XBEL_LOCK: Mutex<()> = Mutex::new(());
+    let _lock = XBEL_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let xbel_path = dirs.data_home.join("recently-used.xbel");
+    let mut xbel = read_optional(&xbel_path)?
+        .filter(|xbel| xbel.contains("</xbel>"))
+        .unwrap_or_else(|| XBEL_TEMPLATE.to_string());
+    if let Some(start) = xbel.find(&format!("<bookmark href=\"{href}\"")) {
+        let end = xbel[start..]
+            .find("</bookmark>")
+            .map(|end| start + end)
+            .with_context(|| format!("unterminated bookmark in {xbel_path:?}"))?;
+        let bookmark = update_bookmark(&xbel[start..end], &app_name, &timestamp, &application);
+        xbel.replace_range(start..end, &bookmark);
+    } else {
+        // Consumers of recently used files filter them by type, e.g. to only list images
+        let mime_type = if path.is_dir() {
+            "inode/directory".to_string()
+        } else {
+            mime_guess::from_path(path)
+                .first_or_octet_stream()
+                .essence_str()
+                .to_string()
+        };
+        let bookmark = format!(
+            "  <bookmark href=\"{href}\" added=\"{timestamp}\" modified=\"{timestamp}\" visited=\"{timestamp}\">\n    \
+                 <info>\n      \
+                   <metadata owner=\"http://freedesktop.org\">\n        \
+                     <mime:mime-type type=\"{mime_type}\"/>\n        \
+                     <bookmark:applications>\n          \
+                       {application}\n        \
+                     </bookmark:applications>\n      \
+                   </metadata>\n    \
+                 </info>\n  \
+               </bookmark>\n"
+        );
+        let end = xbel.rfind("</xbel>").unwrap_or(xbel.len());
+        xbel.insert_str(end, &bookmark);
+    }
+
+    fs::create_dir_all(&dirs.data_home)
+        .with_context(|| format!("creating {:?}", dirs.data_home))?;
+    // Replace the file atomically, as other applications may read it at any time
+    let temp_path = xbel_path.with_extension("xbel.tmp");
+    fs::write(&temp_path, xbel).with_context(|| format!("writing {temp_path:?}"))?;
+    fs::rename(&temp_path, &xbel_path).with_context(|| format!("replacing {xbel_path:?}"))?;
+    Ok(())
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("reading {path:?}")),
+    }
+}
+
+// See https://datatracker.ietf.org/doc/html/rfc3986#section-3.1
+fn is_valid_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Quotes an argument of the `Exec` key, escaped for use in a desktop entry string value.
+fn desktop_exec_arg(arg: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.to_string_lossy().chars() {
+        match c {
+            '"' | '`' | '$' => quoted.push_str(&format!("\\\\{c}")),
+            '\\' => quoted.push_str("\\\\\\\\"),
+            // Field codes start with a percent sign, so a literal one is doubled
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Sets a key of an INI style file such as `mimeapps.list`, keeping the rest of the file as is.
+fn set_ini_value(contents: &str, section: &str, key: &str, value: &str) -> String {
+    let header = format!("[{section}]");
+    let entry = format!("{key}={value}");
+    let mut lines = contents.lines().collect::<Vec<_>>();
+    let Some(start) = lines.iter().position(|line| line.trim() == header) else {
+        let mut contents = contents.trim_end().to_string();
+        if !contents.is_empty() {
+            contents.push_str("\n\n");
+        }
+        contents.push_str(&format!("{header}\n{entry}\n"));
+        return contents;
+    };
+
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |end| start + 1 + end);
+    let existing = (start + 1..end).find(|&ix| {
+        lines[ix]
+            .split_once('=')
+            .is_some_and(|(existing_key, _)| existing_key.trim() == key)
+    });
+    if let Some(ix) = existing {
+        lines[ix] = &entry;
+    } else {
+        // Add the key after the last one of the section
+        let mut ix = end;
+        while ix > start + 1 && lines[ix - 1].trim().is_empty() {
+            ix -= 1;
+        }
+        lines.insert(ix, &entry);
+    }
+
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+fn update_bookmark(bookmark: &str, app_name: &str, timestamp: &str, application: &str) -> String {
+    let tag_end = bookmark.find('>').unwrap_or(bookmark.len());
+    let mut tag = bookmark[..tag_end].to_string();
+    for name in ["modified", "visited"] {
+        tag = set_xml_attribute(&tag, name, timestamp);
+    }
+
+    let mut rest = bookmark[tag_end..].to_string();
+    if let Some(start) = rest.find(&format!("<bookmark:application name=\"{app_name}\"")) {
+        let end = rest[start..]
+            .find("/>")
+            .map_or(rest.len(), |end| start + end);
+        let element = &rest[start..end];
+        let count = xml_attribute(element, "count")
+            .and_then(|count| count.parse::<u32>().ok())
+            .unwrap_or(0);
+        let element = set_xml_attribute(element, "modified", timestamp);
+        let element = set_xml_attribute(&element, "count", &(count + 1).to_string());
+        rest.replace_range(start..end, &element);
+    } else if let Some(end) = rest.find("</bookmark:applications>") {
+        rest.insert_str(end, &format!("  {application}\n        "));
+    }
+
+    tag + &rest
+}
+
+fn xml_attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = element[start..].find('"')?;
+    Some(&element[start..start + end])
+}
+
+fn set_xml_attribute(element: &str, name: &str, value: &str) -> String {
+    let Some(start) = element.find(&format!(" {name}=\"")) else {
+        return format!("{element} {name}=\"{value}\"");
+    };
+    let value_start = start + name.len() + 3;
+    let value_end = element[value_start..]
+        .find('"')
+        .map_or(element.len(), |end| value_start + end);
+    format!(
+        "{}{value}{}",
+        &element[..value_start],
+        &element[value_end..]
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Formats a time as an ISO 8601 timestamp in UTC, as used by XBEL.
+fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, seconds) = ((seconds / 86400) as i64, seconds % 86400);
+
+    // Civil date from days since the epoch, see https://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use util::test::TempTree;
+
+    fn xdg_dirs(tree: &TempTree) -> XdgDirs {
+        XdgDirs {
+            data_home: tree.path().join("data"),
+            config_home: tree.path().join("config"),
+        }
+    }
+
+    #[test]
+    fn test_register_url_scheme() {
+        let tree = TempTree::new(json!({
+            "config": {
+                "mimeapps.list": "[Default Applications]\ntext/plain=editor.desktop;\nx-scheme-handler/zed=old.desktop;\n\n[Added Associations]\ntext/plain=editor.desktop;\n",
+            },
+        }));
+        let dirs = xdg_dirs(&tree);
+
+        let desktop_entry =
+            register_url_scheme(&dirs, "app", Path::new("/opt/my app/app"), "zed").unwrap();
+        assert_eq!(
+            desktop_entry,
+            dirs.applications_dir().join("app-zed-handler.desktop")
+        );
+        let contents = fs::read_to_string(&desktop_entry).unwrap();
+        assert!(contents.contains("Exec=\"/opt/my app/app\" %u\n"));
+        assert!(contents.contains("MimeType=x-scheme-handler/zed;\n"));
+        assert_eq!(
+            desktop_exec_arg(Path::new("/opt/100% \"app\"/app")),
+            r#""/opt/100%% \\"app\\"/app""#
+        );
+
+        register_url_scheme(&dirs, "app", Path::new("/opt/app"), "zed-cli").unwrap();
+        assert_eq!(
+            fs::read_to_string(dirs.config_home.join("mimeapps.list")).unwrap(),
+            "[Default Applications]\ntext/plain=editor.desktop;\nx-scheme-handler/zed=app-zed-handler.desktop;\nx-scheme-handler/zed-cli=app-zed-cli-handler.desktop;\n\n[Added Associations]\ntext/plain=editor.desktop;\n"
+        );
+
+        assert!(register_url_scheme(&dirs, "app", Path::new("/opt/app"), "../zed").is_err());
+    }
+
+    #[test]
+    fn test_add_recent_document() {
+        let tree = TempTree::new(json!({ "a&b.txt": "", "dir": {}, "notes": "" }));
+        let dirs = xdg_dirs(&tree);
+        let file = tree.path().join("a&b.txt");
+        let dir = tree.path().join("dir");
+        let now = UNIX_EPOCH + Duration::from_secs(951782400);
+
+        add_recent_document(&dirs, "app", &file, now).unwrap();
+        add_recent_document(&dirs, "other", &dir, now).unwrap();
+        add_recent_document(&dirs, "app", &file, now + Duration::from_secs(60)).unwrap();
+        add_recent_document(&dirs, "other", &file, now + Duration::from_secs(120)).unwrap();
+
+        let xbel = fs::read_to_string(dirs.data_home.join("recently-used.xbel")).unwrap();
+        assert!(xbel.ends_with("</xbel>\n"));
+        assert_eq!(xbel.matches("<bookmark href=").count(), 2);
+        assert!(xbel.contains(
+            "a&amp;b.txt\" added=\"2000-02-29T00:00:00Z\" modified=\"2000-02-29T00:02:00Z\""
+        ));
+        assert!(xbel.contains("<mime:mime-type type=\"inode/directory\"/>"));
+        assert!(xbel.contains("<mime:mime-type type=\"text/plain\"/>"));
+        assert!(xbel.contains(
+            "<bookmark:application name=\"app\" exec=\"&apos;app %u&apos;\" modified=\"2000-02-29T00:01:00Z\" count=\"2\"/>"
+        ));
+        assert_eq!(
+            xbel.matches("<bookmark:application name=\"other\"").count(),
+            2
+        );
+
+        add_recent_document(&dirs, "app", &tree.path().join("notes"), now).unwrap();
+        let xbel = fs::read_to_string(dirs.data_home.join("recently-used.xbel")).unwrap();
+        assert!(xbel.contains("<mime:mime-type type=\"application/octet-stream\"/>"));
+
+        assert!(add_recent_document(&dirs, "app", Path::new("relative"), now).is_err());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(951825599)),
+            "2000-02-29T11:59:59Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1767225600)),
+            "2026-01-01T00:00:00Z"
+        );
+    }
+}
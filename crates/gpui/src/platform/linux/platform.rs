@@ -2,33 +2,51 @@ use futures::channel::oneshot;
 use xkbcommon::xkb::{self, Keysym, Keycode, State};
 use util::command::{new_smol_command, new_std_command};
 use anyhow::{anyhow, Context, Result};
-use calloop::{LoopSignal, RegistrationToken};
+use calloop::{LoopHandle, LoopSignal, RegistrationToken};
+use itertools::Itertools as _;
 use std::{
+    cell::{RefCell, RefMut},
     collections::HashMap,
     env,
     ffi::OsString,
     fs::File,
     future::Future,
     io::Read,
+    mem,
     os::fd::{AsFd, AsRawFd, FromRawFd},
     path::{Path, PathBuf},
     rc::Rc,
-    sync::Arc,
-    time::Duration,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant, SystemTime},
 };
 use crate::{
-    Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
-    ForegroundExecutor, Keymap, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
-    PlatformWindow, Point, RunnableVariant, Task, Tray, WindowAppearance, WindowParams,
+    Action, AnyWindowHandle, Axis, BackgroundExecutor, ClipboardEntry, ClipboardItem, CursorStyle,
+    DevicePixels, DisplayId, ExternalDrop, ForegroundExecutor, GlobalHotkeyId, Image, ImageFormat,
+    KeyContext, Keymap, Keystroke, Menu, MenuItem, MouseButton, Notification, NotificationEvent,
+    NotificationId, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay,
+    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PlatformWindow, Point,
+    RunnableVariant, SharedString, Task, Tray, TrayClickEvent, TrayEvent, TrayIconData, TrayId,
+    TrayScrollEvent, TrayStatus, WindowAppearance, WindowParams, point,
 };
 use crate::platform::linux::dispatcher::{LinuxDispatcher, PriorityQueueCalloopReceiver};
-use crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::Submenu;
+use crate::platform::linux::freedesktop::{self, XdgDirs};
+use crate::platform::linux::single_instance::{
+    self, ForwardedLaunch, SingleInstance, SingleInstanceClaim,
+};
+use crate::platform::linux::xdg_desktop_portal::app_menu::AppMenuHandle;
+use crate::platform::linux::xdg_desktop_portal::global_shortcuts::LinuxGlobalShortcuts;
+use crate::platform::linux::xdg_desktop_portal::launcher_entry::{
+    LauncherEntryHandle, LauncherEntryProperties,
+};
+use crate::platform::linux::xdg_desktop_portal::notifications::LinuxNotifications;
+use crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::{
+    DBusMenuEvents, Menu as DBusMenu, Submenu,
+};
 use crate::platform::linux::xdg_desktop_portal::status_notifier::item::{
-    Category, Icon, Pixmap, Status, StatusNotifierItemOptions, ToolTip,
+    Attention, Category, Icon, Pixmap, Status, StatusNotifierItemOptions, ToolTip,
 };
 use crate::platform::linux::xdg_desktop_portal::status_notifier::item::{
-    StatusNotifierItem, StatusNotifierItemEvents,
+    StatusNotifierItem, StatusNotifierItemEvents, StatusNotifierItemHandle,
 };
 use util::ResultExt as _;
 
@@ -62,6 +80,9 @@ pub trait LinuxClient {
     fn write_to_clipboard(&self, item: ClipboardItem);
     fn read_from_primary(&self) -> Option<ClipboardItem>;
     fn read_from_clipboard(&self) -> Option<ClipboardItem>;
+    fn read_custom_from_clipboard(&self, _mime: &str) -> Option<Vec<u8>> {
+        None
+    }
     fn active_window(&self) -> Option<AnyWindowHandle>;
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>>;
     fn run(&self);
@@ -75,10 +96,31 @@ pub trait LinuxClient {
 
     fn set_tray_item(
         &self,
+        id: TrayId,
         options: StatusNotifierItemOptions,
-        menu: Option<crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::Menu>,
+        menu: Option<DBusMenu>,
     ) {
     }
+
+    fn remove_tray_item(&self, id: TrayId) {
+        self.with_common(|common| common.trays.remove(&id));
+    }
+
+    fn set_app_menu(&self, _menu: DBusMenu) {}
+
+    fn create_launcher_entry(&self) {}
+
+    fn set_urgency_hint(&self, _urgent: bool) {}
+
+    fn serve_single_instance(&self, _single_instance: SingleInstance) {}
+
+    /// Grabs the hotkey in the client itself, returning false to register it with the
+    /// GlobalShortcuts portal instead.
+    fn grab_global_hotkey(&self, _id: GlobalHotkeyId, _keystroke: &Keystroke) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn ungrab_global_hotkey(&self, _id: GlobalHotkeyId) {}
 }
 
 #[derive(Default)]
@@ -90,6 +132,47 @@ pub(crate) struct PlatformHandlers {
     pub(crate) will_open_app_menu: Option<Box<dyn FnMut()>>,
     pub(crate) validate_app_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
     pub(crate) keyboard_layout_change: Option<Box<dyn FnMut()>>,
+    pub(crate) clipboard_change: Option<Box<dyn FnMut()>>,
+    pub(crate) primary_selection_change: Option<Box<dyn FnMut()>>,
+    pub(crate) tray_event: Option<Box<dyn FnMut(TrayId, TrayEvent)>>,
+}
+
+/// A tray icon shown as a StatusNotifierItem.
+#[derive(Default)]
+pub(crate) struct LinuxTray {
+    pub(crate) item_token: Option<RegistrationToken>,
+    item: Option<StatusNotifierItemHandle>,
+    registering: bool,
+    // The latest update made while the item was being registered
+    pending_update: Option<(StatusNotifierItemOptions, Option<DBusMenu>)>,
+    blink: bool,
+    blink_task: Option<Task<()>>,
+    pub(crate) menu_actions: HashMap<i32, Box<dyn Action>>, // id -> action
+    pub(crate) last_activation: Option<Instant>,
+}
+
+const TRAY_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The application menu, exported over DBusMenu for global menu bars.
+#[derive(Default)]
+pub(crate) struct LinuxAppMenu {
+    pub(crate) handle: Option<AppMenuHandle>,
+    registering: bool,
+    // The latest menu set while the exported menu was being created
+    pending_update: Option<DBusMenu>,
+    pub(crate) actions: HashMap<i32, Box<dyn Action>>, // id -> action
+}
+
+/// The Unity launcher entry of the application, showing the dock menu as its quicklist and the
+/// badge count, progress and urgency hint on the application's icon.
+#[derive(Default)]
+pub(crate) struct LinuxLauncherEntry {
+    pub(crate) handle: Option<LauncherEntryHandle>,
+    registering: bool,
+    // The latest quicklist set while the launcher entry was being created
+    pending_quicklist: Option<DBusMenu>,
+    properties: LauncherEntryProperties,
+    pub(crate) actions: HashMap<i32, Box<dyn Action>>, // id -> action
 }
 
 pub(crate) struct LinuxCommon {
@@ -101,8 +184,11 @@ pub(crate) struct LinuxCommon {
     pub(crate) callbacks: PlatformHandlers,
     pub(crate) signal: LoopSignal,
     pub(crate) menus: Vec<OwnedMenu>,
-    pub(crate) tray_item_token: Option<RegistrationToken>,
-    pub(crate) tray_menu_actions: HashMap<i32, Box<dyn Action>>, // id -> action
+    pub(crate) app_menu: LinuxAppMenu,
+    pub(crate) launcher_entry: LinuxLauncherEntry,
+    pub(crate) trays: HashMap<TrayId, LinuxTray>,
+    pub(crate) notifications: LinuxNotifications,
+    pub(crate) global_shortcuts: LinuxGlobalShortcuts,
 }
 
 impl LinuxCommon {
@@ -129,12 +215,262 @@ impl LinuxCommon {
             callbacks,
             signal,
             menus: Vec::new(),
-            tray_item_token: None,
-            tray_menu_actions: HashMap::new(),
+            app_menu: LinuxAppMenu::default(),
+            launcher_entry: LinuxLauncherEntry::default(),
+            trays: HashMap::new(),
+            notifications: LinuxNotifications::default(),
+            global_shortcuts: LinuxGlobalShortcuts::default(),
         };
 
         (common, main_receiver)
     }
+
+    /// Updates the StatusNotifierItem of the tray icon in place once it has been registered.
+    ///
+    /// Returns the options and menu to register a new item with if there is none yet.
+    pub(crate) fn update_tray_item(
+        &mut self,
+        id: TrayId,
+        options: StatusNotifierItemOptions,
+        menu: Option<DBusMenu>,
+        blink: bool,
+    ) -> Option<(StatusNotifierItemOptions, Option<DBusMenu>)> {
+        let tray = self.trays.entry(id).or_default();
+        tray.blink = blink;
+        if let Some(item) = tray.item.clone() {
+            self.foreground_executor
+                .spawn(async move {
+                    item.update(options, menu).await.log_err();
+                })
+                .detach();
+            tray.restart_blinking(&self.foreground_executor, &self.background_executor);
+            None
+        } else if tray.registering {
+            tray.pending_update = Some((options, menu));
+            None
+        } else {
+            tray.registering = true;
+            Some((options, menu))
+        }
+    }
+
+    /// Stores the item registered for the tray icon, or None if registering it failed.
+    pub(crate) fn tray_item_registered(
+        &mut self,
+        id: TrayId,
+        item: Option<(StatusNotifierItemHandle, RegistrationToken)>,
+    ) {
+        let Some(tray) = self.trays.get_mut(&id) else {
+            return;
+        };
+        tray.registering = false;
+        let Some((item, token)) = item else {
+            return;
+        };
+        if let Some((options, menu)) = tray.pending_update.take() {
+            self.foreground_executor
+                .spawn({
+                    let item = item.clone();
+                    async move {
+                        item.update(options, menu).await.log_err();
+                    }
+                })
+                .detach();
+        }
+        tray.item = Some(item);
+        tray.item_token = Some(token);
+        tray.restart_blinking(&self.foreground_executor, &self.background_executor);
+    }
+
+    /// Updates the exported application menu in place once it has been created.
+    ///
+    /// Returns the menu to export if it hasn't been yet.
+    pub(crate) fn update_app_menu(&mut self, menu: DBusMenu) -> Option<DBusMenu> {
+        let app_menu = &mut self.app_menu;
+        if let Some(handle) = app_menu.handle.clone() {
+            self.foreground_executor
+                .spawn(async move {
+                    handle.update(menu).await.log_err();
+                })
+                .detach();
+            None
+        } else if app_menu.registering {
+            app_menu.pending_update = Some(menu);
+            None
+        } else {
+            app_menu.registering = true;
+            Some(menu)
+        }
+    }
+
+    /// Stores the exported application menu, or None if exporting it failed.
+    pub(crate) fn app_menu_registered(&mut self, handle: Option<AppMenuHandle>) {
+        let app_menu = &mut self.app_menu;
+        app_menu.registering = false;
+        let Some(handle) = handle else {
+            return;
+        };
+        if let Some(menu) = app_menu.pending_update.take() {
+            self.foreground_executor
+                .spawn({
+                    let handle = handle.clone();
+                    async move {
+                        handle.update(menu).await.log_err();
+                    }
+                })
+                .detach();
+        }
+        app_menu.handle = Some(handle);
+    }
+
+    /// Updates the quicklist of the launcher entry in place once it has been created.
+    ///
+    /// Returns whether the launcher entry needs to be created.
+    pub(crate) fn update_launcher_quicklist(&mut self, quicklist: DBusMenu) -> bool {
+        let launcher_entry = &mut self.launcher_entry;
+        if let Some(handle) = launcher_entry.handle.clone() {
+            self.foreground_executor
+                .spawn(async move {
+                    handle.set_quicklist(quicklist).await.log_err();
+                })
+                .detach();
+            false
+        } else {
+            launcher_entry.pending_quicklist = Some(quicklist);
+            !mem::replace(&mut launcher_entry.registering, true)
+        }
+    }
+
+    /// Updates the badge count, progress or urgency hint of the launcher entry.
+    ///
+    /// Returns whether the launcher entry needs to be created.
+    pub(crate) fn update_launcher_properties(
+        &mut self,
+        update: impl FnOnce(&mut LauncherEntryProperties),
+    ) -> bool {
+        let launcher_entry = &mut self.launcher_entry;
+        let mut properties = launcher_entry.properties.clone();
+        update(&mut properties);
+        if properties == launcher_entry.properties {
+            return false;
+        }
+        launcher_entry.properties = properties.clone();
+        if let Some(handle) = launcher_entry.handle.clone() {
+            self.foreground_executor
+                .spawn(async move {
+                    handle.set_properties(properties).await.log_err();
+                })
+                .detach();
+            false
+        } else {
+            !mem::replace(&mut launcher_entry.registering, true)
+        }
+    }
+
+    /// Clears the urgency hint of the launcher entry, as the application was focused.
+    pub(crate) fn clear_launcher_urgency(&mut self) {
+        if self.launcher_entry.properties.urgent {
+            self.update_launcher_properties(|properties| properties.urgent = false);
+        }
+    }
+
+    /// Stores the created launcher entry, or None if creating it failed.
+    pub(crate) fn launcher_entry_registered(&mut self, handle: Option<LauncherEntryHandle>) {
+        let launcher_entry = &mut self.launcher_entry;
+        launcher_entry.registering = false;
+        let Some(handle) = handle else {
+            return;
+        };
+        let quicklist = launcher_entry.pending_quicklist.take();
+        let properties = launcher_entry.properties.clone();
+        self.foreground_executor
+            .spawn({
+                let handle = handle.clone();
+                async move {
+                    if let Some(quicklist) = quicklist {
+                        handle.set_quicklist(quicklist).await.log_err();
+                    }
+                    handle.set_properties(properties).await.log_err();
+                }
+            })
+            .detach();
+        launcher_entry.handle = Some(handle);
+    }
+}
+
+impl LinuxTray {
+    /// Toggles the attention icon periodically while the tray icon should blink.
+    fn restart_blinking(
+        &mut self,
+        foreground: &ForegroundExecutor,
+        background: &BackgroundExecutor,
+    ) {
+        self.blink_task = match (&self.item, self.blink) {
+            (Some(item), true) => {
+                let item = item.clone();
+                let background = background.clone();
+                Some(foreground.spawn(async move {
+                    let mut hidden = false;
+                    loop {
+                        background.timer(TRAY_BLINK_INTERVAL).await;
+                        hidden = !hidden;
+                        if item.set_attention_hidden(hidden).await.log_err().is_none() {
+                            break;
+                        }
+                    }
+                }))
+            }
+            _ => None,
+        };
+    }
+
+    /// Translates a call made by the tray host on the StatusNotifierItem into tray events.
+    ///
+    /// The StatusNotifierItem spec has no notion of double-clicks, so a second activation
+    /// within the double-click interval is reported as both a click and a double-click.
+    pub(crate) fn events(&mut self, event: StatusNotifierItemEvents) -> Vec<TrayEvent> {
+        self.events_at(event, Instant::now())
+    }
+
+    fn events_at(&mut self, event: StatusNotifierItemEvents, now: Instant) -> Vec<TrayEvent> {
+        match event {
+            StatusNotifierItemEvents::Activate(x, y) => {
+                let click = TrayClickEvent {
+                    button: MouseButton::Left,
+                    position: Some(point(DevicePixels(x), DevicePixels(y))),
+                };
+                let is_double_click = self
+                    .last_activation
+                    .is_some_and(|last| now.duration_since(last) < DOUBLE_CLICK_INTERVAL);
+                if is_double_click {
+                    self.last_activation = None;
+                    vec![
+                        TrayEvent::Click(click.clone()),
+                        TrayEvent::DoubleClick(click),
+                    ]
+                } else {
+                    self.last_activation = Some(now);
+                    vec![TrayEvent::Click(click)]
+                }
+            }
+            StatusNotifierItemEvents::SecondaryActivate(x, y) => {
+                vec![TrayEvent::Click(TrayClickEvent {
+                    button: MouseButton::Middle,
+                    position: Some(point(DevicePixels(x), DevicePixels(y))),
+                })]
+            }
+            StatusNotifierItemEvents::Scroll(delta, orientation) => {
+                let axis = if orientation.eq_ignore_ascii_case("horizontal") {
+                    Axis::Horizontal
+                } else {
+                    Axis::Vertical
+                };
+                vec![TrayEvent::Scroll(TrayScrollEvent { delta, axis })]
+            }
+            StatusNotifierItemEvents::XdgActivationToken(_)
+            | StatusNotifierItemEvents::MenuEvent(_) => Vec::new(),
+        }
+    }
 }
 
 impl<P: LinuxClient + 'static> Platform for P {
@@ -474,51 +810,190 @@ impl<P: LinuxClient + 'static> Platform for P {
         Ok(app_path)
     }
 
-    fn set_menus(&self, menus: Vec<Menu>, _keymap: &Keymap) {
-        self.with_common(|common| {
-            common.menus = menus.into_iter().map(|menu| menu.owned()).collect();
-        })
+    fn set_menus(&self, menus: Vec<Menu>, keymap: &Keymap) {
+        let mut actions = HashMap::new();
+        let mut next_id: i32 = 1;
+        let mut children = Vec::new();
+        let mut owned_menus = Vec::new();
+        for menu in menus {
+            let id = next_id;
+            next_id += 1;
+            children.push(Submenu {
+                id,
+                label: Some(menu.name.to_string()),
+                children: create_submenu(&menu.items, &mut next_id, &mut actions, keymap),
+                ..Default::default()
+            });
+            owned_menus.push(menu.owned());
+        }
+
+        let register = self.with_common(|common| {
+            common.menus = owned_menus;
+            common.app_menu.actions = actions;
+            common.update_app_menu(DBusMenu { children })
+        });
+        if let Some(menu) = register {
+            self.set_app_menu(menu);
+        }
     }
 
     fn get_menus(&self) -> Option<Vec<OwnedMenu>> {
         self.with_common(|common| Some(common.menus.clone()))
     }
 
-    fn set_dock_menu(&self, _menu: Vec<MenuItem>, _keymap: &Keymap) {
-        // todo(linux)
+    fn set_dock_menu(&self, menu: Vec<MenuItem>, keymap: &Keymap) {
+        let mut actions = HashMap::new();
+        let mut next_id: i32 = 1;
+        let children = create_submenu(&menu, &mut next_id, &mut actions, keymap);
+        let register = self.with_common(|common| {
+            common.launcher_entry.actions = actions;
+            common.update_launcher_quicklist(DBusMenu { children })
+        });
+        if register {
+            self.create_launcher_entry();
+        }
     }
 
-    fn set_tray(&self, mut tray: Tray, menu: Option<Vec<MenuItem>>, _keymap: &Keymap) {
+    fn set_badge_count(&self, count: Option<u64>) {
+        let register = self.with_common(|common| {
+            common.update_launcher_properties(|properties| properties.count = count)
+        });
+        if register {
+            self.create_launcher_entry();
+        }
+    }
+
+    fn set_progress(&self, progress: Option<f32>) {
+        let progress = progress.map(|progress| progress.clamp(0., 1.) as f64);
+        let register = self.with_common(|common| {
+            common.update_launcher_properties(|properties| properties.progress = progress)
+        });
+        if register {
+            self.create_launcher_entry();
+        }
+    }
+
+    fn request_user_attention(&self, urgent: bool) {
+        let register = self.with_common(|common| {
+            common.update_launcher_properties(|properties| properties.urgent = urgent)
+        });
+        if register {
+            self.create_launcher_entry();
+        }
+        self.set_urgency_hint(urgent);
+    }
+
+    fn claim_single_instance(&self, app_id: &str, urls: Vec<String>) -> bool {
+        match single_instance::claim(app_id, &ForwardedLaunch::new(urls)) {
+            Ok(SingleInstanceClaim::Primary(single_instance)) => {
+                self.serve_single_instance(single_instance);
+                true
+            }
+            Ok(SingleInstanceClaim::Secondary) => false,
+            Err(error) => {
+                log::error!("failed to claim the single instance of {app_id}: {error:#}");
+                true
+            }
+        }
+    }
+
+    fn set_tray(&self, id: TrayId, mut tray: Tray, menu: Option<Vec<MenuItem>>, keymap: &Keymap) {
+        // Reflect visibility in status
+        let status = match tray.status {
+            _ if !tray.visible => Status::Passive,
+            TrayStatus::Passive => Status::Passive,
+            TrayStatus::Active => Status::Active,
+            TrayStatus::NeedsAttention { .. } => Status::NeedsAttention,
+        };
+        let blink = status == Status::NeedsAttention
+            && matches!(tray.status, TrayStatus::NeedsAttention { blink: true });
         let options = StatusNotifierItemOptions::new()
             .title(tray.title.map(|s| s.to_string()).unwrap_or_default())
             .tooltip(
                 ToolTip::new()
                     .title(tray.tooltip.map(|s| s.to_string()).unwrap_or_default()),
             )
-            .icon(if let Some(icon) = tray.icon_data.take() {
-                Icon::Pixmaps(vec![Pixmap {
-                    width: icon.width as i32,
-                    height: icon.height as i32,
-                    bytes: icon.data.to_vec(),
-                }])
-            } else {
-                Icon::default()
-            });
-
-        let mut options = options;
-        // Reflect visibility in status
-        options.status = if tray.visible { Status::Active } else { Status::Passive };
+            .icon(tray_icon(tray.icon_name, tray.icon_data.take()))
+            .attention(Attention {
+                icon: tray_icon(tray.attention_icon_name, tray.attention_icon_data.take()),
+                movie_name: String::new(),
+            })
+            .overlay(tray_icon(
+                tray.overlay_icon_name,
+                tray.overlay_icon_data.take(),
+            ))
+            .status(status);
 
+        let mut action_map: HashMap<i32, Box<dyn Action>> = HashMap::new();
         let menu = menu.map(|menu| {
-            let mut action_map: HashMap<i32, Box<dyn Action>> = HashMap::new();
             let mut next_id: i32 = 1;
-            let children = create_submenu(menu, &mut next_id, &mut action_map);
+            let children = create_submenu(&menu, &mut next_id, &mut action_map, keymap);
+            DBusMenu { children }
+        });
+        let register = self.with_common(|common| {
             // Store action map for click dispatch
-            self.with_common(|common| common.tray_menu_actions = action_map);
-            crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::Menu { children }
+            common.trays.entry(id).or_default().menu_actions = action_map;
+            common.update_tray_item(id, options, menu, blink)
+        });
+
+        if let Some((options, menu)) = register {
+            self.set_tray_item(id, options, menu);
+        }
+    }
+
+    fn remove_tray(&self, id: TrayId) {
+        self.remove_tray_item(id);
+    }
+
+    fn on_tray_event(&self, callback: Box<dyn FnMut(TrayId, TrayEvent)>) {
+        self.with_common(|common| common.callbacks.tray_event = Some(callback));
+    }
+
+    fn show_notification(&self, id: NotificationId, notification: Notification) -> bool {
+        self.with_common(|common| {
+            common
+                .notifications
+                .show(id, notification, &common.foreground_executor)
         });
+        true
+    }
+
+    fn close_notification(&self, id: NotificationId) {
+        self.with_common(|common| common.notifications.close(id, &common.foreground_executor));
+    }
+
+    fn on_notification_event(&self, callback: Box<dyn FnMut(NotificationId, NotificationEvent)>) {
+        self.with_common(|common| common.notifications.on_event(callback));
+    }
+
+    fn register_global_hotkey(
+        &self,
+        id: GlobalHotkeyId,
+        keystroke: &Keystroke,
+        name: &str,
+    ) -> Result<()> {
+        if self.grab_global_hotkey(id, keystroke)? {
+            return Ok(());
+        }
+        self.with_common(|common| {
+            common
+                .global_shortcuts
+                .register(id, keystroke, name, &common.foreground_executor)
+        });
+        Ok(())
+    }
 
-        self.set_tray_item(options, menu);
+    fn unregister_global_hotkey(&self, id: GlobalHotkeyId) {
+        self.ungrab_global_hotkey(id);
+        self.with_common(|common| {
+            common
+                .global_shortcuts
+                .unregister(id, &common.foreground_executor)
+        });
+    }
+
+    fn on_global_hotkey(&self, callback: Box<dyn FnMut(GlobalHotkeyId)>) {
+        self.with_common(|common| common.global_shortcuts.on_activated(callback));
     }
 
     fn path_for_auxiliary_executable(&self, _name: &str) -> Result<PathBuf> {
@@ -606,8 +1081,28 @@ impl<P: LinuxClient + 'static> Platform for P {
         self.with_common(|common| common.appearance)
     }
 
-    fn register_url_scheme(&self, _: &str) -> Task<anyhow::Result<()>> {
-        Task::ready(Err(anyhow!("register_url_scheme unimplemented")))
+    fn register_url_scheme(&self, scheme: &str) -> Task<anyhow::Result<()>> {
+        let scheme = scheme.to_string();
+        self.background_executor().spawn(async move {
+            let executable = env::current_exe()?;
+            let desktop_entry = freedesktop::register_url_scheme(
+                &XdgDirs::from_env(),
+                &freedesktop::app_name(),
+                &executable,
+                &scheme,
+            )?;
+
+            // Update the MIME cache of the directory, so that the handler is found
+            if let Some(applications_dir) = desktop_entry.parent() {
+                new_smol_command("update-desktop-database")
+                    .arg(applications_dir)
+                    .status()
+                    .await
+                    .context("running update-desktop-database")
+                    .log_err();
+            }
+            Ok(())
+        })
     }
 
     fn write_to_primary(&self, item: ClipboardItem) {
@@ -626,7 +1121,32 @@ impl<P: LinuxClient + 'static> Platform for P {
         self.read_from_clipboard()
     }
 
-    fn add_recent_document(&self, _path: &Path) {}
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.read_custom_from_clipboard(mime)
+    }
+
+    fn on_clipboard_change(&self, callback: Box<dyn FnMut()>) {
+        self.with_common(|common| common.callbacks.clipboard_change = Some(callback));
+    }
+
+    fn on_primary_selection_change(&self, callback: Box<dyn FnMut()>) {
+        self.with_common(|common| common.callbacks.primary_selection_change = Some(callback));
+    }
+
+    fn add_recent_document(&self, path: &Path) {
+        let path = path.to_path_buf();
+        self.background_executor()
+            .spawn(async move {
+                freedesktop::add_recent_document(
+                    &XdgDirs::from_env(),
+                    &freedesktop::app_name(),
+                    &path,
+                    SystemTime::now(),
+                )
+                .log_err();
+            })
+            .detach();
+    }
 }
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
@@ -740,6 +1260,187 @@ pub(super) unsafe fn read_fd(mut fd: filedescriptor::FileDescriptor) -> Result<V
     Ok(buffer)
 }
 
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) const PLAIN_TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) const HTML_MIME_TYPE: &str = "text/html";
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// The representations of a clipboard item offered to other applications, as MIME types and
+/// contents. The text of the item comes first, as [`PLAIN_TEXT_MIME_TYPE`].
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn clipboard_representations(item: &ClipboardItem) -> Vec<(String, Vec<u8>)> {
+    let mut representations = Vec::new();
+    if let Some(text) = item.text() {
+        representations.push((PLAIN_TEXT_MIME_TYPE.to_string(), text.into_bytes()));
+    }
+    for entry in item.entries() {
+        let (mime, bytes) = match entry {
+            ClipboardEntry::Image(image) => {
+                (image.format.mime_type().to_string(), image.bytes.clone())
+            }
+            ClipboardEntry::Html(html) => (HTML_MIME_TYPE.to_string(), html.as_bytes().to_vec()),
+            ClipboardEntry::Custom { mime, bytes } => (mime.clone(), bytes.clone()),
+            ClipboardEntry::String(_) | ClipboardEntry::ExternalPaths(_) => continue,
+        };
+        if !representations
+            .iter()
+            .any(|(existing, _)| *existing == mime)
+        {
+            representations.push((mime, bytes));
+        }
+    }
+    representations
+}
+
+/// The representations of the data of a native drag offered to drop targets, as MIME types and
+/// contents. Dragged paths come first as a list of `file://` URLs, and the text is offered under
+/// its other common names as well.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn drag_representations(item: &ClipboardItem) -> Vec<(String, Vec<u8>)> {
+    let paths = item
+        .entries()
+        .iter()
+        .filter_map(|entry| match entry {
+            ClipboardEntry::ExternalPaths(paths) => Some(paths.paths()),
+            _ => None,
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+    let has_text = item
+        .entries()
+        .iter()
+        .any(|entry| matches!(entry, ClipboardEntry::String(_)));
+
+    let mut representations = Vec::new();
+    if !paths.is_empty() {
+        let uri_list = paths
+            .iter()
+            .filter_map(|path| http_client::Url::from_file_path(path).ok())
+            .map(|url| format!("{url}\r\n"))
+            .collect::<String>();
+        representations.push((URI_LIST_MIME_TYPE.to_string(), uri_list.into_bytes()));
+    }
+    for (mime, bytes) in clipboard_representations(item) {
+        if mime == PLAIN_TEXT_MIME_TYPE {
+            // Without text, the item's text is the paths run together, offer one per line instead
+            let bytes = if has_text {
+                bytes
+            } else {
+                paths
+                    .iter()
+                    .map(|path| path.display())
+                    .join("\n")
+                    .into_bytes()
+            };
+            for alias in [PLAIN_TEXT_MIME_TYPE, "UTF8_STRING", "text/plain"] {
+                representations.push((alias.to_string(), bytes.clone()));
+            }
+        } else {
+            representations.push((mime, bytes));
+        }
+    }
+    representations
+}
+
+/// The text MIME types read from a drag of another application, in order of preference.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+const DROP_TEXT_MIME_TYPES: [&str; 4] =
+    [PLAIN_TEXT_MIME_TYPE, "UTF8_STRING", "text/plain", "STRING"];
+
+/// The MIME types to read from those offered by a drag of another application: the list of URLs,
+/// the text, the HTML and an image.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn drop_mime_types(offered: &[String]) -> Vec<String> {
+    let offers = |mime: &str| offered.iter().any(|offered| offered == mime);
+    let mut mime_types = Vec::new();
+    if offers(URI_LIST_MIME_TYPE) {
+        mime_types.push(URI_LIST_MIME_TYPE.to_string());
+    }
+    if let Some(text) = DROP_TEXT_MIME_TYPES.into_iter().find(|mime| offers(mime)) {
+        mime_types.push(text.to_string());
+    }
+    if offers(HTML_MIME_TYPE) {
+        mime_types.push(HTML_MIME_TYPE.to_string());
+    }
+    if let Some(image) = offered
+        .iter()
+        .find(|mime| ImageFormat::from_mime_type(mime).is_some())
+    {
+        mime_types.push(image.clone());
+    }
+    mime_types
+}
+
+/// The data of a drop, from the representations read from the drag of another application.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn external_drop(representations: Vec<(String, Vec<u8>)>) -> ExternalDrop {
+    let mut data = ExternalDrop::default();
+    for (mime, bytes) in representations {
+        if mime == URI_LIST_MIME_TYPE {
+            let Ok(uri_list) = String::from_utf8(bytes) else {
+                continue;
+            };
+            for uri in uri_list
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            {
+                match http_client::Url::parse(uri) {
+                    Ok(url) if url.scheme() == "file" => {
+                        data.paths.0.extend(url.to_file_path().ok());
+                    }
+                    Ok(_) => data.urls.push(uri.to_string()),
+                    Err(error) => log::warn!("invalid URI {uri:?} in drop: {error}"),
+                }
+            }
+        } else if DROP_TEXT_MIME_TYPES.contains(&mime.as_str()) {
+            // Normalize the text to unix line endings, as when pasting it
+            data.text = String::from_utf8(bytes)
+                .ok()
+                .map(|text| text.replace("\r\n", "\n"));
+        } else if mime == HTML_MIME_TYPE {
+            if let Some(ClipboardEntry::Html(html)) = rich_clipboard_entry(&mime, bytes) {
+                data.html = Some(html);
+            }
+        } else if let Some(format) = ImageFormat::from_mime_type(&mime) {
+            data.image = Some(Image::from_bytes(format, bytes));
+        }
+    }
+    data
+}
+
+/// Whether a MIME type offered by another application is read into an entry of the pasted
+/// clipboard item besides its text or image: HTML and RTF. Other types are only read on request,
+/// see `read_custom_from_clipboard`.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn is_rich_clipboard_mime_type(mime: &str) -> bool {
+    mime == HTML_MIME_TYPE || mime == "text/rtf"
+}
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn rich_clipboard_entry(mime: &str, bytes: Vec<u8>) -> Option<ClipboardEntry> {
+    if mime != HTML_MIME_TYPE {
+        return Some(ClipboardEntry::Custom {
+            mime: mime.to_string(),
+            bytes,
+        });
+    }
+    // Some browsers copy HTML as UTF-16 with a byte order mark
+    let html = match bytes.as_slice() {
+        [0xff, 0xfe, utf16 @ ..] => String::from_utf16(
+            &utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        )
+        .ok()?,
+        _ => String::from_utf8(bytes).ok()?,
+    };
+    Some(ClipboardEntry::Html(html))
+}
+
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(super) const DEFAULT_CURSOR_ICON_NAME: &str = "left_ptr";
 
@@ -794,6 +1495,86 @@ pub(super) fn log_cursor_icon_warning(message: impl std::fmt::Display) {
     }
 }
 
+/// Creates the StatusNotifierItem of a tray icon and adds it to the event loop of the client.
+///
+/// `parts` splits the client state into its common state and loop handle, and `client_state`
+/// gets the client state from the data of its event loop.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn register_tray_item<State: 'static, Data: 'static>(
+    state: &Rc<RefCell<State>>,
+    id: TrayId,
+    options: StatusNotifierItemOptions,
+    menu: Option<DBusMenu>,
+    parts: fn(&mut State) -> (&mut LinuxCommon, &LoopHandle<'static, Data>),
+    client_state: fn(&Data) -> Option<Rc<RefCell<State>>>,
+) {
+    let executor = parts(&mut state.borrow_mut()).0.foreground_executor.clone();
+    let state = state.clone();
+    executor
+        .spawn(async move {
+            // Build the item and let it host the DBusMenu internally
+            let item = StatusNotifierItem::new(id.0, options, menu).await.log_err();
+            let mut state = state.borrow_mut();
+            let (common, loop_handle) = parts(&mut state);
+            // The tray icon may have been removed while the item was being created
+            let item = item
+                .filter(|_| common.trays.contains_key(&id))
+                .and_then(|item| {
+                    let handle = item.handle();
+                    let token = loop_handle
+                        .insert_source(item, move |event, _, data| {
+                            if let Some(state) = client_state(data) {
+                                dispatch_tray_item_event(id, event, || {
+                                    RefMut::map(state.borrow_mut(), |state| parts(state).0)
+                                });
+                            }
+                        })
+                        .log_err()?;
+                    Some((handle, token))
+                });
+            common.tray_item_registered(id, item);
+        })
+        .detach();
+}
+
+/// Dispatches an event of a tray item to the app, with the common state borrowed by `common`
+/// released while the app handles it.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+fn dispatch_tray_item_event<'a>(
+    id: TrayId,
+    event: StatusNotifierItemEvents,
+    common: impl Fn() -> RefMut<'a, LinuxCommon>,
+) {
+    let mut state = common();
+    if let StatusNotifierItemEvents::MenuEvent(DBusMenuEvents::MenuClick(menu_id)) = event {
+        let action = state
+            .trays
+            .get(&id)
+            .and_then(|tray| tray.menu_actions.get(&menu_id))
+            .map(|action| action.boxed_clone());
+        if let Some(action) = action
+            && let Some(mut callback) = state.callbacks.app_menu_action.take()
+        {
+            drop(state);
+            callback(action.as_ref());
+            common().callbacks.app_menu_action = Some(callback);
+        }
+    } else {
+        let events = state
+            .trays
+            .get_mut(&id)
+            .map(|tray| tray.events(event))
+            .unwrap_or_default();
+        if let Some(mut callback) = state.callbacks.tray_event.take() {
+            drop(state);
+            for event in events {
+                callback(id, event);
+            }
+            common().callbacks.tray_event = Some(callback);
+        }
+    }
+}
+
 #[cfg(any(feature = "wayland", feature = "x11"))]
 fn guess_ascii(keycode: Keycode, shift: bool) -> Option<char> {
     // ... (rest of the file is hidden) -> Wait, I need to append the create_submenu function at the end or somewhere appropriate.
@@ -1073,7 +1854,9 @@ impl crate::Capslock {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Point, px};
+    use crate::{self as gpui, KeyBinding, Point, px};
+
+    actions!(test_only, [Save, SaveAll, Undo]);
 
     #[test]
     fn test_is_within_click_distance() {
@@ -1092,37 +1875,227 @@ mod tests {
             Point::new(px(5.0), px(5.1))
         ),);
     }
+
+    #[test]
+    fn test_tray_double_click() {
+        let mut tray = LinuxTray::default();
+        let click = TrayEvent::Click(TrayClickEvent {
+            button: MouseButton::Left,
+            position: Some(point(DevicePixels(10), DevicePixels(20))),
+        });
+        let double_click = TrayEvent::DoubleClick(TrayClickEvent {
+            button: MouseButton::Left,
+            position: Some(point(DevicePixels(10), DevicePixels(20))),
+        });
+        let start = Instant::now();
+        let mut activate_after = |elapsed: Duration| {
+            tray.events_at(StatusNotifierItemEvents::Activate(10, 20), start + elapsed)
+        };
+
+        assert_eq!(activate_after(Duration::ZERO), vec![click.clone()]);
+        assert_eq!(
+            activate_after(DOUBLE_CLICK_INTERVAL / 2),
+            vec![click.clone(), double_click]
+        );
+        // A third activation starts a new click, rather than continuing the double-click.
+        assert_eq!(activate_after(DOUBLE_CLICK_INTERVAL), vec![click.clone()]);
+        // Activations further apart than the interval are single clicks.
+        assert_eq!(activate_after(DOUBLE_CLICK_INTERVAL * 3), vec![click]);
+    }
+
+    #[test]
+    fn test_dbus_menu_shortcut() {
+        let mut keymap = Keymap::default();
+        keymap.add_bindings([
+            KeyBinding::new("ctrl-s", Save, None),
+            KeyBinding::new("ctrl-alt-s", Save, Some("Terminal")),
+            KeyBinding::new("ctrl-k s", SaveAll, None),
+            KeyBinding::new("ctrl-shift-=", Undo, None),
+        ]);
+
+        assert_eq!(
+            dbus_menu_shortcut(&Save, &keymap),
+            Some(vec![vec!["Control".to_string(), "S".to_string()]])
+        );
+        // Sequences of keystrokes can't be shown in menus
+        assert_eq!(dbus_menu_shortcut(&SaveAll, &keymap), None);
+        assert_eq!(
+            dbus_menu_shortcut(&Undo, &keymap),
+            Some(vec![vec![
+                "Control".to_string(),
+                "Shift".to_string(),
+                "equal".to_string()
+            ]])
+        );
+    }
+
+    #[test]
+    fn test_create_submenu() {
+        let menu = [
+            MenuItem::action("Save_As", Save),
+            MenuItem::separator(),
+            MenuItem::submenu(Menu {
+                name: "Edit".into(),
+                items: vec![MenuItem::action("Undo", Undo)],
+            }),
+        ];
+        let mut next_id = 1;
+        let mut actions = HashMap::default();
+        let submenus = create_submenu(&menu, &mut next_id, &mut actions, &Keymap::default());
+
+        assert_eq!(
+            submenus
+                .iter()
+                .map(|item| (item.id, item.label.as_deref(), item.separator))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Some("Save__As"), false),
+                (2, None, true),
+                (3, Some("Edit"), false)
+            ]
+        );
+        assert_eq!(submenus[2].children[0].label.as_deref(), Some("Undo"));
+        let mut action_ids = actions.keys().copied().collect::<Vec<_>>();
+        action_ids.sort();
+        assert_eq!(action_ids, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_drag_representations() {
+        let item =
+            ClipboardItem::new_paths([PathBuf::from("/tmp/a b.txt"), PathBuf::from("/tmp/c.txt")]);
+        let representations = drag_representations(&item);
+        assert_eq!(
+            representations[0],
+            (
+                URI_LIST_MIME_TYPE.to_string(),
+                b"file:///tmp/a%20b.txt\r\nfile:///tmp/c.txt\r\n".to_vec()
+            )
+        );
+        assert_eq!(
+            representations[1],
+            (
+                PLAIN_TEXT_MIME_TYPE.to_string(),
+                b"/tmp/a b.txt\n/tmp/c.txt".to_vec()
+            )
+        );
+
+        let item = ClipboardItem::new_string("text".to_string());
+        assert_eq!(
+            drag_representations(&item),
+            vec![
+                (PLAIN_TEXT_MIME_TYPE.to_string(), b"text".to_vec()),
+                ("UTF8_STRING".to_string(), b"text".to_vec()),
+                ("text/plain".to_string(), b"text".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_external_drop() {
+        let offered = [
+            "text/x-moz-url",
+            "text/uri-list",
+            "text/html",
+            "text/plain",
+            "UTF8_STRING",
+            "image/png",
+        ]
+        .map(str::to_string);
+        assert_eq!(
+            drop_mime_types(&offered),
+            ["text/uri-list", "UTF8_STRING", "text/html", "image/png"]
+        );
+
+        let data = external_drop(vec![
+            (
+                URI_LIST_MIME_TYPE.to_string(),
+                b"# comment\r\nfile:///tmp/a%20b.txt\r\nhttps://zed.dev/\r\n".to_vec(),
+            ),
+            ("UTF8_STRING".to_string(), b"one\r\ntwo".to_vec()),
+            (HTML_MIME_TYPE.to_string(), b"<b>bold</b>".to_vec()),
+            ("image/png".to_string(), b"png".to_vec()),
+        ]);
+        assert_eq!(data.paths(), [PathBuf::from("/tmp/a b.txt")]);
+        assert_eq!(data.urls(), ["https://zed.dev/"]);
+        assert_eq!(data.text(), Some("one\ntwo"));
+        assert_eq!(data.html(), Some("<b>bold</b>"));
+        assert_eq!(
+            data.image(),
+            Some(&Image::from_bytes(ImageFormat::Png, b"png".to_vec()))
+        );
+
+        assert!(external_drop(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_clipboard_representations() {
+        let item = ClipboardItem::new_string("bold".to_string())
+            .with_entry(ClipboardEntry::Html("<b>bold</b>".to_string()))
+            .with_entry(ClipboardEntry::Custom {
+                mime: "text/rtf".to_string(),
+                bytes: b"{\\rtf1 {\\b bold}}".to_vec(),
+            });
+        assert_eq!(
+            clipboard_representations(&item),
+            [
+                (PLAIN_TEXT_MIME_TYPE.to_string(), b"bold".to_vec()),
+                (HTML_MIME_TYPE.to_string(), b"<b>bold</b>".to_vec()),
+                ("text/rtf".to_string(), b"{\\rtf1 {\\b bold}}".to_vec()),
+            ]
+        );
+
+        assert!(is_rich_clipboard_mime_type("text/rtf"));
+        assert!(!is_rich_clipboard_mime_type("application/x-gpui-test"));
+        assert!(!is_rich_clipboard_mime_type("text/plain"));
+        let utf16: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain("<i>x</i>".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(
+            rich_clipboard_entry(HTML_MIME_TYPE, utf16),
+            Some(ClipboardEntry::Html("<i>x</i>".to_string()))
+        );
+    }
 }
 
 
 fn create_submenu(
-    menu: Vec<MenuItem>,
+    menu: &[MenuItem],
     next_id: &mut i32,
     action_map: &mut HashMap<i32, Box<dyn Action>>,
+    keymap: &Keymap,
 ) -> Vec<Submenu> {
     let mut submenus = Vec::new();
-    for item in menu.into_iter() {
+    for item in menu {
         match item {
             MenuItem::Separator => {
-                // Separators not explicitly handled in simple struct yet.
+                let id = *next_id;
+                *next_id += 1;
+                submenus.push(Submenu {
+                    id,
+                    separator: true,
+                    ..Default::default()
+                });
             }
             MenuItem::Action { name, action, .. } => {
                 let id = *next_id;
                 *next_id += 1;
-                action_map.insert(id, action);
                 submenus.push(Submenu {
                     id,
-                    label: Some(name.to_string()),
+                    label: Some(dbus_menu_label(name)),
+                    shortcut: dbus_menu_shortcut(action.as_ref(), keymap),
                     ..Default::default()
                 });
+                action_map.insert(id, action.boxed_clone());
             }
             MenuItem::Submenu(sub) => {
                 let id = *next_id;
                 *next_id += 1;
-                let children = create_submenu(sub.items, next_id, action_map);
+                let children = create_submenu(&sub.items, next_id, action_map, keymap);
                 submenus.push(Submenu {
                     id,
-                    label: Some(sub.name.to_string()),
+                    label: Some(dbus_menu_label(&sub.name)),
                     children,
                     ..Default::default()
                 });
@@ -1135,5 +2108,112 @@ fn create_submenu(
     submenus
 }
 
+/// Escapes underscores in a DBusMenu label, which would otherwise mark the mnemonic of the item.
+fn dbus_menu_label(name: &str) -> String {
+    name.replace('_', "__")
+}
+
+/// Renders the keybinding of an action as a DBusMenu shortcut, e.g. `[["Control", "S"]]`.
+///
+/// Only bindings of a single keystroke are shown, as menu hosts can't display sequences.
+fn dbus_menu_shortcut(action: &dyn Action, keymap: &Keymap) -> Option<Vec<Vec<String>>> {
+    static DEFAULT_CONTEXT: OnceLock<Vec<KeyContext>> = OnceLock::new();
+
+    // Like on macOS, prefer the earliest binding that applies in an editor
+    let binding = keymap
+        .bindings_for_action(action)
+        .find_or_first(|binding| {
+            binding.predicate().is_none_or(|predicate| {
+                predicate.eval(DEFAULT_CONTEXT.get_or_init(|| {
+                    let mut workspace_context = KeyContext::new_with_defaults();
+                    workspace_context.add("Workspace");
+                    let mut pane_context = KeyContext::new_with_defaults();
+                    pane_context.add("Pane");
+                    let mut editor_context = KeyContext::new_with_defaults();
+                    editor_context.add("Editor");
+
+                    pane_context.extend(&editor_context);
+                    workspace_context.extend(&pane_context);
+                    vec![workspace_context]
+                }))
+            })
+        })?;
+    let [keystroke] = binding.keystrokes() else {
+        return None;
+    };
+
+    let modifiers = keystroke.modifiers();
+    let mut keys: Vec<String> = [
+        (modifiers.control, "Control"),
+        (modifiers.alt, "Alt"),
+        (modifiers.shift, "Shift"),
+        (modifiers.platform, "Super"),
+    ]
+    .into_iter()
+    .filter(|(pressed, _)| *pressed)
+    .map(|(_, name)| name.to_string())
+    .collect();
+    keys.push(keysym_name(keystroke.key()));
+    Some(vec![keys])
+}
+
+/// Converts a GPUI key name to its X keysym name, as used by DBusMenu hosts and key grabs.
+pub(crate) fn keysym_name(key: &str) -> String {
+    let name = match key {
+        "enter" => "Return",
+        "escape" => "Escape",
+        "backspace" => "BackSpace",
+        "tab" => "Tab",
+        "space" => "space",
+        "delete" => "Delete",
+        "insert" => "Insert",
+        "home" => "Home",
+        "end" => "End",
+        "pageup" => "Page_Up",
+        "pagedown" => "Page_Down",
+        "up" => "Up",
+        "down" => "Down",
+        "left" => "Left",
+        "right" => "Right",
+        "-" => "minus",
+        "=" => "equal",
+        "+" => "plus",
+        "," => "comma",
+        "." => "period",
+        "/" => "slash",
+        "\\" => "backslash",
+        ";" => "semicolon",
+        "'" => "apostrophe",
+        "`" => "grave",
+        "[" => "bracketleft",
+        "]" => "bracketright",
+        // Letters and function keys, e.g. "a" and "f12"
+        key => return key.to_uppercase(),
+    };
+    name.to_string()
+}
+
+/// Converts a rendered tray icon to a StatusNotifierItem icon, whose pixmaps are ARGB in network
+/// byte order.
+fn tray_icon(name: Option<SharedString>, data: Option<TrayIconData>) -> Icon {
+    Icon {
+        name: name.map(|name| name.to_string()).unwrap_or_default(),
+        pixmaps: data
+            .map(|icon| {
+                // The icon is rendered as BGRA, so reversing each pixel gives ARGB.
+                let mut bytes = icon.data.to_vec();
+                for pixel in bytes.chunks_exact_mut(4) {
+                    pixel.reverse();
+                }
+                vec![Pixmap {
+                    width: icon.width as i32,
+                    height: icon.height as i32,
+                    bytes,
+                }]
+            })
+            .unwrap_or_default(),
+    }
+}
+
 // Fallback messages for missing portals
 const FILE_PICKER_PORTAL_MISSING: &str = "File picker portal not found. Please install and run xdg-desktop-portal for your compositor (e.g., xdg-desktop-portal-gtk or xdg-desktop-portal-kde).";
\ No newline at end of file
@@ -0,0 +1,327 @@
+//! Keeps a single instance of the application running. The primary instance owns a well-known
+//! D-Bus name, or listens on a Unix socket in `$XDG_RUNTIME_DIR` without a session bus, and
+//! later launches forward their URLs to it before exiting.
+
+use std::{
+    env, fs,
+    io::{BufRead as _, BufReader, ErrorKind, Write as _},
+    mem,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::{Context as _, Result};
+use calloop::channel::{self, Channel};
+use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+use zbus::interface;
+use zbus::object_server::Interface as _;
+
+type OnLaunch = Box<dyn Fn(ForwardedLaunch) + Send + Sync>;
+
+/// A launch of the application forwarded from another process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ForwardedLaunch {
+    pub urls: Vec<String>,
+    // Lets the primary instance activate its window, see the XDG activation protocol
+    pub activation_token: Option<String>,
+}
+
+impl ForwardedLaunch {
+    pub fn new(urls: Vec<String>) -> Self {
+        let activation_token = env::var("XDG_ACTIVATION_TOKEN")
+            .or_else(|_| env::var("DESKTOP_STARTUP_ID"))
+            .ok()
+            .filter(|token| !token.is_empty());
+        Self {
+            urls,
+            activation_token,
+        }
+    }
+}
+
+pub(crate) enum SingleInstanceClaim {
+    /// This process is the primary instance, receiving the launches of later ones.
+    Primary(SingleInstance),
+    /// Another instance is running, and the launch was forwarded to it.
+    Secondary,
+}
+
+pub(crate) struct SingleInstance {
+    channel: Channel<ForwardedLaunch>,
+    // Keep the name or the socket claimed while the application runs
+    _connection: Option<zbus::blocking::Connection>,
+    _socket: Option<SocketGuard>,
+}
+
+/// Removes the socket once the primary instance exits.
+struct SocketGuard(PathBuf);
+
+impl Drop for SocketGuard {
+    fn drop(&mut self) {
+        fs::remove_file(&self.0).ok();
+    }
+}
+
+/// Claims the single instance of the application, or forwards the launch to the running one.
+pub(crate) fn claim(app_id: &str, launch: &ForwardedLaunch) -> Result<SingleInstanceClaim> {
+    let (sender, channel) = channel::channel();
+    let on_launch = move |launch| {
+        // Ignore send errors (receiver dropped) on purpose
+        let _ = sender.send(launch);
+    };
+
+    let mut retried = false;
+    let connection = loop {
+        match claim_bus_name(app_id, Box::new(on_launch.clone())) {
+            Ok(Some(connection)) => break Ok(connection),
+            Ok(None) => match forward_to_bus_name(app_id, launch) {
+                Ok(()) => return Ok(SingleInstanceClaim::Secondary),
+                // The running instance may have exited since, releasing the name
+                Err(error) if !mem::replace(&mut retried, true) => {
+                    log::info!("retrying to claim the single instance: {error:#}");
+                }
+                // Falling back to the socket would start a second primary instance
+                Err(error) => {
+                    return Err(error.context("forwarding the launch to the running instance"));
+                }
+            },
+            Err(error) => break Err(error),
+        }
+    };
+
+    let (connection, socket) = match connection {
+        Ok(connection) => (Some(connection), None),
+        Err(error) => {
+            log::info!("falling back to a socket for the single instance: {error:#}");
+            let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+                .map(PathBuf::from)
+                .context("XDG_RUNTIME_DIR is not set")?;
+            let socket_path = runtime_dir.join(format!("{app_id}.sock"));
+            match claim_socket(&socket_path, launch, Box::new(on_launch))? {
+                Some(socket) => (None, Some(socket)),
+                None => return Ok(SingleInstanceClaim::Secondary),
+            }
+        }
+    };
+
+    Ok(SingleInstanceClaim::Primary(SingleInstance {
+        channel,
+        _connection: connection,
+        _socket: socket,
+    }))
+}
+
+struct SingleInstanceInterface {
+    on_launch: OnLaunch,
+}
+
+#[interface(name = "org.gpui.SingleInstance")]
+impl SingleInstanceInterface {
+    fn open(&self, urls: Vec<String>, activation_token: String) {
+        (self.on_launch)(ForwardedLaunch {
+            urls,
+            activation_token: Some(activation_token).filter(|token| !token.is_empty()),
+        });
+    }
+}
+
+// Like GApplication, e.g. "/dev/zed/Zed" for "dev.zed.Zed"
+fn object_path(app_id: &str) -> String {
+    format!("/{}", app_id.replace('.', "/").replace('-', "_"))
+}
+
+/// Returns the connection owning the name of the application, or None if another instance owns
+/// it.
+fn claim_bus_name(app_id: &str, on_launch: OnLaunch) -> Result<Option<zbus::blocking::Connection>> {
+    let connection = zbus::blocking::connection::Builder::session()?
+        .serve_at(object_path(app_id), SingleInstanceInterface { on_launch })?
+        .name(app_id)?
+        .build();
+    match connection {
+        Ok(connection) => Ok(Some(connection)),
+        Err(zbus::Error::NameTaken) => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Forwards the launch to the instance owning the name of the application.
+fn forward_to_bus_name(app_id: &str, launch: &ForwardedLaunch) -> Result<()> {
+    zbus::blocking::Connection::session()?.call_method(
+        Some(app_id),
+        object_path(app_id).as_str(),
+        Some(SingleInstanceInterface::name()),
+        "Open",
+        &(
+            &launch.urls,
+            launch.activation_token.as_deref().unwrap_or_default(),
+        ),
+    )?;
+    Ok(())
+}
+
+/// Returns a guard of the socket listened on, or None if another instance listens on it and the
+/// launch was forwarded to it.
+fn claim_socket(
+    path: &Path,
+    launch: &ForwardedLaunch,
+    on_launch: OnLaunch,
+) -> Result<Option<SocketGuard>> {
+    let listener = match UnixListener::bind(path) {
+        Ok(listener) => listener,
+        Err(error) if error.kind() == ErrorKind::AddrInUse => match UnixStream::connect(path) {
+            Ok(mut stream) => {
+                serde_json::to_writer(&mut stream, launch)?;
+                stream.write_all(b"\n")?;
+                return Ok(None);
+            }
+            // Left behind by an instance that didn't exit cleanly
+            Err(error) if error.kind() == ErrorKind::ConnectionRefused => {
+                fs::remove_file(path).with_context(|| format!("removing {path:?}"))?;
+                UnixListener::bind(path).with_context(|| format!("binding {path:?}"))?
+            }
+            Err(error) => return Err(error).with_context(|| format!("connecting to {path:?}")),
+        },
+        Err(error) => return Err(error).with_context(|| format!("binding {path:?}")),
+    };
+
+    thread::Builder::new()
+        .name("SingleInstance".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Some(stream) = stream.log_err() else {
+                    continue;
+                };
+                let mut line = String::new();
+                if BufReader::new(stream)
+                    .read_line(&mut line)
+                    .log_err()
+                    .is_some()
+                    && let Some(launch) = serde_json::from_str(&line).log_err()
+                {
+                    on_launch(launch);
+                }
+            }
+        })?;
+    Ok(Some(SocketGuard(path.to_path_buf())))
+}
+
+impl EventSource for SingleInstance {
+    type Event = ForwardedLaunch;
+    type Metadata = ();
+    type Ret = ();
+    type Error = anyhow::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.channel.process_events(readiness, token, |evt, _| {
+            if let calloop::channel::Event::Msg(msg) = evt {
+                (callback)(msg, &mut ())
+            }
+        })?;
+        Ok(PostAction::Continue)
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.channel.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.channel.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.channel.unregister(poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::{sync::mpsc, time::Duration};
+    use util::test::TempTree;
+
+    #[test]
+    fn test_single_instance_socket() {
+        let tree = TempTree::new(json!({}));
+        let path = tree.path().join("app.sock");
+        let (sender, receiver) = mpsc::channel();
+        let on_launch = move |launch| sender.send(launch).unwrap();
+        let launch = ForwardedLaunch {
+            urls: vec!["file:///tmp/a.txt".to_string(), "--new".to_string()],
+            activation_token: Some("token".to_string()),
+        };
+
+        let guard = claim_socket(&path, &launch, Box::new(on_launch.clone())).unwrap();
+        assert!(guard.is_some());
+        let forwarded = claim_socket(&path, &launch, Box::new(on_launch.clone())).unwrap();
+        assert!(forwarded.is_none());
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            launch
+        );
+
+        drop(guard);
+        assert!(!path.exists());
+
+        // A socket nobody listens on anymore is claimed again
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        let guard = claim_socket(&path, &launch, Box::new(on_launch)).unwrap();
+        assert!(guard.is_some());
+    }
+
+    #[test]
+    fn test_single_instance_bus_name() {
+        // Needs a session bus, e.g. run within `dbus-run-session`
+        if zbus::blocking::Connection::session().is_err() {
+            return;
+        }
+        let app_id = format!("org.gpui.Test{}", std::process::id());
+        let (sender, receiver) = mpsc::channel();
+        let on_launch = move |launch| sender.send(launch).unwrap();
+        let launch = ForwardedLaunch {
+            urls: vec!["file:///tmp/a.txt".to_string()],
+            activation_token: None,
+        };
+
+        let connection = claim_bus_name(&app_id, Box::new(on_launch.clone())).unwrap();
+        assert!(connection.is_some());
+        let taken = claim_bus_name(&app_id, Box::new(on_launch.clone())).unwrap();
+        assert!(taken.is_none());
+        forward_to_bus_name(&app_id, &launch).unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            launch
+        );
+
+        // Nobody serves the name anymore
+        drop(connection);
+        assert!(forward_to_bus_name(&app_id, &launch).is_err());
+        let connection = claim_bus_name(&app_id, Box::new(on_launch)).unwrap();
+        assert!(connection.is_some());
+    }
+
+    #[test]
+    fn test_object_path() {
+        assert_eq!(object_path("dev.zed.Zed-Preview"), "/dev/zed/Zed_Preview");
+    }
+}
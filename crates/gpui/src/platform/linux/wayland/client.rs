@@ -1,6 +1,7 @@
 use std::{
     cell::{RefCell, RefMut},
     hash::Hash,
+    mem,
     os::fd::{AsRawFd, BorrowedFd},
     path::PathBuf,
     rc::{Rc, Weak},
@@ -15,8 +16,7 @@ use calloop::{
 use calloop_wayland_source::WaylandSource;
 use collections::HashMap;
 use filedescriptor::Pipe;
-use http_client::Url;
-use smallvec::SmallVec;
+use futures::channel::oneshot;
 use util::ResultExt as _;
 use wayland_backend::client::ObjectId;
 use wayland_backend::protocol::WEnum;
@@ -42,6 +42,13 @@ use wayland_protocols::wp::cursor_shape::v1::client::{
 use wayland_protocols::wp::fractional_scale::v1::client::{
     wp_fractional_scale_manager_v1, wp_fractional_scale_v1,
 };
+use wayland_protocols::wp::idle_inhibit::zv1::client::{
+    zwp_idle_inhibit_manager_v1, zwp_idle_inhibitor_v1,
+};
+use wayland_protocols::wp::pointer_constraints::zv1::client::zwp_pointer_constraints_v1::Lifetime;
+use wayland_protocols::wp::pointer_constraints::zv1::client::{
+    zwp_confined_pointer_v1, zwp_locked_pointer_v1, zwp_pointer_constraints_v1,
+};
 use wayland_protocols::wp::primary_selection::zv1::client::zwp_primary_selection_offer_v1::{
     self, ZwpPrimarySelectionOfferV1,
 };
@@ -49,6 +56,9 @@ use wayland_protocols::wp::primary_selection::zv1::client::{
     zwp_primary_selection_device_manager_v1, zwp_primary_selection_device_v1,
     zwp_primary_selection_source_v1,
 };
+use wayland_protocols::wp::relative_pointer::zv1::client::{
+    zwp_relative_pointer_manager_v1, zwp_relative_pointer_v1,
+};
 use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::{
     ContentHint, ContentPurpose,
 };
@@ -61,6 +71,9 @@ use wayland_protocols::xdg::decoration::zv1::client::{
     zxdg_decoration_manager_v1, zxdg_toplevel_decoration_v1,
 };
 use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use wayland_protocols_plasma::appmenu::client::{
+    org_kde_kwin_appmenu, org_kde_kwin_appmenu_manager,
+};
 use wayland_protocols_plasma::blur::client::{org_kde_kwin_blur, org_kde_kwin_blur_manager};
 use wayland_protocols_wlr::layer_shell::v1::client::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
 use xkbcommon::xkb::ffi::XKB_KEYMAP_FORMAT_TEXT_V1;
@@ -72,15 +85,16 @@ use super::{
 };
 
 use crate::{
-    AnyWindowHandle, Bounds, Capslock, CursorStyle, DevicePixels, DisplayId, FileDropEvent,
-    ForegroundExecutor, KeyDownEvent, KeyUpEvent, Keystroke, LinuxCommon, LinuxKeyboardLayout,
-    Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseExitEvent, MouseMoveEvent,
-    MouseUpEvent, NavigationDirection, Pixels, PlatformDisplay, PlatformInput,
-    PlatformKeyboardLayout, Point, ScrollDelta, ScrollWheelEvent, Size, TouchPhase, WindowParams,
+    AnyWindowHandle, Bounds, Capslock, ClipboardItem, CursorStyle, DevicePixels, DisplayId,
+    DragAction, DragResult, ExternalDrop, FileDropEvent, ForegroundExecutor, KeyDownEvent,
+    KeyUpEvent, Keystroke, LinuxCommon, LinuxKeyboardLayout, Modifiers, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection,
+    Pixels, PlatformDisplay, PlatformInput, PlatformKeyboardLayout, Point, PointerConstraint,
+    RawMouseMotionEvent, ScrollDelta, ScrollWheelEvent, Size, Task, TouchPhase, WindowParams,
     point, profiler, px, size,
 };
 use crate::{
-    RunnableVariant, TaskTiming,
+    RunnableVariant, TaskTiming, TrayId,
     platform::{PlatformWindow, blade::BladeContext},
 };
 use crate::{
@@ -89,7 +103,7 @@ use crate::{
         LinuxClient, get_xkb_compose_state, is_within_click_distance, open_uri_internal, read_fd,
         reveal_path_internal,
         wayland::{
-            clipboard::{Clipboard, DataOffer, FILE_LIST_MIME_TYPE, TEXT_MIME_TYPES},
+            clipboard::{Clipboard, DataOffer, offered_mime_types},
             cursor::Cursor,
             serial::{SerialKind, SerialTracker},
             window::WaylandWindow,
@@ -97,10 +111,18 @@ use crate::{
         xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
     },
 };
-use crate::platform::linux::xdg_desktop_portal::status_notifier::item::{
-    StatusNotifierItem, StatusNotifierItemEvents, StatusNotifierItemOptions,
+use crate::platform::linux::freedesktop;
+use crate::platform::linux::platform::{
+    DOUBLE_CLICK_INTERVAL, SCROLL_LINES, URI_LIST_MIME_TYPE, drag_representations, drop_mime_types,
+    external_drop, register_tray_item,
+};
+use crate::platform::linux::single_instance::SingleInstance;
+use crate::platform::linux::xdg_desktop_portal::app_menu::AppMenu;
+use crate::platform::linux::xdg_desktop_portal::launcher_entry::LauncherEntry;
+use crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::{
+    DBusMenuEvents, Menu as DBusMenu,
 };
-use crate::platform::linux::platform::{DOUBLE_CLICK_INTERVAL, SCROLL_LINES};
+use crate::platform::linux::xdg_desktop_portal::status_notifier::item::StatusNotifierItemOptions;
 
 /// Used to convert evdev scancode to xkb scancode
 const MIN_KEYCODE: u32 = 8;
@@ -125,7 +147,12 @@ pub struct Globals {
     pub decoration_manager: Option<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1>,
     pub layer_shell: Option<zwlr_layer_shell_v1::ZwlrLayerShellV1>,
     pub blur_manager: Option<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager>,
+    pub appmenu_manager: Option<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager>,
     pub text_input_manager: Option<zwp_text_input_manager_v3::ZwpTextInputManagerV3>,
+    pub idle_inhibit_manager: Option<zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1>,
+    pub pointer_constraints: Option<zwp_pointer_constraints_v1::ZwpPointerConstraintsV1>,
+    pub relative_pointer_manager:
+        Option<zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1>,
     pub executor: ForegroundExecutor,
 }
 
@@ -163,7 +190,11 @@ impl Globals {
             decoration_manager: globals.bind(&qh, 1..=1, ()).ok(),
             layer_shell: globals.bind(&qh, 1..=5, ()).ok(),
             blur_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            appmenu_manager: globals.bind(&qh, 1..=1, ()).ok(),
             text_input_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            idle_inhibit_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            pointer_constraints: globals.bind(&qh, 1..=1, ()).ok(),
+            relative_pointer_manager: globals.bind(&qh, 1..=1, ()).ok(),
             executor,
             qh,
         }
@@ -206,6 +237,7 @@ pub(crate) struct WaylandClientState {
     gpu_context: BladeContext,
     wl_seat: wl_seat::WlSeat, // TODO: Multi seat support
     wl_pointer: Option<wl_pointer::WlPointer>,
+    relative_pointer: Option<zwp_relative_pointer_v1::ZwpRelativePointerV1>,
     wl_keyboard: Option<wl_keyboard::WlKeyboard>,
     cursor_shape_device: Option<wp_cursor_shape_device_v1::WpCursorShapeDeviceV1>,
     data_device: Option<wl_data_device::WlDataDevice>,
@@ -223,6 +255,7 @@ pub(crate) struct WaylandClientState {
     keymap_state: Option<xkb::State>,
     compose_state: Option<xkb::compose::State>,
     drag: DragState,
+    drag_source: Option<DragSource>,
     click: ClickState,
     repeat: KeyRepeat,
     pub modifiers: Modifiers,
@@ -238,6 +271,7 @@ pub(crate) struct WaylandClientState {
     button_pressed: Option<MouseButton>,
     mouse_focused_window: Option<WaylandWindowStatePtr>,
     keyboard_focused_window: Option<WaylandWindowStatePtr>,
+    last_focused_window: Option<WaylandWindowStatePtr>,
     loop_handle: LoopHandle<'static, WaylandClientStatePtr>,
     cursor_style: Option<CursorStyle>,
     clipboard: Clipboard,
@@ -251,10 +285,19 @@ pub(crate) struct WaylandClientState {
 
 pub struct DragState {
     data_offer: Option<wl_data_offer::WlDataOffer>,
+    mime_types: Vec<String>,
     window: Option<WaylandWindowStatePtr>,
     position: Point<Pixels>,
 }
 
+/// A drag of data out of the application, see [`WaylandClientStatePtr::start_drag`].
+pub struct DragSource {
+    data_source: wl_data_source::WlDataSource,
+    representations: Vec<(String, Vec<u8>)>,
+    action: DndAction,
+    result: oneshot::Sender<DragResult>,
+}
+
 pub struct ClickState {
     last_mouse_button: Option<MouseButton>,
     last_click: Instant,
@@ -278,6 +321,21 @@ pub(crate) enum PendingActivation {
     Window(ObjectId),
 }
 
+/// A lock or confinement of the pointer to a surface, released once destroyed.
+pub(crate) enum ConstrainedPointer {
+    Locked(zwp_locked_pointer_v1::ZwpLockedPointerV1),
+    Confined(zwp_confined_pointer_v1::ZwpConfinedPointerV1),
+}
+
+impl ConstrainedPointer {
+    pub fn destroy(&self) {
+        match self {
+            ConstrainedPointer::Locked(locked_pointer) => locked_pointer.destroy(),
+            ConstrainedPointer::Confined(confined_pointer) => confined_pointer.destroy(),
+        }
+    }
+}
+
 /// This struct is required to conform to Rust's orphan rules, so we can dispatch on the state but hand the
 /// window to GPUI.
 #[derive(Clone)]
@@ -294,6 +352,109 @@ impl WaylandClientStatePtr {
         self.0.upgrade().unwrap().borrow().serial_tracker.get(kind)
     }
 
+    /// Locks or confines the pointer to the surface, until the returned constraint is destroyed.
+    pub fn constrain_pointer(
+        &self,
+        surface: &wl_surface::WlSurface,
+        constraint: PointerConstraint,
+    ) -> Option<ConstrainedPointer> {
+        let client = self.get_client();
+        let state = client.borrow();
+        let (Some(pointer_constraints), Some(wl_pointer)) =
+            (&state.globals.pointer_constraints, &state.wl_pointer)
+        else {
+            return None;
+        };
+        let qh = &state.globals.qh;
+
+        // Persistent constraints are reactivated whenever the surface regains focus
+        Some(match constraint {
+            PointerConstraint::Locked => {
+                let locked_pointer = pointer_constraints.lock_pointer(
+                    surface,
+                    wl_pointer,
+                    None,
+                    Lifetime::Persistent,
+                    qh,
+                    (),
+                );
+                ConstrainedPointer::Locked(locked_pointer)
+            }
+            PointerConstraint::Confined(bounds) => {
+                let bounds = bounds.map(|v| v.0 as i32);
+                let region = state.globals.compositor.create_region(qh, ());
+                region.add(
+                    bounds.origin.x,
+                    bounds.origin.y,
+                    bounds.size.width,
+                    bounds.size.height,
+                );
+                let confined_pointer = pointer_constraints.confine_pointer(
+                    surface,
+                    wl_pointer,
+                    Some(&region),
+                    Lifetime::Persistent,
+                    qh,
+                    (),
+                );
+                region.destroy();
+                ConstrainedPointer::Confined(confined_pointer)
+            }
+        })
+    }
+
+    /// Starts a drag of the data from the surface, within the implicit grab of the last button
+    /// press.
+    pub fn start_drag(
+        &self,
+        origin: &wl_surface::WlSurface,
+        item: ClipboardItem,
+        actions: &[DragAction],
+    ) -> Option<oneshot::Receiver<DragResult>> {
+        let client = self.get_client();
+        let mut state = client.borrow_mut();
+        let (Some(data_device_manager), Some(data_device)) = (
+            state.globals.data_device_manager.clone(),
+            state.data_device.clone(),
+        ) else {
+            return None;
+        };
+
+        // Wayland has no link action
+        let mut dnd_actions = actions
+            .iter()
+            .filter_map(|action| match action {
+                DragAction::Copy => Some(DndAction::Copy),
+                DragAction::Move => Some(DndAction::Move),
+                DragAction::Link => None,
+            })
+            .fold(DndAction::empty(), |actions, action| actions | action);
+        if dnd_actions.is_empty() {
+            dnd_actions = DndAction::Copy;
+        }
+
+        let representations = drag_representations(&item);
+        let data_source = data_device_manager.create_data_source(&state.globals.qh, ());
+        for (mime_type, _) in &representations {
+            data_source.offer(mime_type.clone());
+        }
+        data_source.set_actions(dnd_actions);
+        let serial = state.serial_tracker.get(SerialKind::MousePress);
+        data_device.start_drag(Some(&data_source), origin, None, serial);
+
+        let (result, receiver) = oneshot::channel();
+        // Replacing a previous drag cancels it
+        if let Some(previous) = state.drag_source.replace(DragSource {
+            data_source,
+            representations,
+            action: DndAction::empty(),
+            result,
+        }) {
+            previous.data_source.destroy();
+        }
+        Some(receiver)
+    }
+
     pub fn set_pending_activation(&self, window: ObjectId) {
         self.0.upgrade().unwrap().borrow_mut().pending_activation =
             Some(PendingActivation::Window(window));
@@ -394,6 +555,11 @@ impl WaylandClientStatePtr {
         {
             state.keyboard_focused_window = Some(window);
         }
+        if let Some(window) = state.last_focused_window.take()
+            && !window.ptr_eq(&closed_window)
+        {
+            state.last_focused_window = Some(window);
+        }
     }
 }
 
@@ -405,6 +571,9 @@ impl Drop for WaylandClient {
         let mut state = self.0.borrow_mut();
         state.windows.clear();
 
+        if let Some(relative_pointer) = &state.relative_pointer {
+            relative_pointer.destroy();
+        }
         if let Some(wl_pointer) = &state.wl_pointer {
             wl_pointer.release();
         }
@@ -594,6 +763,7 @@ impl WaylandClient {
             gpu_context,
             wl_seat: seat,
             wl_pointer: None,
+            relative_pointer: None,
             wl_keyboard: None,
             cursor_shape_device: None,
             data_device,
@@ -611,9 +781,11 @@ impl WaylandClient {
             compose_state: None,
             drag: DragState {
                 data_offer: None,
+                mime_types: Vec::new(),
                 window: None,
                 position: Point::default(),
             },
+            drag_source: None,
             click: ClickState {
                 last_click: Instant::now(),
                 last_mouse_button: None,
@@ -644,6 +816,7 @@ impl WaylandClient {
             button_pressed: None,
             mouse_focused_window: None,
             keyboard_focused_window: None,
+            last_focused_window: None,
             loop_handle: handle.clone(),
             enter_token: None,
             cursor_style: None,
@@ -748,6 +921,9 @@ impl LinuxClient for WaylandClient {
             parent,
         )?;
         state.windows.insert(surface_id, window.0.clone());
+        if let Some(app_menu) = &state.common.app_menu.handle {
+            window.0.set_app_menu(app_menu);
+        }
 
         Ok(Box::new(window))
     }
@@ -803,45 +979,177 @@ impl LinuxClient for WaylandClient {
 
     fn set_tray_item(
         &self,
+        id: TrayId,
         options: StatusNotifierItemOptions,
-        menu: Option<crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::Menu>,
+        menu: Option<DBusMenu>,
     ) {
-        let state = self.0.clone();
+        register_tray_item(
+            &self.0,
+            id,
+            options,
+            menu,
+            |state| (&mut state.common, &state.loop_handle),
+            |client| client.0.upgrade(),
+        );
+    }
+
+    fn remove_tray_item(&self, id: TrayId) {
+        let mut state = self.0.borrow_mut();
+        if let Some(tray) = state.common.trays.remove(&id)
+            && let Some(token) = tray.item_token
+        {
+            state.loop_handle.remove(token);
+        }
+    }
+
+    fn set_urgency_hint(&self, urgent: bool) {
+        // Wayland has no urgency hint, but compositors indicate activation requests they don't
+        // grant instead, until the window is focused.
+        let state = self.0.borrow();
+        if !urgent || state.keyboard_focused_window.is_some() {
+            return;
+        }
+        let window = state
+            .last_focused_window
+            .clone()
+            .or_else(|| state.windows.values().next().cloned());
+        drop(state);
+        if let Some(window) = window {
+            window.request_activation();
+        }
+    }
+
+    fn serve_single_instance(&self, single_instance: SingleInstance) {
+        self.0
+            .borrow()
+            .loop_handle
+            .insert_source(single_instance, |launch, _, client| {
+                let Some(client) = client.0.upgrade() else {
+                    return;
+                };
+                let mut state = client.borrow_mut();
+                if !launch.urls.is_empty()
+                    && let Some(mut callback) = state.common.callbacks.open_urls.take()
+                {
+                    drop(state);
+                    callback(launch.urls);
+                    state = client.borrow_mut();
+                    state.common.callbacks.open_urls = Some(callback);
+                }
+                let window = state
+                    .keyboard_focused_window
+                    .clone()
+                    .or_else(|| state.windows.values().next().cloned());
+                let activation = state.globals.activation.clone();
+                drop(state);
+                let Some(window) = window else {
+                    return;
+                };
+                // Compositors only grant activation with the token of the forwarded launch
+                match (activation, launch.activation_token) {
+                    (Some(activation), Some(token)) => {
+                        activation.activate(token, &window.surface())
+                    }
+                    _ => window.request_activation(),
+                }
+            })
+            .log_err();
+    }
+
+    fn create_launcher_entry(&self) {
         self.0
             .borrow()
             .common
             .foreground_executor
             .spawn({
-                let state = state.clone();
+                let state = self.0.clone();
                 async move {
-                    // Build the item and let it host the DBusMenu internally
-                    let item = StatusNotifierItem::new(1, options, menu).await.log_err();
-                    if let Some(item) = item {
-                        let mut state = state.borrow_mut();
-                        if let Some(token) = state.common.tray_item_token {
-                            state.loop_handle.remove(token);
-                        }
-                        state.common.tray_item_token = state
+                    let launcher_entry = LauncherEntry::new(&freedesktop::desktop_file_id())
+                        .await
+                        .log_err();
+                    let mut state = state.borrow_mut();
+                    let handle = launcher_entry.and_then(|launcher_entry| {
+                        let handle = launcher_entry.handle();
+                        state
                             .loop_handle
-                            .insert_source(item, move |event, _, client| {
-                                match event {
-                                    StatusNotifierItemEvents::MenuEvent(crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::DBusMenuEvents::MenuClick(id)) => {
-                                        if let Some(client_rc) = client.0.upgrade() {
-                                            let mut state = client_rc.borrow_mut();
-                                            // Dispatch the associated action if any
-                                            if let Some(mut callback) = state.common.callbacks.app_menu_action.take() {
-                                                if let Some(action) = state.common.tray_menu_actions.get(&id).map(|a| a.boxed_clone()) {
-                                                    callback(&*action);
-                                                }
-                                                state.common.callbacks.app_menu_action = Some(callback);
-                                            }
-                                        }
+                            .insert_source(
+                                launcher_entry,
+                                |DBusMenuEvents::MenuClick(id), _, client| {
+                                    let Some(client) = client.0.upgrade() else {
+                                        return;
+                                    };
+                                    let mut state = client.borrow_mut();
+                                    let action = state
+                                        .common
+                                        .launcher_entry
+                                        .actions
+                                        .get(&id)
+                                        .map(|action| action.boxed_clone());
+                                    if let Some(action) = action
+                                        && let Some(mut callback) =
+                                            state.common.callbacks.app_menu_action.take()
+                                    {
+                                        drop(state);
+                                        callback(action.as_ref());
+                                        client.borrow_mut().common.callbacks.app_menu_action =
+                                            Some(callback);
                                     }
-                                    _ => {}
+                                },
+                            )
+                            .log_err()?;
+                        Some(handle)
+                    });
+                    state.common.launcher_entry_registered(handle);
+                }
+            })
+            .detach();
+    }
+
+    fn set_app_menu(&self, menu: DBusMenu) {
+        self.0
+            .borrow()
+            .common
+            .foreground_executor
+            .spawn({
+                let state = self.0.clone();
+                async move {
+                    let app_menu = AppMenu::new(menu).await.log_err();
+                    let mut state = state.borrow_mut();
+                    let state = &mut *state;
+                    let handle = app_menu.and_then(|app_menu| {
+                        let handle = app_menu.handle();
+                        state
+                            .loop_handle
+                            .insert_source(app_menu, |DBusMenuEvents::MenuClick(id), _, client| {
+                                let Some(client) = client.0.upgrade() else {
+                                    return;
+                                };
+                                let mut state = client.borrow_mut();
+                                let action = state
+                                    .common
+                                    .app_menu
+                                    .actions
+                                    .get(&id)
+                                    .map(|action| action.boxed_clone());
+                                if let Some(action) = action
+                                    && let Some(mut callback) =
+                                        state.common.callbacks.app_menu_action.take()
+                                {
+                                    drop(state);
+                                    callback(action.as_ref());
+                                    client.borrow_mut().common.callbacks.app_menu_action =
+                                        Some(callback);
                                 }
                             })
-                            .log_err();
+                            .log_err()?;
+                        Some(handle)
+                    });
+                    if let Some(handle) = &handle {
+                        for window in state.windows.values() {
+                            window.set_app_menu(handle);
+                        }
                     }
+                    state.common.app_menu_registered(handle);
                 }
             })
             .detach();
@@ -895,11 +1203,12 @@ impl LinuxClient for WaylandClient {
             return;
         };
         if state.mouse_focused_window.is_some() || state.keyboard_focused_window.is_some() {
+            let mime_types = offered_mime_types(&item);
             state.clipboard.set_primary(item);
             let serial = state.serial_tracker.get(SerialKind::KeyPress);
             let data_source = primary_selection_manager.create_source(&state.globals.qh, ());
-            for mime_type in TEXT_MIME_TYPES {
-                data_source.offer(mime_type.to_string());
+            for mime_type in mime_types {
+                data_source.offer(mime_type);
             }
             data_source.offer(state.clipboard.self_mime());
             primary_selection.set_selection(Some(&data_source), serial);
@@ -915,11 +1224,12 @@ impl LinuxClient for WaylandClient {
             return;
         };
         if state.mouse_focused_window.is_some() || state.keyboard_focused_window.is_some() {
+            let mime_types = offered_mime_types(&item);
             state.clipboard.set(item);
             let serial = state.serial_tracker.get(SerialKind::KeyPress);
             let data_source = data_device_manager.create_data_source(&state.globals.qh, ());
-            for mime_type in TEXT_MIME_TYPES {
-                data_source.offer(mime_type.to_string());
+            for mime_type in mime_types {
+                data_source.offer(mime_type);
             }
             data_source.offer(state.clipboard.self_mime());
             data_device.set_selection(Some(&data_source), serial);
@@ -934,6 +1244,10 @@ impl LinuxClient for WaylandClient {
         self.0.borrow_mut().clipboard.read()
     }
 
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.0.borrow().clipboard.read_custom(mime)
+    }
+
     fn active_window(&self) -> Option<AnyWindowHandle> {
         self.0
             .borrow_mut()
@@ -984,6 +1298,9 @@ impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandClientStat
                 version,
             } => match &interface[..] {
                 "wl_seat" => {
+                    if let Some(relative_pointer) = state.relative_pointer.take() {
+                        relative_pointer.destroy();
+                    }
                     if let Some(wl_pointer) = state.wl_pointer.take() {
                         wl_pointer.release();
                     }
@@ -1036,8 +1353,16 @@ delegate_noop!(WaylandClientStatePtr: ignore zwlr_layer_shell_v1::ZwlrLayerShell
 delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_blur_manager::OrgKdeKwinBlurManager);
 delegate_noop!(WaylandClientStatePtr: ignore zwp_text_input_manager_v3::ZwpTextInputManagerV3);
 delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_blur::OrgKdeKwinBlur);
+delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager);
+delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_appmenu::OrgKdeKwinAppmenu);
 delegate_noop!(WaylandClientStatePtr: ignore wp_viewporter::WpViewporter);
 delegate_noop!(WaylandClientStatePtr: ignore wp_viewport::WpViewport);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_pointer_constraints_v1::ZwpPointerConstraintsV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_locked_pointer_v1::ZwpLockedPointerV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_confined_pointer_v1::ZwpConfinedPointerV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1);
 
 impl Dispatch<WlCallback, ObjectId> for WaylandClientStatePtr {
     fn event(
@@ -1068,6 +1393,33 @@ fn get_window(
     state.windows.get(surface_id).cloned()
 }
 
+/// Receives the given representations of the dragged data and reads them in the background.
+fn read_drop(
+    state: &WaylandClientState,
+    data_offer: &wl_data_offer::WlDataOffer,
+    mime_types: Vec<String>,
+) -> Task<anyhow::Result<ExternalDrop>> {
+    let reads = mime_types
+        .into_iter()
+        .map(|mime_type| {
+            let pipe = Pipe::new().unwrap();
+            data_offer.receive(mime_type.clone(), unsafe {
+                BorrowedFd::borrow_raw(pipe.write.as_raw_fd())
+            });
+            drop(pipe.write);
+            (mime_type, pipe.read)
+        })
+        .collect::<Vec<_>>();
+
+    state.common.background_executor.spawn(async {
+        let mut representations = Vec::new();
+        for (mime_type, fd) in reads {
+            representations.push((mime_type, unsafe { read_fd(fd)? }));
+        }
+        anyhow::Ok(external_drop(representations))
+    })
+}
+
 impl Dispatch<wl_surface::WlSurface, ()> for WaylandClientStatePtr {
     fn event(
         this: &mut Self,
@@ -1287,6 +1639,15 @@ impl Dispatch<wl_seat::WlSeat, ()> for WaylandClientStatePtr {
                     .as_ref()
                     .map(|cursor_shape_manager| cursor_shape_manager.get_pointer(&pointer, qh, ()));
 
+                if let Some(relative_pointer) = &state.relative_pointer {
+                    relative_pointer.destroy();
+                }
+                state.relative_pointer = state
+                    .globals
+                    .relative_pointer_manager
+                    .as_ref()
+                    .map(|manager| manager.get_relative_pointer(&pointer, qh, ()));
+
                 if let Some(wl_pointer) = &state.wl_pointer {
                     wl_pointer.release();
                 }
@@ -1345,8 +1706,9 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandClientStatePtr {
             wl_keyboard::Event::Enter { surface, .. } => {
                 state.keyboard_focused_window = get_window(&mut state, &surface.id());
                 state.enter_token = Some(());
-
                 if let Some(window) = state.keyboard_focused_window.clone() {
+                    state.last_focused_window = Some(window.clone());
+                    state.common.clear_launcher_urgency();
                     drop(state);
                     window.set_focused(true);
                 }
@@ -1933,6 +2295,42 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
     }
 }
 
+impl Dispatch<zwp_relative_pointer_v1::ZwpRelativePointerV1, ()> for WaylandClientStatePtr {
+    fn event(
+        this: &mut Self,
+        _: &zwp_relative_pointer_v1::ZwpRelativePointerV1,
+        event: zwp_relative_pointer_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let state = client.borrow();
+
+        if let zwp_relative_pointer_v1::Event::RelativeMotion {
+            dx_unaccel,
+            dy_unaccel,
+            ..
+        } = event
+        {
+            // Raw motion is only reported while the pointer is locked or confined
+            let Some(window) = state
+                .mouse_focused_window
+                .clone()
+                .filter(|window| window.has_constrained_pointer())
+            else {
+                return;
+            };
+            let input = PlatformInput::RawMouseMotion(RawMouseMotionEvent {
+                delta: point(px(dx_unaccel as f32), px(dy_unaccel as f32)),
+                modifiers: state.modifiers,
+            });
+            drop(state);
+            window.handle_input(input);
+        }
+    }
+}
+
 impl Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, ObjectId> for WaylandClientStatePtr {
     fn event(
         this: &mut Self,
@@ -1998,15 +2396,22 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                 }
             }
             wl_data_device::Event::Selection { id: data_offer } => {
-                if let Some(offer) = data_offer {
+                let changed = if let Some(offer) = data_offer {
                     let offer = state
                         .data_offers
                         .iter()
                         .find(|wrapper| wrapper.inner.id() == offer.id());
                     let offer = offer.cloned();
-                    state.clipboard.set_offer(offer);
+                    state.clipboard.set_offer(offer)
                 } else {
-                    state.clipboard.set_offer(None);
+                    state.clipboard.set_offer(None)
+                };
+                if changed
+                    && let Some(mut callback) = state.common.callbacks.clipboard_change.take()
+                {
+                    drop(state);
+                    callback();
+                    client.borrow_mut().common.callbacks.clipboard_change = Some(callback);
                 }
             }
 
@@ -2027,53 +2432,52 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                     const ACTIONS: DndAction = DndAction::Copy;
                     data_offer.set_actions(ACTIONS, ACTIONS);
 
-                    let pipe = Pipe::new().unwrap();
-                    data_offer.receive(FILE_LIST_MIME_TYPE.to_string(), unsafe {
-                        BorrowedFd::borrow_raw(pipe.write.as_raw_fd())
-                    });
-                    let fd = pipe.read;
-                    drop(pipe.write);
+                    let offered = state
+                        .data_offers
+                        .iter()
+                        .find(|offer| offer.inner == data_offer)
+                        .map(|offer| offer.mime_types().to_vec())
+                        .unwrap_or_default();
+                    let mime_types = drop_mime_types(&offered);
+
+                    // Prevent dropping data we can't make sense of.
+                    if mime_types.is_empty() {
+                        data_offer.destroy();
+                        return;
+                    }
 
-                    let read_task = state.common.background_executor.spawn(async {
-                        let buffer = unsafe { read_fd(fd)? };
-                        let text = String::from_utf8(buffer)?;
-                        anyhow::Ok(text)
-                    });
+                    // Only the file list is read up front, everything else once it's dropped.
+                    let file_list = mime_types
+                        .iter()
+                        .filter(|mime_type| *mime_type == URI_LIST_MIME_TYPE)
+                        .cloned()
+                        .collect();
+                    let read_task = read_drop(&state, &data_offer, file_list);
 
                     let this = this.clone();
                     state
                         .common
                         .foreground_executor
                         .spawn(async move {
-                            let file_list = match read_task.await {
-                                Ok(list) => list,
+                            let data = match read_task.await {
+                                Ok(data) => data,
                                 Err(err) => {
                                     log::error!("error reading drag and drop pipe: {err:?}");
                                     return;
                                 }
                             };
-
-                            let paths: SmallVec<[_; 2]> = file_list
-                                .lines()
-                                .filter_map(|path| Url::parse(path).log_err())
-                                .filter_map(|url| url.to_file_path().log_err())
-                                .collect();
                             let position = Point::new(x.into(), y.into());
 
-                            // Prevent dropping text from other programs.
-                            if paths.is_empty() {
-                                data_offer.destroy();
-                                return;
-                            }
-
                             let input = PlatformInput::FileDrop(FileDropEvent::Entered {
                                 position,
-                                paths: crate::ExternalPaths(paths),
+                                paths: data.paths.clone(),
+                                data,
                             });
 
                             let client = this.get_client();
                             let mut state = client.borrow_mut();
                             state.drag.data_offer = Some(data_offer);
+                            state.drag.mime_types = mime_types;
                             state.drag.window = Some(drag_window.clone());
                             state.drag.position = position;
 
@@ -2102,6 +2506,7 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                 data_offer.destroy();
 
                 state.drag.data_offer = None;
+                state.drag.mime_types.clear();
                 state.drag.window = None;
 
                 let input = PlatformInput::FileDrop(FileDropEvent::Exited {});
@@ -2109,21 +2514,38 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                 drag_window.handle_input(input);
             }
             wl_data_device::Event::Drop => {
-                let Some(drag_window) = state.drag.window.clone() else {
+                let Some(drag_window) = state.drag.window.take() else {
                     return;
                 };
-                let data_offer = state.drag.data_offer.clone().unwrap();
-                data_offer.finish();
-                data_offer.destroy();
-
-                state.drag.data_offer = None;
-                state.drag.window = None;
+                let data_offer = state.drag.data_offer.take().unwrap();
+                let mime_types = mem::take(&mut state.drag.mime_types);
+                let position = state.drag.position;
 
-                let input = PlatformInput::FileDrop(FileDropEvent::Submit {
-                    position: state.drag.position,
-                });
-                drop(state);
-                drag_window.handle_input(input);
+                // Read the rest of the data and send it to the window before the drop itself.
+                let read_task = read_drop(&state, &data_offer, mime_types);
+                state
+                    .common
+                    .foreground_executor
+                    .spawn(async move {
+                        let data = read_task.await;
+                        data_offer.finish();
+                        data_offer.destroy();
+
+                        match data {
+                            Ok(data) => drag_window.handle_input(PlatformInput::FileDrop(
+                                FileDropEvent::Entered {
+                                    position,
+                                    paths: data.paths.clone(),
+                                    data,
+                                },
+                            )),
+                            Err(err) => log::error!("error reading drag and drop pipe: {err:?}"),
+                        }
+                        drag_window.handle_input(PlatformInput::FileDrop(FileDropEvent::Submit {
+                            position,
+                        }));
+                    })
+                    .detach();
             }
             _ => {}
         }
@@ -2148,7 +2570,7 @@ impl Dispatch<wl_data_offer::WlDataOffer, ()> for WaylandClientStatePtr {
 
         if let wl_data_offer::Event::Offer { mime_type } = event {
             // Drag and drop
-            if mime_type == FILE_LIST_MIME_TYPE {
+            if !drop_mime_types(std::slice::from_ref(&mime_type)).is_empty() {
                 let serial = state.serial_tracker.get(SerialKind::DataDevice);
                 let mime_type = mime_type.clone();
                 data_offer.accept(serial, Some(mime_type));
@@ -2178,6 +2600,47 @@ impl Dispatch<wl_data_source::WlDataSource, ()> for WaylandClientStatePtr {
         let client = this.get_client();
         let mut state = client.borrow_mut();
 
+        if let Some(drag_source) = state
+            .drag_source
+            .as_mut()
+            .filter(|drag_source| drag_source.data_source == *data_source)
+        {
+            match event {
+                wl_data_source::Event::Send { mime_type, fd } => {
+                    if let Some((_, bytes)) = drag_source
+                        .representations
+                        .iter()
+                        .find(|(representation, _)| *representation == mime_type)
+                    {
+                        let bytes = bytes.clone();
+                        state.clipboard.send_bytes(fd, bytes);
+                    }
+                }
+                wl_data_source::Event::Action { dnd_action } => {
+                    if let WEnum::Value(dnd_action) = dnd_action {
+                        drag_source.action = dnd_action;
+                    }
+                }
+                wl_data_source::Event::DndFinished => {
+                    let drag_source = state.drag_source.take().unwrap();
+                    let action = if drag_source.action.contains(DndAction::Move) {
+                        DragAction::Move
+                    } else {
+                        DragAction::Copy
+                    };
+                    drag_source.result.send(DragResult::Dropped(action)).ok();
+                    data_source.destroy();
+                }
+                wl_data_source::Event::Cancelled => {
+                    let drag_source = state.drag_source.take().unwrap();
+                    drag_source.result.send(DragResult::Cancelled).ok();
+                    data_source.destroy();
+                }
+                _ => {}
+            }
+            return;
+        }
+
         match event {
             wl_data_source::Event::Send { mime_type, fd } => {
                 state.clipboard.send(mime_type, fd);
@@ -2212,11 +2675,23 @@ impl Dispatch<zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1, ()>
                 }
             }
             zwp_primary_selection_device_v1::Event::Selection { id: data_offer } => {
-                if data_offer.is_some() {
+                let changed = if data_offer.is_some() {
                     let offer = state.primary_data_offer.clone();
-                    state.clipboard.set_primary_offer(offer);
+                    state.clipboard.set_primary_offer(offer)
                 } else {
-                    state.clipboard.set_primary_offer(None);
+                    state.clipboard.set_primary_offer(None)
+                };
+                if changed
+                    && let Some(mut callback) =
+                        state.common.callbacks.primary_selection_change.take()
+                {
+                    drop(state);
+                    callback();
+                    client
+                        .borrow_mut()
+                        .common
+                        .callbacks
+                        .primary_selection_change = Some(callback);
                 }
             }
             _ => {}
@@ -1,6 +1,7 @@
 use std::{
     fs::File,
     io::{ErrorKind, Write},
+    mem,
     os::fd::{AsRawFd, BorrowedFd, OwnedFd},
 };
 
@@ -12,17 +13,78 @@ use wayland_protocols::wp::primary_selection::zv1::client::zwp_primary_selection
 
 use crate::{
     ClipboardEntry, ClipboardItem, Image, ImageFormat, WaylandClientStatePtr, hash,
-    platform::linux::platform::read_fd,
+    platform::linux::platform::{
+        PLAIN_TEXT_MIME_TYPE, clipboard_representations, is_rich_clipboard_mime_type, read_fd,
+        rich_clipboard_entry,
+    },
 };
 
 /// Text mime types that we'll offer to other programs.
 pub(crate) const TEXT_MIME_TYPES: [&str; 3] =
     ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
-pub(crate) const FILE_LIST_MIME_TYPE: &str = "text/uri-list";
 
 /// Text mime types that we'll accept from other programs.
 pub(crate) const ALLOWED_TEXT_MIME_TYPES: [&str; 2] = ["text/plain;charset=utf-8", "UTF8_STRING"];
 
+/// The mime types to offer other programs for a clipboard item.
+pub(crate) fn offered_mime_types(item: &ClipboardItem) -> Vec<String> {
+    let mut mime_types = Vec::new();
+    for (mime_type, _) in clipboard_representations(item) {
+        if mime_type == PLAIN_TEXT_MIME_TYPE {
+            mime_types.extend(TEXT_MIME_TYPES.map(str::to_string));
+        } else {
+            mime_types.push(mime_type);
+        }
+    }
+    mime_types
+}
+
+/// The contents of a clipboard item to send for a requested mime type.
+fn representation(item: &ClipboardItem, mime_type: &str) -> Option<Vec<u8>> {
+    let mime_type = if TEXT_MIME_TYPES.contains(&mime_type) {
+        PLAIN_TEXT_MIME_TYPE
+    } else {
+        mime_type
+    };
+    clipboard_representations(item)
+        .into_iter()
+        .find(|(representation, _)| representation == mime_type)
+        .map(|(_, bytes)| bytes)
+}
+
+/// Whether `offer` changes the contents offered by `current_offer`, updating `cached_read` with
+/// the contents of `offer`. Compositors send the selection again whenever a window gains keyboard
+/// focus, so an offer of the same mime types is compared by its contents, if those of the
+/// current offer were read.
+fn offer_changed<T: ReceiveData>(
+    connection: &Connection,
+    self_mime: &str,
+    current_offer: Option<&DataOffer<T>>,
+    offer: Option<&DataOffer<T>>,
+    cached_read: &mut Option<ClipboardItem>,
+    written: &mut bool,
+) -> bool {
+    let (Some(current_offer), Some(offer)) = (current_offer, offer) else {
+        *cached_read = None;
+        return current_offer.is_some() || offer.is_some();
+    };
+    if offer.mime_types != current_offer.mime_types {
+        *cached_read = None;
+        return true;
+    }
+    // Offered by this application, whose contents only change when written
+    if offer.has_mime_type(self_mime) {
+        return mem::take(written);
+    }
+    let Some(current_item) = cached_read.take() else {
+        return true;
+    };
+    let item = offer.read_item(connection);
+    let changed = item.as_ref() != Some(&current_item);
+    *cached_read = item;
+    changed
+}
+
 pub(crate) struct Clipboard {
     connection: Connection,
     loop_handle: LoopHandle<'static, WaylandClientStatePtr>,
@@ -31,6 +93,9 @@ pub(crate) struct Clipboard {
     // Internal clipboard
     contents: Option<ClipboardItem>,
     primary_contents: Option<ClipboardItem>,
+    // Whether the contents were written since their last offer
+    written: bool,
+    primary_written: bool,
 
     // External clipboard
     cached_read: Option<ClipboardItem>,
@@ -74,6 +139,10 @@ impl<T: ReceiveData> DataOffer<T> {
         self.mime_types.push(mime_type)
     }
 
+    pub fn mime_types(&self) -> &[String] {
+        &self.mime_types
+    }
+
     fn has_mime_type(&self, mime_type: &str) -> bool {
         self.mime_types.iter().any(|t| t == mime_type)
     }
@@ -135,6 +204,35 @@ impl<T: ReceiveData> DataOffer<T> {
         }
         None
     }
+
+    /// Reads the HTML and RTF representations of the offer.
+    fn read_rich_entries(&self, connection: &Connection) -> Vec<ClipboardEntry> {
+        self.mime_types
+            .iter()
+            .filter(|mime_type| is_rich_clipboard_mime_type(mime_type))
+            .filter_map(|mime_type| {
+                let bytes = self.read_bytes(connection, mime_type)?;
+                rich_clipboard_entry(mime_type, bytes)
+            })
+            .collect()
+    }
+
+    fn read_item(&self, connection: &Connection) -> Option<ClipboardItem> {
+        let item = self
+            .read_text(connection)
+            .or_else(|| self.read_image(connection));
+        let rich_entries = self.read_rich_entries(connection);
+        match item {
+            Some(mut item) => {
+                item.entries.extend(rich_entries);
+                Some(item)
+            }
+            None if !rich_entries.is_empty() => Some(ClipboardItem {
+                entries: rich_entries,
+            }),
+            None => None,
+        }
+    }
 }
 
 impl Clipboard {
@@ -149,6 +247,8 @@ impl Clipboard {
 
             contents: None,
             primary_contents: None,
+            written: false,
+            primary_written: false,
 
             cached_read: None,
             current_offer: None,
@@ -159,39 +259,66 @@ impl Clipboard {
 
     pub fn set(&mut self, item: ClipboardItem) {
         self.contents = Some(item);
+        self.written = true;
     }
 
     pub fn set_primary(&mut self, item: ClipboardItem) {
         self.primary_contents = Some(item);
+        self.primary_written = true;
     }
 
-    pub fn set_offer(&mut self, data_offer: Option<DataOffer<WlDataOffer>>) {
-        self.cached_read = None;
+    /// Replaces the clipboard offer, returning whether the contents of the clipboard changed.
+    pub fn set_offer(&mut self, data_offer: Option<DataOffer<WlDataOffer>>) -> bool {
+        let changed = offer_changed(
+            &self.connection,
+            &self.self_mime,
+            self.current_offer.as_ref(),
+            data_offer.as_ref(),
+            &mut self.cached_read,
+            &mut self.written,
+        );
         self.current_offer = data_offer;
+        changed
     }
 
-    pub fn set_primary_offer(&mut self, data_offer: Option<DataOffer<ZwpPrimarySelectionOfferV1>>) {
-        self.cached_primary_read = None;
+    /// Replaces the primary selection offer, returning whether its contents changed.
+    pub fn set_primary_offer(
+        &mut self,
+        data_offer: Option<DataOffer<ZwpPrimarySelectionOfferV1>>,
+    ) -> bool {
+        let changed = offer_changed(
+            &self.connection,
+            &self.self_mime,
+            self.current_primary_offer.as_ref(),
+            data_offer.as_ref(),
+            &mut self.cached_primary_read,
+            &mut self.primary_written,
+        );
         self.current_primary_offer = data_offer;
+        changed
     }
 
     pub fn self_mime(&self) -> String {
         self.self_mime.clone()
     }
 
-    pub fn send(&self, _mime_type: String, fd: OwnedFd) {
-        if let Some(text) = self.contents.as_ref().and_then(|contents| contents.text()) {
-            self.send_internal(fd, text.as_bytes().to_owned());
+    pub fn send(&self, mime_type: String, fd: OwnedFd) {
+        if let Some(bytes) = self
+            .contents
+            .as_ref()
+            .and_then(|contents| representation(contents, &mime_type))
+        {
+            self.send_bytes(fd, bytes);
         }
     }
 
-    pub fn send_primary(&self, _mime_type: String, fd: OwnedFd) {
-        if let Some(text) = self
+    pub fn send_primary(&self, mime_type: String, fd: OwnedFd) {
+        if let Some(bytes) = self
             .primary_contents
             .as_ref()
-            .and_then(|contents| contents.text())
+            .and_then(|contents| representation(contents, &mime_type))
         {
-            self.send_internal(fd, text.as_bytes().to_owned());
+            self.send_bytes(fd, bytes);
         }
     }
 
@@ -205,14 +332,28 @@ impl Clipboard {
             return self.contents.clone();
         }
 
-        let item = offer
-            .read_text(&self.connection)
-            .or_else(|| offer.read_image(&self.connection))?;
+        let item = offer.read_item(&self.connection)?;
 
         self.cached_read = Some(item.clone());
         Some(item)
     }
 
+    /// Reads the data of the given mime type from the clipboard, if offered.
+    pub fn read_custom(&self, mime_type: &str) -> Option<Vec<u8>> {
+        let offer = self.current_offer.as_ref()?;
+        if offer.has_mime_type(&self.self_mime) {
+            return self
+                .contents
+                .as_ref()?
+                .custom(mime_type)
+                .map(<[u8]>::to_vec);
+        }
+        if !offer.has_mime_type(mime_type) {
+            return None;
+        }
+        offer.read_bytes(&self.connection, mime_type)
+    }
+
     pub fn read_primary(&mut self) -> Option<ClipboardItem> {
         let offer = self.current_primary_offer.as_ref()?;
         if let Some(cached) = self.cached_primary_read.clone() {
@@ -223,15 +364,13 @@ impl Clipboard {
             return self.primary_contents.clone();
         }
 
-        let item = offer
-            .read_text(&self.connection)
-            .or_else(|| offer.read_image(&self.connection))?;
+        let item = offer.read_item(&self.connection)?;
 
         self.cached_primary_read = Some(item.clone());
         Some(item)
     }
 
-    fn send_internal(&self, fd: OwnedFd, bytes: Vec<u8>) {
+    pub fn send_bytes(&self, fd: OwnedFd, bytes: Vec<u8>) {
         let mut written = 0;
         self.loop_handle
             .insert_source(
@@ -22,14 +22,16 @@ use wayland_protocols::{
     wp::fractional_scale::v1::client::wp_fractional_scale_v1,
     xdg::shell::client::xdg_toplevel::XdgToplevel,
 };
+use wayland_protocols_plasma::appmenu::client::org_kde_kwin_appmenu;
 use wayland_protocols_plasma::blur::client::org_kde_kwin_blur;
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1;
 
 use crate::{
-    AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptButton, PromptLevel, RequestFrameOptions,
-    ResizeEdge, Size, Tiling, WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControlArea, WindowControls, WindowDecorations, WindowParams,
+    AnyWindowHandle, Bounds, ClipboardItem, Decorations, DragAction, DragResult, Globals, GpuSpecs,
+    Modifiers, Output, Pixels, PlatformDisplay, PlatformInput, Point, PointerConstraint,
+    PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, Size, Subscription, Tiling,
+    WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControlArea, WindowControls, WindowDecorations, WindowParams,
     layer_shell::LayerShellNotSupportedError, px, size,
 };
 use crate::{
@@ -37,7 +39,10 @@ use crate::{
     platform::{
         PlatformAtlas, PlatformInputHandler, PlatformWindow,
         blade::{BladeContext, BladeRenderer, BladeSurfaceConfig},
-        linux::wayland::{display::WaylandDisplay, serial::SerialKind},
+        linux::{
+            wayland::{client::ConstrainedPointer, display::WaylandDisplay, serial::SerialKind},
+            xdg_desktop_portal::{app_menu::AppMenuHandle, idle_inhibit},
+        },
     },
 };
 use crate::{WindowKind, scene::Scene};
@@ -91,6 +96,7 @@ pub struct WaylandWindowState {
     app_id: Option<String>,
     appearance: WindowAppearance,
     blur: Option<org_kde_kwin_blur::OrgKdeKwinBlur>,
+    appmenu: Option<org_kde_kwin_appmenu::OrgKdeKwinAppmenu>,
     viewport: Option<wp_viewport::WpViewport>,
     outputs: HashMap<ObjectId, Output>,
     display: Option<(ObjectId, Output)>,
@@ -114,6 +120,7 @@ pub struct WaylandWindowState {
     in_progress_window_controls: Option<WindowControls>,
     window_controls: WindowControls,
     client_inset: Option<Pixels>,
+    constrained_pointer: Option<ConstrainedPointer>,
 }
 
 pub enum WaylandSurfaceState {
@@ -322,6 +329,7 @@ impl WaylandWindowState {
             surface,
             app_id: None,
             blur: None,
+            appmenu: None,
             viewport,
             globals,
             outputs: HashMap::default(),
@@ -346,6 +354,7 @@ impl WaylandWindowState {
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             client_inset: None,
+            constrained_pointer: None,
         })
     }
 
@@ -399,6 +408,12 @@ impl Drop for WaylandWindow {
         if let Some(blur) = &state.blur {
             blur.release();
         }
+        if let Some(appmenu) = &state.appmenu {
+            appmenu.release();
+        }
+        if let Some(constrained_pointer) = state.constrained_pointer.take() {
+            constrained_pointer.destroy();
+        }
 
         // Decorations must be destroyed before the xdg state.
         // See https://wayland.app/protocols/xdg-decoration-unstable-v1#zxdg_toplevel_decoration_v1
@@ -493,10 +508,40 @@ impl WaylandWindowStatePtr {
         self.state.borrow().surface.clone()
     }
 
+    /// Requests the window to be activated. Even though the activation is likely going to be
+    /// rejected, KWin and Mutter can use the app_id to visually indicate we're requesting attention.
+    pub fn request_activation(&self) {
+        let state = self.state.borrow();
+        if let (Some(activation), Some(app_id)) = (&state.globals.activation, state.app_id.clone())
+        {
+            state.client.set_pending_activation(state.surface.id());
+            let token = activation.get_activation_token(&state.globals.qh, ());
+            // The serial isn't exactly important here, since the activation is probably going to be rejected anyway.
+            let serial = state.client.get_serial(SerialKind::MousePress);
+            token.set_app_id(app_id);
+            token.set_serial(serial, &state.globals.seat);
+            token.set_surface(&state.surface);
+            token.commit();
+        }
+    }
+
     pub fn toplevel(&self) -> Option<xdg_toplevel::XdgToplevel> {
         self.state.borrow().surface_state.toplevel().cloned()
     }
 
+    /// Announces the exported application menu for this window to the compositor.
+    pub fn set_app_menu(&self, app_menu: &AppMenuHandle) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let Some(appmenu_manager) = &state.globals.appmenu_manager else {
+            return;
+        };
+        let appmenu = state
+            .appmenu
+            .get_or_insert_with(|| appmenu_manager.create(&state.surface, &state.globals.qh, ()));
+        appmenu.set_address(app_menu.service_name(), app_menu.object_path().to_string());
+    }
+
     pub fn ptr_eq(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.state, &other.state)
     }
@@ -532,6 +577,8 @@ impl WaylandWindowStatePtr {
 
                 if let Some(mut configure) = state.in_progress_configure.take() {
                     let got_unmaximized = state.maximized && !configure.maximized;
+                    let window_state_changed = state.fullscreen != configure.fullscreen
+                        || state.maximized != configure.maximized;
                     state.fullscreen = configure.fullscreen;
                     state.maximized = configure.maximized;
                     state.tiling = configure.tiling;
@@ -558,6 +605,13 @@ impl WaylandWindowStatePtr {
                     if let Some(size) = configure.size {
                         self.resize(size);
                     }
+                    // The window bounds change when the window gets maximized or fullscreen,
+                    // even when its size doesn't
+                    if window_state_changed
+                        && let Some(ref mut fun) = self.callbacks.borrow_mut().moved
+                    {
+                        fun();
+                    }
                 }
             }
             let mut state = self.state.borrow_mut();
@@ -893,6 +947,10 @@ impl WaylandWindowStatePtr {
         self.set_size_and_scale(None, Some(scale));
     }
 
+    pub fn has_constrained_pointer(&self) -> bool {
+        self.state.borrow().constrained_pointer.is_some()
+    }
+
     pub fn close(&self) {
         let mut callbacks = self.callbacks.borrow_mut();
         if let Some(fun) = callbacks.close.take() {
@@ -1095,21 +1153,39 @@ impl PlatformWindow for WaylandWindow {
         None
     }
 
-    fn activate(&self) {
-        // Try to request an activation token. Even though the activation is likely going to be rejected,
-        // KWin and Mutter can use the app_id to visually indicate we're requesting attention.
+    fn start_drag(
+        &self,
+        item: ClipboardItem,
+        actions: &[DragAction],
+    ) -> Option<Receiver<DragResult>> {
         let state = self.borrow();
-        if let (Some(activation), Some(app_id)) = (&state.globals.activation, state.app_id.clone())
-        {
-            state.client.set_pending_activation(state.surface.id());
-            let token = activation.get_activation_token(&state.globals.qh, ());
-            // The serial isn't exactly important here, since the activation is probably going to be rejected anyway.
-            let serial = state.client.get_serial(SerialKind::MousePress);
-            token.set_app_id(app_id);
-            token.set_serial(serial, &state.globals.seat);
-            token.set_surface(&state.surface);
-            token.commit();
+        state.client.start_drag(&state.surface, item, actions)
+    }
+
+    fn set_pointer_constraint(&self, constraint: Option<PointerConstraint>) {
+        let mut state = self.borrow_mut();
+        if let Some(constrained_pointer) = state.constrained_pointer.take() {
+            constrained_pointer.destroy();
         }
+        let constrained_pointer = constraint
+            .and_then(|constraint| state.client.constrain_pointer(&state.surface, constraint));
+        state.constrained_pointer = constrained_pointer;
+    }
+
+    fn inhibit_idle(&self, reason: &str) -> Subscription {
+        let state = self.borrow();
+        match &state.globals.idle_inhibit_manager {
+            // Only inhibits idling while the surface is visible
+            Some(manager) => {
+                let inhibitor = manager.create_inhibitor(&state.surface, &state.globals.qh, ());
+                Subscription::new(move || inhibitor.destroy())
+            }
+            None => idle_inhibit::inhibit_idle(&state.globals.executor, String::new(), reason),
+        }
+    }
+
+    fn activate(&self) {
+        self.0.request_activation();
     }
 
     fn is_active(&self) -> bool {
@@ -1,4 +1,4 @@
-use crate::{Capslock, RunnableVariant, TaskTiming, profiler, xcb_flush};
+use crate::{Capslock, RunnableVariant, TaskTiming, TrayId, profiler, xcb_flush};
 use anyhow::{Context as _, anyhow};
 use ashpd::WindowIdentifier;
 use calloop::{
@@ -7,12 +7,12 @@ use calloop::{
 };
 use collections::HashMap;
 use core::str;
-use http_client::Url;
+use futures::channel::oneshot;
 use log::Level;
-use smallvec::SmallVec;
 use std::{
     cell::RefCell,
     collections::{BTreeMap, HashSet},
+    mem,
     ops::Deref,
     path::PathBuf,
     rc::{Rc, Weak},
@@ -25,13 +25,14 @@ use x11rb::{
     cursor,
     errors::ConnectionError,
     protocol::randr::ConnectionExt as _,
+    protocol::xfixes::ConnectionExt as _,
     protocol::xinput::ConnectionExt,
     protocol::xkb::ConnectionExt as _,
     protocol::xproto::{
         AtomEnum, ChangeWindowAttributesAux, ClientMessageData, ClientMessageEvent,
         ConnectionExt as _, EventMask, Visibility,
     },
-    protocol::{Event, randr, render, xinput, xkb, xproto},
+    protocol::{Event, randr, render, xfixes, xinput, xkb, xproto},
     resource_manager::Database,
     wrapper::ConnectionExt as _,
     xcb_ffi::XCBConnection,
@@ -54,18 +55,27 @@ use crate::platform::{
     linux::{
         DEFAULT_CURSOR_ICON_NAME, LinuxClient, get_xkb_compose_state, is_within_click_distance,
         log_cursor_icon_warning, open_uri_internal,
-        platform::{DOUBLE_CLICK_INTERVAL, SCROLL_LINES},
+        platform::{
+            DOUBLE_CLICK_INTERVAL, SCROLL_LINES, drag_representations, drop_mime_types,
+            external_drop, keysym_name, register_tray_item,
+        },
         reveal_path_internal,
-        xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
+        xdg_desktop_portal::{Event as XDPEvent, XDPEventSource, global_shortcuts},
     },
 };
-use crate::platform::linux::xdg_desktop_portal::status_notifier::item::{
-    StatusNotifierItem, StatusNotifierItemEvents, StatusNotifierItemOptions,
+use crate::platform::linux::freedesktop;
+use crate::platform::linux::single_instance::SingleInstance;
+use crate::platform::linux::xdg_desktop_portal::app_menu::{AppMenu, AppMenuHandle};
+use crate::platform::linux::xdg_desktop_portal::launcher_entry::LauncherEntry;
+use crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::{
+    DBusMenuEvents, Menu as DBusMenu,
 };
+use crate::platform::linux::xdg_desktop_portal::status_notifier::item::StatusNotifierItemOptions;
 use crate::{
-    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DisplayId, FileDropEvent, Keystroke,
-    LinuxKeyboardLayout, Modifiers, ModifiersChangedEvent, MouseButton, Pixels, Platform,
-    PlatformDisplay, PlatformInput, PlatformKeyboardLayout, Point, RequestFrameOptions,
+    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DisplayId, DragAction, DragResult,
+    FileDropEvent, GlobalHotkeyId, Keystroke, LinuxKeyboardLayout, Modifiers,
+    ModifiersChangedEvent, MouseButton, Pixels, Platform, PlatformDisplay, PlatformInput,
+    PlatformKeyboardLayout, Point, PointerConstraint, RawMouseMotionEvent, RequestFrameOptions,
     ScrollDelta, Size, TouchPhase, WindowParams, X11Window, modifiers_from_xinput_info, point, px,
 };
 
@@ -146,11 +156,63 @@ impl From<xim::ClientError> for EventHandlerError {
 #[derive(Debug, Default)]
 pub struct Xdnd {
     other_window: xproto::Window,
-    drag_type: u32,
-    retrieved: bool,
+    /// The types of the drag still to convert, as atoms and MIME types.
+    pending_types: Vec<(xproto::Atom, String)>,
+    representations: Vec<(String, Vec<u8>)>,
+    requested: bool,
+    time: xproto::Timestamp,
     position: Point<Pixels>,
 }
 
+/// A drag of data out of one of our windows, see [`X11ClientStatePtr::start_drag`].
+pub struct XdndSource {
+    source_window: xproto::Window,
+    representations: Vec<(xproto::Atom, Vec<u8>)>,
+    /// The action atoms, in order of preference.
+    actions: Vec<xproto::Atom>,
+    target: Option<XdndTarget>,
+    dropped: bool,
+    /// Cancels the drag when the target doesn't finish it in time after the drop.
+    drop_timeout: Option<RegistrationToken>,
+    result: oneshot::Sender<DragResult>,
+}
+
+/// A lock or confinement of the pointer to one of our windows, see
+/// [`X11ClientStatePtr::set_pointer_constraint`].
+struct PointerGrab {
+    window: xproto::Window,
+    /// The input-only child window the pointer is confined to.
+    confine_window: Option<xproto::Window>,
+    /// Where the pointer is held while locked, relative to the window.
+    lock_position: Option<(i16, i16)>,
+}
+
+/// A hotkey registered with [`LinuxClient::grab_global_hotkey`], grabbed on the root window.
+struct GlobalHotkeyGrab {
+    keystroke: Keystroke,
+    /// The modifiers of the keystroke, grabbed together with each combination of the locks.
+    modifiers: xproto::ModMask,
+    /// The keycode producing the key in the current keymap, if there is one.
+    keycode: Option<xproto::Keycode>,
+}
+
+/// The XDND aware window under the pointer during a drag.
+struct XdndTarget {
+    window: xproto::Window,
+    /// The window messages are sent to: the target itself, or the proxy named by its XdndProxy.
+    proxy: xproto::Window,
+    version: u32,
+    accepted_action: Option<xproto::Atom>,
+    // Positions are only sent once the previous one is answered with a status
+    awaiting_status: bool,
+    pending_position: Option<(u32, xproto::Timestamp)>,
+}
+
+// The lowest version of the protocol with the XdndFinished message
+const XDND_MIN_VERSION: u32 = 3;
+const XDND_VERSION: u32 = 5;
+const XDND_DROP_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 struct PointerDeviceState {
     horizontal: ScrollAxisState,
@@ -219,6 +281,9 @@ pub struct X11ClientState {
     pub(crate) clipboard: Clipboard,
     pub(crate) clipboard_item: Option<ClipboardItem>,
     pub(crate) xdnd_state: Xdnd,
+    xdnd_source: Option<XdndSource>,
+    pointer_grab: Option<PointerGrab>,
+    global_hotkeys: HashMap<GlobalHotkeyId, GlobalHotkeyGrab>,
 }
 
 #[derive(Clone)]
@@ -249,6 +314,126 @@ impl X11ClientStatePtr {
             state.keyboard_focused_window = None;
         }
         state.cursor_styles.remove(&x_window);
+        if state
+            .pointer_grab
+            .as_ref()
+            .is_some_and(|grab| grab.window == x_window)
+        {
+            state.release_pointer_grab();
+        }
+    }
+
+    /// Locks or confines the pointer to the window by grabbing it, or releases it. While the
+    /// pointer is grabbed, the raw motion events of the root window are selected.
+    pub fn set_pointer_constraint(
+        &self,
+        x_window: xproto::Window,
+        constraint: Option<PointerConstraint>,
+    ) {
+        let Some(client) = self.get_client() else {
+            return;
+        };
+        let mut state = client.0.borrow_mut();
+        // There's only one pointer grab, which may have been taken over by another window
+        if constraint.is_some()
+            || state
+                .pointer_grab
+                .as_ref()
+                .is_some_and(|grab| grab.window == x_window)
+        {
+            state.release_pointer_grab();
+        }
+        if let Some(constraint) = constraint {
+            state.pointer_grab = grab_pointer(&state, x_window, constraint).log_err();
+        }
+        xcb_flush(&state.xcb_connection);
+    }
+
+    /// Starts a drag of the data from the window, within the implicit grab of the pressed button.
+    pub fn start_drag(
+        &self,
+        x_window: xproto::Window,
+        item: ClipboardItem,
+        actions: &[DragAction],
+    ) -> Option<oneshot::Receiver<DragResult>> {
+        let client = self.get_client()?;
+        let mut state = client.0.borrow_mut();
+        let atoms = state.atoms;
+
+        let representations = drag_representations(&item)
+            .into_iter()
+            .filter_map(|(mime_type, bytes)| {
+                let atom = get_reply(
+                    || format!("Failed to intern atom {mime_type}"),
+                    state
+                        .xcb_connection
+                        .intern_atom(false, mime_type.as_bytes()),
+                )
+                .log_err()?
+                .atom;
+                Some((atom, bytes))
+            })
+            .collect::<Vec<_>>();
+        let types = representations
+            .iter()
+            .map(|(atom, _)| *atom)
+            .collect::<Vec<_>>();
+        check_reply(
+            || "Failed to set XdndTypeList property",
+            state.xcb_connection.change_property32(
+                xproto::PropMode::REPLACE,
+                x_window,
+                atoms.XdndTypeList,
+                atoms.XA_ATOM,
+                &types,
+            ),
+        )
+        .log_err()?;
+        check_reply(
+            || "Failed to own the XdndSelection",
+            state.xcb_connection.set_selection_owner(
+                x_window,
+                atoms.XdndSelection,
+                x11rb::CURRENT_TIME,
+            ),
+        )
+        .log_err()?;
+
+        let mut actions = actions
+            .iter()
+            .map(|action| match action {
+                DragAction::Copy => atoms.XdndActionCopy,
+                DragAction::Move => atoms.XdndActionMove,
+                DragAction::Link => atoms.XdndActionLink,
+            })
+            .collect::<Vec<_>>();
+        if actions.is_empty() {
+            actions.push(atoms.XdndActionCopy);
+        }
+
+        // Replacing a previous drag cancels it
+        if let Some(previous) = state.take_xdnd_source()
+            && let Some(target) = previous.target
+        {
+            xdnd_send_message(
+                &state.xcb_connection,
+                atoms.XdndLeave,
+                &target,
+                [previous.source_window, 0, 0, 0, 0],
+            );
+        }
+        let (result, receiver) = oneshot::channel();
+        state.xdnd_source = Some(XdndSource {
+            source_window: x_window,
+            representations,
+            actions,
+            target: None,
+            dropped: false,
+            drop_timeout: None,
+            result,
+        });
+        xcb_flush(&state.xcb_connection);
+        Some(receiver)
     }
 
     pub fn update_ime_position(&self, bounds: Bounds<Pixels>) {
@@ -360,6 +545,7 @@ impl X11Client {
         xcb_connection.prefetch_extension_information(randr::X11_EXTENSION_NAME)?;
         xcb_connection.prefetch_extension_information(render::X11_EXTENSION_NAME)?;
         xcb_connection.prefetch_extension_information(xinput::X11_EXTENSION_NAME)?;
+        xcb_connection.prefetch_extension_information(xfixes::X11_EXTENSION_NAME)?;
 
         // Announce to X server that XInput up to 2.1 is supported. To increase this to 2.2 and
         // beyond, support for touch events would need to be added.
@@ -421,6 +607,27 @@ impl X11Client {
             ),
         )?;
 
+        // Selection owner changes are only reported through XFixes, notify the application of
+        // them when it's available.
+        if get_reply(
+            || "XFixes QueryVersion failed",
+            xcb_connection.xfixes_query_version(5, 0),
+        )
+        .log_err()
+        .is_some()
+        {
+            let events = xfixes::SelectionEventMask::SET_SELECTION_OWNER
+                | xfixes::SelectionEventMask::SELECTION_WINDOW_DESTROY
+                | xfixes::SelectionEventMask::SELECTION_CLIENT_CLOSE;
+            for selection in [atoms.CLIPBOARD, AtomEnum::PRIMARY.into()] {
+                check_reply(
+                    || "Failed to select XFixes selection events",
+                    xcb_connection.xfixes_select_selection_input(root, selection, events),
+                )
+                .log_err();
+            }
+        }
+
         let xkb_context = xkbc::Context::new(xkbc::CONTEXT_NO_FLAGS);
         let xkb_device_id = xkbc::x11::get_core_keyboard_device_id(&xcb_connection);
         let xkb_state = {
@@ -543,6 +750,9 @@ impl X11Client {
             clipboard,
             clipboard_item: None,
             xdnd_state: Xdnd::default(),
+            xdnd_source: None,
+            pointer_grab: None,
+            global_hotkeys: HashMap::default(),
         }))))
     }
 
@@ -808,20 +1018,15 @@ impl X11Client {
 
                 if event.type_ == state.atoms.XdndEnter {
                     state.xdnd_state.other_window = atom;
-                    if (arg1 & 0x1) == 0x1 {
-                        state.xdnd_state.drag_type = xdnd_get_supported_atom(
-                            &state.xcb_connection,
-                            &state.atoms,
-                            state.xdnd_state.other_window,
-                        );
+                    let types = if (arg1 & 0x1) == 0x1 {
+                        xdnd_get_types(&state.xcb_connection, &state.atoms, atom)
                     } else {
-                        if let Some(atom) = [arg2, arg3, arg4]
+                        [arg2, arg3, arg4]
                             .into_iter()
-                            .find(|atom| xdnd_is_atom_supported(*atom, &state.atoms))
-                        {
-                            state.xdnd_state.drag_type = atom;
-                        }
-                    }
+                            .filter(|atom| *atom != x11rb::NONE)
+                            .collect()
+                    };
+                    state.xdnd_state.pending_types = xdnd_drop_types(&state.xcb_connection, &types);
                 } else if event.type_ == state.atoms.XdndLeave {
                     let position = state.xdnd_state.position;
                     drop(state);
@@ -837,18 +1042,10 @@ impl X11Client {
                         state.xdnd_state.position =
                             Point::new(Pixels(pos.win_x as f32), Pixels(pos.win_y as f32));
                     }
-                    if !state.xdnd_state.retrieved {
-                        check_reply(
-                            || "Failed to convert selection for drag and drop",
-                            state.xcb_connection.convert_selection(
-                                event.window,
-                                state.atoms.XdndSelection,
-                                state.xdnd_state.drag_type,
-                                state.atoms.XDND_DATA,
-                                arg3,
-                            ),
-                        )
-                        .log_err();
+                    if !state.xdnd_state.requested {
+                        state.xdnd_state.requested = true;
+                        state.xdnd_state.time = arg3;
+                        state.xdnd_convert_next(event.window);
                     }
                     xdnd_send_status(
                         &state.xcb_connection,
@@ -873,40 +1070,62 @@ impl X11Client {
                     window
                         .handle_input(PlatformInput::FileDrop(FileDropEvent::Submit { position }));
                     self.0.borrow_mut().xdnd_state = Xdnd::default();
+                } else if event.type_ == state.atoms.XdndStatus {
+                    drop(state);
+                    self.xdnd_source_status(event.data.as_data32());
+                } else if event.type_ == state.atoms.XdndFinished {
+                    drop(state);
+                    self.xdnd_source_finished(event.data.as_data32());
+                }
+            }
+            Event::SelectionRequest(event) => {
+                if event.selection == self.0.borrow().atoms.XdndSelection {
+                    self.xdnd_source_send_data(event);
                 }
             }
             Event::SelectionNotify(event) => {
                 let window = self.get_window(event.requestor)?;
                 let mut state = self.0.borrow_mut();
-                let reply = get_reply(
-                    || "Failed to get XDND_DATA",
-                    state.xcb_connection.get_property(
-                        false,
-                        event.requestor,
-                        state.atoms.XDND_DATA,
-                        AtomEnum::ANY,
-                        0,
-                        1024,
-                    ),
-                )
-                .log_err();
-                let Some(reply) = reply else {
+                if !state.xdnd_state.requested || state.xdnd_state.pending_types.is_empty() {
+                    return Some(());
+                }
+                let (_, mime_type) = state.xdnd_state.pending_types.remove(0);
+                // The source refuses to convert to the type without a property
+                if event.property != x11rb::NONE
+                    && let Some(reply) = get_reply(
+                        || "Failed to get XDND_DATA",
+                        state.xcb_connection.get_property(
+                            false,
+                            event.requestor,
+                            state.atoms.XDND_DATA,
+                            AtomEnum::ANY,
+                            0,
+                            u32::MAX / 4,
+                        ),
+                    )
+                    .log_err()
+                {
+                    state
+                        .xdnd_state
+                        .representations
+                        .push((mime_type, reply.value));
+                }
+                if !state.xdnd_state.pending_types.is_empty() {
+                    state.xdnd_convert_next(event.requestor);
+                    return Some(());
+                }
+
+                let data = external_drop(mem::take(&mut state.xdnd_state.representations));
+                if data.is_empty() {
                     return Some(());
-                };
-                if let Ok(file_list) = str::from_utf8(&reply.value) {
-                    let paths: SmallVec<[_; 2]> = file_list
-                        .lines()
-                        .filter_map(|path| Url::parse(path).log_err())
-                        .filter_map(|url| url.to_file_path().log_err())
-                        .collect();
-                    let input = PlatformInput::FileDrop(FileDropEvent::Entered {
-                        position: state.xdnd_state.position,
-                        paths: crate::ExternalPaths(paths),
-                    });
-                    drop(state);
-                    window.handle_input(input);
-                    self.0.borrow_mut().xdnd_state.retrieved = true;
                 }
+                let input = PlatformInput::FileDrop(FileDropEvent::Entered {
+                    position: state.xdnd_state.position,
+                    paths: data.paths.clone(),
+                    data,
+                });
+                drop(state);
+                window.handle_input(input);
             }
             Event::ConfigureNotify(event) => {
                 let bounds = Bounds {
@@ -937,6 +1156,7 @@ impl X11Client {
                 window.set_active(true);
                 let mut state = self.0.borrow_mut();
                 state.keyboard_focused_window = Some(event.event);
+                state.common.clear_launcher_urgency();
                 if let Some(handler) = state.xim_handler.as_mut() {
                     handler.window = event.event;
                 }
@@ -972,6 +1192,7 @@ impl X11Client {
                     )
                 };
                 state.xkb = xkb_state;
+                state.regrab_global_hotkeys();
                 drop(state);
                 self.handle_keyboard_layout_change();
             }
@@ -1014,6 +1235,25 @@ impl X11Client {
                     self.handle_keyboard_layout_change();
                 }
             }
+            Event::KeyPress(event) if event.event == event.root => {
+                // Only the keys grabbed for global hotkeys are delivered to the root window
+                let state = self.0.borrow();
+                let modifiers = modifiers_from_state(event.state);
+                let id = state.global_hotkeys.iter().find_map(|(id, grab)| {
+                    (grab.keycode == Some(event.detail) && grab.keystroke.modifiers == modifiers)
+                        .then_some(*id)
+                })?;
+                let callback = state.common.global_shortcuts.callback();
+                drop(state);
+                global_shortcuts::activate(&callback, id);
+            }
+            Event::KeyPress(event)
+                if self.is_dragging()
+                    && self.0.borrow().xkb.key_get_one_sym(event.detail.into())
+                        == xkbc::Keysym::Escape =>
+            {
+                self.xdnd_source_cancel();
+            }
             Event::KeyPress(event) => {
                 let window = self.get_window(event.event)?;
                 let mut state = self.0.borrow_mut();
@@ -1182,6 +1422,9 @@ impl X11Client {
             }
             Event::XinputButtonRelease(event) => {
                 let window = self.get_window(event.event)?;
+                if self.is_dragging() {
+                    self.xdnd_source_release(event.time);
+                }
                 let mut state = self.0.borrow_mut();
                 let modifiers = modifiers_from_xinput_info(event.mods);
                 state.modifiers = modifiers;
@@ -1207,6 +1450,11 @@ impl X11Client {
             }
             Event::XinputMotion(event) => {
                 let window = self.get_window(event.event)?;
+                // The pointer stays grabbed by the source window during a drag out of it
+                if self.is_dragging() {
+                    self.xdnd_source_motion(event.root_x >> 16, event.root_y >> 16, event.time);
+                    return Some(());
+                }
                 let mut state = self.0.borrow_mut();
                 let pressed_button = pressed_button_from_mask(event.button_mask[0]);
                 let position = point(
@@ -1217,7 +1465,8 @@ impl X11Client {
                 state.modifiers = modifiers;
                 drop(state);
 
-                if event.valuator_mask[0] & 3 != 0 {
+                // The locked pointer is warped back in place after every motion
+                if event.valuator_mask[0] & 3 != 0 && !self.is_pointer_locked() {
                     window.handle_input(PlatformInput::MouseMove(crate::MouseMoveEvent {
                         position,
                         pressed_button,
@@ -1238,12 +1487,46 @@ impl X11Client {
                     }
                 }
             }
+            Event::XinputRawMotion(event) => {
+                let state = self.0.borrow();
+                let Some(grab) = &state.pointer_grab else {
+                    return Some(());
+                };
+                let x_window = grab.window;
+                if let Some((x, y)) = grab.lock_position {
+                    check_reply(
+                        || "X11 WarpPointer failed.",
+                        state
+                            .xcb_connection
+                            .warp_pointer(x11rb::NONE, x_window, 0, 0, 0, 0, x, y),
+                    )
+                    .log_err();
+                }
+                let delta = raw_motion_delta(&event);
+                let input = PlatformInput::RawMouseMotion(RawMouseMotionEvent {
+                    delta: point(
+                        px(delta.x / state.scale_factor),
+                        px(delta.y / state.scale_factor),
+                    ),
+                    modifiers: state.modifiers,
+                });
+                drop(state);
+
+                let window = self.get_window(x_window)?;
+                window.handle_input(input);
+            }
             Event::XinputEnter(event) if event.mode == xinput::NotifyMode::NORMAL => {
                 let window = self.get_window(event.event)?;
                 window.set_hovered(true);
                 let mut state = self.0.borrow_mut();
                 state.mouse_focused_window = Some(event.event);
             }
+            // Another client grabbing the pointer ends the implicit grab a drag relies on
+            Event::XinputLeave(event)
+                if event.mode == xinput::NotifyMode::GRAB && self.is_dragging() =>
+            {
+                self.xdnd_source_cancel();
+            }
             Event::XinputLeave(event) if event.mode == xinput::NotifyMode::NORMAL => {
                 let mut state = self.0.borrow_mut();
 
@@ -1289,12 +1572,321 @@ impl X11Client {
                     reset_pointer_device_scroll_positions(pointer);
                 }
             }
+            Event::XfixesSelectionNotify(event) => {
+                let mut state = self.0.borrow_mut();
+                if event.selection == state.atoms.CLIPBOARD {
+                    if let Some(mut callback) = state.common.callbacks.clipboard_change.take() {
+                        drop(state);
+                        callback();
+                        self.0.borrow_mut().common.callbacks.clipboard_change = Some(callback);
+                    }
+                } else if event.selection == u32::from(AtomEnum::PRIMARY) {
+                    if let Some(mut callback) =
+                        state.common.callbacks.primary_selection_change.take()
+                    {
+                        drop(state);
+                        callback();
+                        self.0
+                            .borrow_mut()
+                            .common
+                            .callbacks
+                            .primary_selection_change = Some(callback);
+                    }
+                }
+            }
             _ => {}
         };
 
         Some(())
     }
 
+    fn is_pointer_locked(&self) -> bool {
+        self.0
+            .borrow()
+            .pointer_grab
+            .as_ref()
+            .is_some_and(|grab| grab.lock_position.is_some())
+    }
+
+    fn is_dragging(&self) -> bool {
+        self.0
+            .borrow()
+            .xdnd_source
+            .as_ref()
+            .is_some_and(|source| !source.dropped)
+    }
+
+    fn xdnd_source_motion(&self, root_x: i32, root_y: i32, time: xproto::Timestamp) {
+        let mut state = self.0.borrow_mut();
+        let connection = state.xcb_connection.clone();
+        let atoms = state.atoms;
+        let root = connection.setup().roots[state.x_root_index].root;
+        let target = xdnd_find_target(&connection, &atoms, root, root_x as i16, root_y as i16);
+        let Some(source) = state.xdnd_source.as_mut() else {
+            return;
+        };
+
+        if source.target.as_ref().map(|target| target.window) != target.map(|(window, _, _)| window)
+        {
+            if let Some(previous) = source.target.take() {
+                xdnd_send_message(
+                    &connection,
+                    atoms.XdndLeave,
+                    &previous,
+                    [source.source_window, 0, 0, 0, 0],
+                );
+            }
+            if let Some((window, proxy, version)) = target {
+                let mut data = [
+                    source.source_window,
+                    version.min(XDND_VERSION) << 24,
+                    0,
+                    0,
+                    0,
+                ];
+                // The target reads the XdndTypeList property for more than three types
+                if source.representations.len() > 3 {
+                    data[1] |= 1;
+                }
+                for (slot, (atom, _)) in data[2..].iter_mut().zip(&source.representations) {
+                    *slot = *atom;
+                }
+                let target = XdndTarget {
+                    window,
+                    proxy,
+                    version,
+                    accepted_action: None,
+                    awaiting_status: false,
+                    pending_position: None,
+                };
+                xdnd_send_message(&connection, atoms.XdndEnter, &target, data);
+                source.target = Some(target);
+            }
+        }
+
+        if let Some(target) = source.target.as_mut() {
+            let position = ((root_x.max(0) as u32) << 16) | (root_y.max(0) as u32 & 0xffff);
+            if target.awaiting_status {
+                target.pending_position = Some((position, time));
+            } else {
+                xdnd_send_message(
+                    &connection,
+                    atoms.XdndPosition,
+                    target,
+                    [source.source_window, 0, position, time, source.actions[0]],
+                );
+                target.awaiting_status = true;
+            }
+        }
+        xcb_flush(&connection);
+    }
+
+    fn xdnd_source_status(&self, [target_window, flags, _, _, action]: [u32; 5]) {
+        let mut state = self.0.borrow_mut();
+        let connection = state.xcb_connection.clone();
+        let atoms = state.atoms;
+        let Some(source) = state.xdnd_source.as_mut() else {
+            return;
+        };
+        let Some(target) = source
+            .target
+            .as_mut()
+            .filter(|target| target.window == target_window)
+        else {
+            return;
+        };
+
+        target.accepted_action = (flags & 1 == 1).then(|| {
+            if action == x11rb::NONE {
+                atoms.XdndActionCopy
+            } else {
+                action
+            }
+        });
+        target.awaiting_status = false;
+        if let Some((position, time)) = target.pending_position.take() {
+            xdnd_send_message(
+                &connection,
+                atoms.XdndPosition,
+                target,
+                [source.source_window, 0, position, time, source.actions[0]],
+            );
+            target.awaiting_status = true;
+            xcb_flush(&connection);
+        }
+    }
+
+    fn xdnd_source_release(&self, time: xproto::Timestamp) {
+        let mut state = self.0.borrow_mut();
+        let connection = state.xcb_connection.clone();
+        let loop_handle = state.loop_handle.clone();
+        let atoms = state.atoms;
+        let Some(source) = state.xdnd_source.as_mut() else {
+            return;
+        };
+        let Some(target) = source
+            .target
+            .as_ref()
+            .filter(|target| target.accepted_action.is_some())
+        else {
+            drop(state);
+            self.xdnd_source_cancel();
+            return;
+        };
+
+        xdnd_send_message(
+            &connection,
+            atoms.XdndDrop,
+            target,
+            [source.source_window, 0, time, 0, 0],
+        );
+        xcb_flush(&connection);
+        // The drag finishes once the target has read the data
+        source.dropped = true;
+        source.drop_timeout = loop_handle
+            .insert_source(
+                calloop::timer::Timer::from_duration(XDND_DROP_TIMEOUT),
+                |_, _, client| {
+                    if let Some(source) = client.0.borrow_mut().xdnd_source.as_mut() {
+                        source.drop_timeout = None;
+                    }
+                    log::warn!("XDnD target didn't finish the drop in time");
+                    client.xdnd_source_cancel();
+                    calloop::timer::TimeoutAction::Drop
+                },
+            )
+            .log_err();
+    }
+
+    /// Cancels the drag out of one of our windows, leaving the target it's over.
+    fn xdnd_source_cancel(&self) {
+        let mut state = self.0.borrow_mut();
+        let Some(source) = state.take_xdnd_source() else {
+            return;
+        };
+        if let Some(target) = &source.target {
+            xdnd_send_message(
+                &state.xcb_connection,
+                state.atoms.XdndLeave,
+                target,
+                [source.source_window, 0, 0, 0, 0],
+            );
+            xcb_flush(&state.xcb_connection);
+        }
+        source.result.send(DragResult::Cancelled).ok();
+    }
+
+    fn xdnd_source_finished(&self, [target_window, flags, action, _, _]: [u32; 5]) {
+        let mut state = self.0.borrow_mut();
+        let atoms = state.atoms;
+        let is_finished = state.xdnd_source.as_ref().is_some_and(|source| {
+            source.dropped
+                && source
+                    .target
+                    .as_ref()
+                    .is_some_and(|target| target.window == target_window)
+        });
+        if !is_finished {
+            return;
+        }
+        let Some(XdndSource {
+            target: Some(target),
+            result,
+            ..
+        }) = state.take_xdnd_source()
+        else {
+            return;
+        };
+
+        // Only version 5 reports whether the drop succeeded and the action performed
+        let (succeeded, action) = if target.version >= 5 {
+            (flags & 1 == 1, action)
+        } else {
+            (true, target.accepted_action.unwrap_or(atoms.XdndActionCopy))
+        };
+        let drag_result = if !succeeded {
+            DragResult::Cancelled
+        } else if action == atoms.XdndActionMove {
+            DragResult::Dropped(DragAction::Move)
+        } else if action == atoms.XdndActionLink {
+            DragResult::Dropped(DragAction::Link)
+        } else {
+            DragResult::Dropped(DragAction::Copy)
+        };
+        result.send(drag_result).ok();
+    }
+
+    fn xdnd_source_send_data(&self, event: xproto::SelectionRequestEvent) {
+        let state = self.0.borrow();
+        let atoms = state.atoms;
+        let connection = &state.xcb_connection;
+        // Obsolete clients don't name a property
+        let property = if event.property == x11rb::NONE {
+            event.target
+        } else {
+            event.property
+        };
+
+        // Data beyond the maximum request size would need the INCR protocol, which isn't
+        // supported, so it fails to be served
+        let served = state.xdnd_source.as_ref().and_then(|source| {
+            if event.target == atoms.TARGETS {
+                let targets = source
+                    .representations
+                    .iter()
+                    .map(|(atom, _)| *atom)
+                    .collect::<Vec<_>>();
+                check_reply(
+                    || "Failed to set XDnD targets",
+                    connection.change_property32(
+                        xproto::PropMode::REPLACE,
+                        event.requestor,
+                        property,
+                        atoms.XA_ATOM,
+                        &targets,
+                    ),
+                )
+                .log_err()
+            } else {
+                let (_, bytes) = source
+                    .representations
+                    .iter()
+                    .find(|(atom, _)| *atom == event.target)?;
+                check_reply(
+                    || "Failed to set XDnD data",
+                    connection.change_property8(
+                        xproto::PropMode::REPLACE,
+                        event.requestor,
+                        property,
+                        event.target,
+                        bytes,
+                    ),
+                )
+                .log_err()
+            }
+        });
+
+        let notify = xproto::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: event.time,
+            requestor: event.requestor,
+            selection: event.selection,
+            target: event.target,
+            property: if served.is_some() {
+                property
+            } else {
+                x11rb::NONE
+            },
+        };
+        check_reply(
+            || "Failed to send XDnD selection notify event",
+            connection.send_event(false, event.requestor, EventMask::NO_EVENT, notify),
+        )
+        .log_err();
+        xcb_flush(connection);
+    }
+
     fn handle_xim_callback_event(&self, event: XimCallbackEvent) {
         match event {
             XimCallbackEvent::XimXEvent(event) => {
@@ -1524,6 +2116,9 @@ impl LinuxClient for X11Client {
         };
 
         state.windows.insert(x_window, window_ref);
+        if let Some(app_menu) = state.common.app_menu.handle.clone() {
+            state.register_app_menu(x_window, &app_menu);
+        }
         Ok(Box::new(window))
     }
 
@@ -1573,8 +2168,8 @@ impl LinuxClient for X11Client {
         let state = self.0.borrow_mut();
         state
             .clipboard
-            .set_text(
-                std::borrow::Cow::Owned(item.text().unwrap_or_default()),
+            .set_item(
+                &item,
                 clipboard::ClipboardKind::Primary,
                 clipboard::WaitConfig::None,
             )
@@ -1586,8 +2181,8 @@ impl LinuxClient for X11Client {
         let mut state = self.0.borrow_mut();
         state
             .clipboard
-            .set_text(
-                std::borrow::Cow::Owned(item.text().unwrap_or_default()),
+            .set_item(
+                &item,
                 clipboard::ClipboardKind::Clipboard,
                 clipboard::WaitConfig::None,
             )
@@ -1637,6 +2232,26 @@ impl LinuxClient for X11Client {
         event_loop.run(None, &mut self.clone(), |_| {}).log_err();
     }
 
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        let state = self.0.borrow_mut();
+        if state
+            .clipboard
+            .is_owner(clipboard::ClipboardKind::Clipboard)
+        {
+            return state
+                .clipboard_item
+                .as_ref()?
+                .custom(mime)
+                .map(<[u8]>::to_vec);
+        }
+        state
+            .clipboard
+            .get_custom(clipboard::ClipboardKind::Clipboard, mime)
+            .context("X11: Failed to read from clipboard")
+            .log_with_level(log::Level::Debug)
+            .flatten()
+    }
+
     fn active_window(&self) -> Option<AnyWindowHandle> {
         let state = self.0.borrow();
         state.keyboard_focused_window.and_then(|focused_window| {
@@ -1701,42 +2316,182 @@ impl LinuxClient for X11Client {
 
     fn set_tray_item(
         &self,
+        id: TrayId,
         options: StatusNotifierItemOptions,
-        menu: Option<crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::Menu>,
+        menu: Option<DBusMenu>,
     ) {
-        let state = self.0.clone();
+        register_tray_item(
+            &self.0,
+            id,
+            options,
+            menu,
+            |state| (&mut state.common, &state.loop_handle),
+            |client| Some(client.0.clone()),
+        );
+    }
+
+    fn remove_tray_item(&self, id: TrayId) {
+        let mut state = self.0.borrow_mut();
+        if let Some(tray) = state.common.trays.remove(&id)
+            && let Some(token) = tray.item_token
+        {
+            state.loop_handle.remove(token);
+        }
+    }
+
+    fn set_urgency_hint(&self, urgent: bool) {
+        let state = self.0.borrow();
+        for window in state.windows.values() {
+            window.window.set_demands_attention(urgent);
+        }
+    }
+
+    fn grab_global_hotkey(
+        &self,
+        id: GlobalHotkeyId,
+        keystroke: &Keystroke,
+    ) -> anyhow::Result<bool> {
+        let mut state = self.0.borrow_mut();
+        // Release the previous grab first, it may be for the same key
+        if let Some(previous) = state.global_hotkeys.remove(&id) {
+            state.ungrab_global_hotkey(&previous);
+        }
+        let modifiers = global_hotkey_modifiers(keystroke);
+        let keycode = state.grab_global_hotkey(keystroke, modifiers);
+        xcb_flush(&state.xcb_connection);
+        let grab = GlobalHotkeyGrab {
+            keystroke: keystroke.clone(),
+            modifiers,
+            keycode: Some(keycode?),
+        };
+        state.global_hotkeys.insert(id, grab);
+        Ok(true)
+    }
+
+    fn ungrab_global_hotkey(&self, id: GlobalHotkeyId) {
+        let mut state = self.0.borrow_mut();
+        if let Some(grab) = state.global_hotkeys.remove(&id) {
+            state.ungrab_global_hotkey(&grab);
+            xcb_flush(&state.xcb_connection);
+        }
+    }
+
+    fn serve_single_instance(&self, single_instance: SingleInstance) {
+        self.0
+            .borrow()
+            .loop_handle
+            .insert_source(single_instance, |launch, _, client| {
+                let mut state = client.0.borrow_mut();
+                if !launch.urls.is_empty()
+                    && let Some(mut callback) = state.common.callbacks.open_urls.take()
+                {
+                    drop(state);
+                    callback(launch.urls);
+                    state = client.0.borrow_mut();
+                    state.common.callbacks.open_urls = Some(callback);
+                }
+                let window = state
+                    .keyboard_focused_window
+                    .and_then(|x_window| state.windows.get(&x_window))
+                    .or_else(|| state.windows.values().next())
+                    .map(|window| window.window.clone());
+                drop(state);
+                if let Some(window) = window {
+                    window.activate();
+                }
+            })
+            .log_err();
+    }
+
+    fn create_launcher_entry(&self) {
         self.0
             .borrow()
             .common
             .foreground_executor
             .spawn({
-                let state = state.clone();
+                let state = self.0.clone();
                 async move {
-                    // Build the item and let it host the DBusMenu internally
-                    let item = StatusNotifierItem::new(1, options, menu).await.log_err();
-                    if let Some(item) = item {
-                        let mut state = state.borrow_mut();
-                        if let Some(token) = state.common.tray_item_token {
-                            state.loop_handle.remove(token);
-                        }
-                        state.common.tray_item_token = state
+                    let launcher_entry = LauncherEntry::new(&freedesktop::desktop_file_id())
+                        .await
+                        .log_err();
+                    let mut state = state.borrow_mut();
+                    let handle = launcher_entry.and_then(|launcher_entry| {
+                        let handle = launcher_entry.handle();
+                        state
                             .loop_handle
-                            .insert_source(item, move |event, _, client| {
-                                match event {
-                                    StatusNotifierItemEvents::MenuEvent(crate::platform::linux::xdg_desktop_portal::status_notifier::dbusmenu::DBusMenuEvents::MenuClick(id)) => {
-                                        let mut state = client.0.borrow_mut();
-                                        if let Some(mut callback) = state.common.callbacks.app_menu_action.take() {
-                                            if let Some(action) = state.common.tray_menu_actions.get(&id).map(|a| a.boxed_clone()) {
-                                                callback(&*action);
-                                            }
-                                            state.common.callbacks.app_menu_action = Some(callback);
-                                        }
+                            .insert_source(
+                                launcher_entry,
+                                |DBusMenuEvents::MenuClick(id), _, client| {
+                                    let mut state = client.0.borrow_mut();
+                                    let action = state
+                                        .common
+                                        .launcher_entry
+                                        .actions
+                                        .get(&id)
+                                        .map(|action| action.boxed_clone());
+                                    if let Some(action) = action
+                                        && let Some(mut callback) =
+                                            state.common.callbacks.app_menu_action.take()
+                                    {
+                                        drop(state);
+                                        callback(action.as_ref());
+                                        client.0.borrow_mut().common.callbacks.app_menu_action =
+                                            Some(callback);
                                     }
-                                    _ => {}
+                                },
+                            )
+                            .log_err()?;
+                        Some(handle)
+                    });
+                    state.common.launcher_entry_registered(handle);
+                }
+            })
+            .detach();
+    }
+
+    fn set_app_menu(&self, menu: DBusMenu) {
+        self.0
+            .borrow()
+            .common
+            .foreground_executor
+            .spawn({
+                let state = self.0.clone();
+                async move {
+                    let app_menu = AppMenu::new(menu).await.log_err();
+                    let mut state = state.borrow_mut();
+                    let state = &mut *state;
+                    let handle = app_menu.and_then(|app_menu| {
+                        let handle = app_menu.handle();
+                        state
+                            .loop_handle
+                            .insert_source(app_menu, |DBusMenuEvents::MenuClick(id), _, client| {
+                                let mut state = client.0.borrow_mut();
+                                let action = state
+                                    .common
+                                    .app_menu
+                                    .actions
+                                    .get(&id)
+                                    .map(|action| action.boxed_clone());
+                                if let Some(action) = action
+                                    && let Some(mut callback) =
+                                        state.common.callbacks.app_menu_action.take()
+                                {
+                                    drop(state);
+                                    callback(action.as_ref());
+                                    client.0.borrow_mut().common.callbacks.app_menu_action =
+                                        Some(callback);
                                 }
                             })
-                            .log_err();
+                            .log_err()?;
+                        Some(handle)
+                    });
+                    if let Some(handle) = &handle {
+                        let windows = state.windows.keys().copied().collect::<Vec<_>>();
+                        for x_window in windows {
+                            state.register_app_menu(x_window, handle);
+                        }
                     }
+                    state.common.app_menu_registered(handle);
                 }
             })
             .detach();
@@ -1744,6 +2499,174 @@ impl LinuxClient for X11Client {
 }
 
 impl X11ClientState {
+    /// Grabs the key of the hotkey on the root window, with any combination of the lock
+    /// modifiers, so that it's pressed regardless of Caps Lock and Num Lock.
+    fn grab_global_hotkey(
+        &self,
+        keystroke: &Keystroke,
+        modifiers: xproto::ModMask,
+    ) -> anyhow::Result<xproto::Keycode> {
+        let keycode =
+            keycode_for_key(&self.xkb.get_keymap(), &keystroke.key).with_context(|| {
+                format!("No key for global hotkey {keystroke} in the current keymap")
+            })?;
+        let root = self.xcb_connection.setup().roots[self.x_root_index].root;
+        let lock_masks = global_hotkey_lock_masks();
+        for (ix, locks) in lock_masks.into_iter().enumerate() {
+            let grabbed = check_reply(
+                || format!("X11 GrabKey for global hotkey {keystroke} failed."),
+                self.xcb_connection.grab_key(
+                    false,
+                    root,
+                    modifiers | locks,
+                    keycode,
+                    xproto::GrabMode::ASYNC,
+                    xproto::GrabMode::ASYNC,
+                ),
+            );
+            if let Err(error) = grabbed {
+                // Release the combinations grabbed before, e.g. when only some are taken
+                for locks in &lock_masks[..ix] {
+                    check_reply(
+                        || "X11 UngrabKey for global hotkey failed.",
+                        self.xcb_connection
+                            .ungrab_key(keycode, root, modifiers | *locks),
+                    )
+                    .log_err();
+                }
+                return Err(error);
+            }
+        }
+        Ok(keycode)
+    }
+
+    fn ungrab_global_hotkey(&self, grab: &GlobalHotkeyGrab) {
+        let Some(keycode) = grab.keycode else {
+            return;
+        };
+        // Only the combinations grabbed, other hotkeys may use the same key
+        let root = self.xcb_connection.setup().roots[self.x_root_index].root;
+        for locks in global_hotkey_lock_masks() {
+            check_reply(
+                || "X11 UngrabKey for global hotkey failed.",
+                self.xcb_connection
+                    .ungrab_key(keycode, root, grab.modifiers | locks),
+            )
+            .log_err();
+        }
+    }
+
+    /// Grabs the keys of the global hotkeys again after the keymap changed.
+    fn regrab_global_hotkeys(&mut self) {
+        let mut global_hotkeys = mem::take(&mut self.global_hotkeys);
+        // Release all the old grabs before any new one, which may reuse their keycodes
+        for grab in global_hotkeys.values() {
+            self.ungrab_global_hotkey(grab);
+        }
+        for grab in global_hotkeys.values_mut() {
+            grab.keycode = self
+                .grab_global_hotkey(&grab.keystroke, grab.modifiers)
+                .log_err();
+        }
+        self.global_hotkeys = global_hotkeys;
+        xcb_flush(&self.xcb_connection);
+    }
+
+    /// Takes the drag out of one of our windows, stopping its drop timeout.
+    fn take_xdnd_source(&mut self) -> Option<XdndSource> {
+        let source = self.xdnd_source.take()?;
+        if let Some(token) = source.drop_timeout {
+            self.loop_handle.remove(token);
+        }
+        Some(source)
+    }
+
+    fn release_pointer_grab(&mut self) {
+        let Some(grab) = self.pointer_grab.take() else {
+            return;
+        };
+        let xcb = &self.xcb_connection;
+        check_reply(
+            || "X11 UngrabPointer failed.",
+            xcb.ungrab_pointer(x11rb::CURRENT_TIME),
+        )
+        .log_err();
+        if let Some(confine_window) = grab.confine_window {
+            check_reply(
+                || "X11 DestroyWindow for pointer confinement failed.",
+                xcb.destroy_window(confine_window),
+            )
+            .log_err();
+        }
+        let root = xcb.setup().roots[self.x_root_index].root;
+        check_reply(
+            || "X11 XiSelectEvents for raw motion failed.",
+            xcb.xinput_xi_select_events(
+                root,
+                &[xinput::EventMask {
+                    deviceid: XINPUT_ALL_DEVICE_GROUPS,
+                    mask: vec![xinput::XIEventMask::from(0u32)],
+                }],
+            ),
+        )
+        .log_err();
+    }
+
+    /// Requests the next type of the drag over the window, received as a `SelectionNotify`.
+    fn xdnd_convert_next(&self, x_window: xproto::Window) {
+        let Some((atom, _)) = self.xdnd_state.pending_types.first() else {
+            return;
+        };
+        check_reply(
+            || "Failed to convert selection for drag and drop",
+            self.xcb_connection.convert_selection(
+                x_window,
+                self.atoms.XdndSelection,
+                *atom,
+                self.atoms.XDND_DATA,
+                self.xdnd_state.time,
+            ),
+        )
+        .log_err();
+    }
+
+    /// Announces the exported application menu for a window, both through the window properties
+    /// read by KDE and through the AppMenu registrar.
+    fn register_app_menu(&self, x_window: xproto::Window, app_menu: &AppMenuHandle) {
+        for (atom, value) in [
+            (
+                self.atoms._KDE_NET_WM_APPMENU_SERVICE_NAME,
+                app_menu.service_name(),
+            ),
+            (
+                self.atoms._KDE_NET_WM_APPMENU_OBJECT_PATH,
+                app_menu.object_path().to_string(),
+            ),
+        ] {
+            check_reply(
+                || "X11 ChangeProperty8 for the application menu failed.",
+                self.xcb_connection.change_property8(
+                    xproto::PropMode::REPLACE,
+                    x_window,
+                    atom,
+                    xproto::AtomEnum::STRING,
+                    value.as_bytes(),
+                ),
+            )
+            .log_err();
+        }
+        xcb_flush(&self.xcb_connection);
+
+        let app_menu = app_menu.clone();
+        self.common
+            .background_executor
+            .spawn(async move {
+                // Most desktops don't run a registrar, so failing to register is expected
+                app_menu.register_window(x_window).await.ok();
+            })
+            .detach();
+    }
+
     fn has_xim(&self) -> bool {
         self.ximc.is_some() && self.xim_handler.is_some()
     }
@@ -1976,6 +2899,146 @@ fn fp3232_to_f32(value: xinput::Fp3232) -> f32 {
     value.integral as f32 + value.frac as f32 / u32::MAX as f32
 }
 
+/// The unaccelerated motion of a raw event, from the first two valuators of the device.
+fn raw_motion_delta(event: &xinput::RawMotionEvent) -> Point<f32> {
+    let mask = event.valuator_mask.first().copied().unwrap_or(0);
+    // The raw values are only listed for the valuators set in the mask
+    let mut values = event.axisvalues_raw.iter().copied().map(fp3232_to_f32);
+    let x = if mask & 1 != 0 { values.next() } else { None };
+    let y = if mask & 2 != 0 { values.next() } else { None };
+    point(x.unwrap_or(0.0), y.unwrap_or(0.0))
+}
+
+fn grab_pointer(
+    state: &X11ClientState,
+    x_window: xproto::Window,
+    constraint: PointerConstraint,
+) -> anyhow::Result<PointerGrab> {
+    let xcb = &state.xcb_connection;
+    let (confine_window, lock_position) = match constraint {
+        PointerConstraint::Locked => {
+            let pointer = get_reply(|| "X11 QueryPointer failed.", xcb.query_pointer(x_window))?;
+            (None, Some((pointer.win_x, pointer.win_y)))
+        }
+        PointerConstraint::Confined(bounds) => {
+            let bounds = bounds.scale(state.scale_factor);
+            let confine_window = xcb.generate_id()?;
+            check_reply(
+                || "X11 CreateWindow for pointer confinement failed.",
+                xcb.create_window(
+                    x11rb::COPY_DEPTH_FROM_PARENT,
+                    confine_window,
+                    x_window,
+                    bounds.origin.x.0 as i16,
+                    bounds.origin.y.0 as i16,
+                    (bounds.size.width.0 as u16).max(1),
+                    (bounds.size.height.0 as u16).max(1),
+                    0,
+                    xproto::WindowClass::INPUT_ONLY,
+                    x11rb::COPY_FROM_PARENT,
+                    &xproto::CreateWindowAux::new(),
+                ),
+            )?;
+            check_reply(
+                || "X11 MapWindow for pointer confinement failed.",
+                xcb.map_window(confine_window),
+            )?;
+            (Some(confine_window), None)
+        }
+    };
+
+    let grab = get_reply(
+        || "X11 GrabPointer failed.",
+        xcb.grab_pointer(
+            true,
+            x_window,
+            EventMask::NO_EVENT,
+            xproto::GrabMode::ASYNC,
+            xproto::GrabMode::ASYNC,
+            confine_window.unwrap_or(x_window),
+            x11rb::NONE,
+            x11rb::CURRENT_TIME,
+        ),
+    );
+    let grab = match grab {
+        Ok(grab) if grab.status == xproto::GrabStatus::SUCCESS => Ok(()),
+        Ok(grab) => Err(anyhow!("X11 GrabPointer failed: {:?}", grab.status)),
+        Err(error) => Err(error),
+    };
+    if let Err(error) = grab {
+        if let Some(confine_window) = confine_window {
+            xcb.destroy_window(confine_window).log_err();
+        }
+        return Err(error);
+    }
+
+    let root = xcb.setup().roots[state.x_root_index].root;
+    check_reply(
+        || "X11 XiSelectEvents for raw motion failed.",
+        xcb.xinput_xi_select_events(
+            root,
+            &[xinput::EventMask {
+                deviceid: XINPUT_ALL_DEVICE_GROUPS,
+                mask: vec![xinput::XIEventMask::RAW_MOTION],
+            }],
+        ),
+    )
+    .log_err();
+
+    Ok(PointerGrab {
+        window: x_window,
+        confine_window,
+        lock_position,
+    })
+}
+
+/// Finds the keycode that produces the key on the first layout of the keymap.
+/// The X11 modifier mask of the modifiers of a global hotkey.
+fn global_hotkey_modifiers(keystroke: &Keystroke) -> xproto::ModMask {
+    [
+        (keystroke.modifiers.control, xproto::ModMask::CONTROL),
+        (keystroke.modifiers.alt, xproto::ModMask::M1),
+        (keystroke.modifiers.shift, xproto::ModMask::SHIFT),
+        (keystroke.modifiers.platform, xproto::ModMask::M4),
+    ]
+    .into_iter()
+    .filter(|(pressed, _)| *pressed)
+    .fold(xproto::ModMask::from(0u16), |mask, (_, modifier)| {
+        mask | modifier
+    })
+}
+
+/// The combinations of Caps Lock and Num Lock a global hotkey is grabbed with, so that it's
+/// pressed regardless of them.
+fn global_hotkey_lock_masks() -> [xproto::ModMask; 4] {
+    [
+        xproto::ModMask::from(0u16),
+        xproto::ModMask::LOCK,
+        xproto::ModMask::M2,
+        xproto::ModMask::LOCK | xproto::ModMask::M2,
+    ]
+}
+
+fn keycode_for_key(keymap: &xkbc::Keymap, key: &str) -> Option<xproto::Keycode> {
+    let keysym = xkbc::keysym_from_name(&keysym_name(key), xkbc::KEYSYM_CASE_INSENSITIVE);
+    if keysym == xkbc::Keysym::NoSymbol {
+        return None;
+    }
+    let min = keymap.min_keycode().raw();
+    let max = keymap.max_keycode().raw();
+    (min..=max).map(xkbc::Keycode::new).find_map(|keycode| {
+        let levels = keymap.num_levels_for_key(keycode, 0);
+        (0..levels)
+            .any(|level| {
+                keymap
+                    .key_get_syms_by_level(keycode, 0, level)
+                    .contains(&keysym)
+            })
+            .then(|| xproto::Keycode::try_from(keycode.raw()).ok())
+            .flatten()
+    })
+}
+
 fn check_compositor_present(xcb_connection: &XCBConnection, root: u32) -> bool {
     // Method 1: Check for _NET_WM_CM_S{root}
     let atom_name = format!("_NET_WM_CM_S{}", root);
@@ -2074,41 +3137,128 @@ fn check_gtk_frame_extents_supported(
     supported_atom_ids.contains(&atoms._GTK_FRAME_EXTENTS)
 }
 
-fn xdnd_is_atom_supported(atom: u32, atoms: &XcbAtoms) -> bool {
-    atom == atoms.TEXT
-        || atom == atoms.STRING
-        || atom == atoms.UTF8_STRING
-        || atom == atoms.TEXT_PLAIN
-        || atom == atoms.TEXT_PLAIN_UTF8
-        || atom == atoms.TextUriList
-}
-
-fn xdnd_get_supported_atom(
+/// Returns the types of a drag with more than three of them, listed on the source window.
+fn xdnd_get_types(
     xcb_connection: &XCBConnection,
-    supported_atoms: &XcbAtoms,
-    target: xproto::Window,
-) -> u32 {
-    if let Some(reply) = get_reply(
-        || "Failed to get XDnD supported atoms",
-        xcb_connection.get_property(
-            false,
-            target,
-            supported_atoms.XdndTypeList,
-            AtomEnum::ANY,
-            0,
-            1024,
-        ),
+    atoms: &XcbAtoms,
+    source: xproto::Window,
+) -> Vec<xproto::Atom> {
+    get_reply(
+        || "Failed to get XDnD types",
+        xcb_connection.get_property(false, source, atoms.XdndTypeList, AtomEnum::ANY, 0, 1024),
     )
     .log_with_level(Level::Warn)
-        && let Some(atoms) = reply.value32()
-    {
-        for atom in atoms {
-            if xdnd_is_atom_supported(atom, supported_atoms) {
-                return atom;
-            }
+    .and_then(|reply| Some(reply.value32()?.collect()))
+    .unwrap_or_default()
+}
+
+/// The types to convert from those of a drag, see [`drop_mime_types`].
+fn xdnd_drop_types(
+    xcb_connection: &XCBConnection,
+    types: &[xproto::Atom],
+) -> Vec<(xproto::Atom, String)> {
+    let offered = types
+        .iter()
+        .filter_map(|atom| {
+            let name = get_reply(
+                || "Failed to get XDnD type name",
+                xcb_connection.get_atom_name(*atom),
+            )
+            .log_err()?
+            .name;
+            Some((*atom, String::from_utf8(name).ok()?))
+        })
+        .collect::<Vec<_>>();
+    let mime_types = offered
+        .iter()
+        .map(|(_, mime_type)| mime_type.clone())
+        .collect::<Vec<_>>();
+    drop_mime_types(&mime_types)
+        .into_iter()
+        .filter_map(|mime_type| {
+            offered
+                .iter()
+                .find(|(_, offered)| *offered == mime_type)
+                .cloned()
+        })
+        .collect()
+}
+
+/// Returns the XDND aware window at the root position and its protocol version, walking down the
+/// window tree as the window manager may have reparented the top-level window into a frame.
+fn xdnd_find_target(
+    xcb_connection: &XCBConnection,
+    atoms: &XcbAtoms,
+    root: xproto::Window,
+    x: i16,
+    y: i16,
+) -> Option<(xproto::Window, xproto::Window, u32)> {
+    let mut window = root;
+    loop {
+        let child = get_reply(
+            || "Failed to translate coordinates",
+            xcb_connection.translate_coordinates(root, window, x, y),
+        )
+        .log_err()?
+        .child;
+        if child == x11rb::NONE {
+            return None;
         }
+        // A window with a proxy, such as a desktop's root, is XDnD aware through the proxy
+        let proxy = xdnd_proxy(xcb_connection, atoms, child).unwrap_or(child);
+        let version = get_reply(
+            || "Failed to get XdndAware property",
+            xcb_connection.get_property(false, proxy, atoms.XdndAware, AtomEnum::ANY, 0, 1),
+        )
+        .log_err()
+        .and_then(|reply| reply.value32()?.next());
+        if let Some(version) = version.filter(|version| *version >= XDND_MIN_VERSION) {
+            return Some((child, proxy, version));
+        }
+        window = child;
     }
-    0
+}
+
+/// The window named by the XdndProxy property of the window, if the proxy names itself in turn,
+/// which tells it apart from a property left behind by a proxy that no longer exists.
+fn xdnd_proxy(
+    xcb_connection: &XCBConnection,
+    atoms: &XcbAtoms,
+    window: xproto::Window,
+) -> Option<xproto::Window> {
+    let proxy_of = |window| {
+        get_reply(
+            || "Failed to get XdndProxy property",
+            xcb_connection.get_property(false, window, atoms.XdndProxy, AtomEnum::WINDOW, 0, 1),
+        )
+        .ok()?
+        .value32()?
+        .next()
+    };
+    let proxy = proxy_of(window)?;
+    (proxy_of(proxy) == Some(proxy)).then_some(proxy)
+}
+
+/// Sends a message about the target window, to its proxy if it has one.
+fn xdnd_send_message(
+    xcb_connection: &XCBConnection,
+    type_: xproto::Atom,
+    target: &XdndTarget,
+    data: [u32; 5],
+) {
+    let message = ClientMessageEvent {
+        format: 32,
+        window: target.window,
+        type_,
+        data: ClientMessageData::from(data),
+        sequence: 0,
+        response_type: xproto::CLIENT_MESSAGE_EVENT,
+    };
+    check_reply(
+        || "Failed to send XDnD message",
+        xcb_connection.send_event(false, target.proxy, EventMask::default(), message),
+    )
+    .log_err();
 }
 
 fn xdnd_send_finished(
@@ -19,7 +19,6 @@
 // https://freedesktop.org/wiki/ClipboardManager/
 
 use std::{
-    borrow::Cow,
     cell::RefCell,
     collections::{HashMap, hash_map::Entry},
     sync::{
@@ -47,7 +46,13 @@ use x11rb::{
     wrapper::ConnectionExt as _,
 };
 
-use crate::{ClipboardItem, Image, ImageFormat, hash};
+use crate::{
+    ClipboardItem, Image, ImageFormat, hash,
+    platform::linux::platform::{
+        PLAIN_TEXT_MIME_TYPE, clipboard_representations, is_rich_clipboard_mime_type,
+        rich_clipboard_entry,
+    },
+};
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -278,6 +283,51 @@ impl Inner {
         Ok(())
     }
 
+    /// Reads the formats of the selection whose MIME types are wanted, such as HTML and RTF,
+    /// which are read besides the text or image.
+    fn read_formats(
+        &self,
+        selection: ClipboardKind,
+        wanted: impl Fn(&str) -> bool,
+    ) -> Result<Vec<ClipboardData>> {
+        if self.is_owner(selection)? {
+            let data = self.selection_of(selection).data.read();
+            return Ok(data
+                .iter()
+                .flatten()
+                .filter(|data| wanted(self.atom_name(data.format)))
+                .cloned()
+                .collect());
+        }
+        let reader = XContext::new()?;
+        let targets = self.read_single(&reader, selection, self.atoms.TARGETS)?;
+        if targets.format != self.atoms.ATOM {
+            return Ok(Vec::new());
+        }
+        Ok(Self::parse_formats(&targets.bytes)
+            .into_iter()
+            .filter(|format| wanted(self.atom_name(*format)))
+            .filter_map(|format| {
+                self.read_single(&reader, selection, format)
+                    .map_err(|error| {
+                        log::trace!("Conversion to {} failed: {error}", self.atom_name(format))
+                    })
+                    .ok()
+            })
+            .collect())
+    }
+
+    fn intern_atom(&self, name: &str) -> Result<Atom> {
+        Ok(self
+            .server
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(into_unknown)?
+            .reply()
+            .map_err(into_unknown)?
+            .atom)
+    }
+
     /// `formats` must be a slice of atoms, where each atom represents a target format.
     /// The first format from `formats`, which the clipboard owner supports will be the
     /// format of the return value.
@@ -714,7 +764,14 @@ impl Inner {
             log::trace!("Handling request for (probably) the clipboard contents.");
             let data = self.selection_of(selection).data.read();
             if let Some(data_list) = &*data {
-                success = match data_list.iter().find(|d| d.format == event.target) {
+                // The text is stored once, but requested under any of its equivalent formats
+                let target =
+                    if [self.atoms.UTF8_MIME_0, self.atoms.UTF8_MIME_1].contains(&event.target) {
+                        self.atoms.UTF8_STRING
+                    } else {
+                        event.target
+                    };
+                success = match data_list.iter().find(|d| d.format == target) {
                     Some(data) => {
                         self.server
                             .conn
@@ -976,44 +1033,64 @@ impl Clipboard {
         Ok(Self { inner: ctx })
     }
 
-    pub(crate) fn set_text(
+    /// Offers every representation of the item: its text, images, HTML and custom formats.
+    pub(crate) fn set_item(
         &self,
-        message: Cow<'_, str>,
+        item: &ClipboardItem,
         selection: ClipboardKind,
         wait: WaitConfig,
     ) -> Result<()> {
-        let data = vec![ClipboardData {
-            bytes: message.into_owned().into_bytes(),
-            format: self.inner.atoms.UTF8_STRING,
-        }];
+        let data = clipboard_representations(item)
+            .into_iter()
+            .map(|(mime_type, bytes)| {
+                let format = if mime_type == PLAIN_TEXT_MIME_TYPE {
+                    self.inner.atoms.UTF8_STRING
+                } else {
+                    self.inner.intern_atom(&mime_type)?
+                };
+                Ok(ClipboardData { bytes, format })
+            })
+            .collect::<Result<Vec<_>>>()?;
         self.inner.write(data, selection, wait)
     }
 
-    #[allow(unused)]
-    pub(crate) fn set_image(
+    pub(crate) fn get_any(&self, selection: ClipboardKind) -> Result<ClipboardItem> {
+        let item = self.get_text_or_image(selection);
+        let rich_entries = self
+            .inner
+            .read_formats(selection, is_rich_clipboard_mime_type)
+            .map_err(|error| log::trace!("reading rich clipboard formats failed: {error}"))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|data| rich_clipboard_entry(self.inner.atom_name(data.format), data.bytes))
+            .collect::<Vec<_>>();
+        match item {
+            Ok(mut item) => {
+                item.entries.extend(rich_entries);
+                Ok(item)
+            }
+            Err(_) if !rich_entries.is_empty() => Ok(ClipboardItem {
+                entries: rich_entries,
+            }),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Reads the data of the given MIME type from the selection, if offered.
+    pub(crate) fn get_custom(
         &self,
-        image: Image,
         selection: ClipboardKind,
-        wait: WaitConfig,
-    ) -> Result<()> {
-        let format = match image.format {
-            ImageFormat::Png => self.inner.atoms.PNG__MIME,
-            ImageFormat::Jpeg => self.inner.atoms.JPEG_MIME,
-            ImageFormat::Webp => self.inner.atoms.WEBP_MIME,
-            ImageFormat::Gif => self.inner.atoms.GIF__MIME,
-            ImageFormat::Svg => self.inner.atoms.SVG__MIME,
-            ImageFormat::Bmp => self.inner.atoms.BMP__MIME,
-            ImageFormat::Tiff => self.inner.atoms.TIFF_MIME,
-            ImageFormat::Ico => self.inner.atoms.ICO__MIME,
-        };
-        let data = vec![ClipboardData {
-            bytes: image.bytes,
-            format: self.inner.atoms.PNG__MIME,
-        }];
-        self.inner.write(data, selection, wait)
+        mime: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .inner
+            .read_formats(selection, |format| format == mime)?
+            .into_iter()
+            .next()
+            .map(|data| data.bytes))
     }
 
-    pub(crate) fn get_any(&self, selection: ClipboardKind) -> Result<ClipboardItem> {
+    fn get_text_or_image(&self, selection: ClipboardKind) -> Result<ClipboardItem> {
         const IMAGE_FORMAT_COUNT: usize = 7;
         let image_format_atoms: [Atom; IMAGE_FORMAT_COUNT] = [
             self.inner.atoms.PNG__MIME,
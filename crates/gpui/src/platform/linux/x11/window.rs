@@ -2,11 +2,13 @@ use anyhow::{Context as _, anyhow};
 use x11rb::connection::RequestConnection;
 
 use crate::platform::blade::{BladeContext, BladeRenderer, BladeSurfaceConfig};
+use crate::platform::linux::xdg_desktop_portal::idle_inhibit;
 use crate::{
-    AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
+    AnyWindowHandle, Bounds, ClipboardItem, Decorations, DevicePixels, DragAction, DragResult,
+    ForegroundExecutor, GpuSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PointerConstraint, PromptButton, PromptLevel,
+    RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size, Subscription, Tiling,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
     WindowDecorations, WindowKind, WindowParams, X11ClientStatePtr, px, size,
 };
 
@@ -46,7 +48,12 @@ x11rb::atom_manager! {
         XdndDrop,
         XdndFinished,
         XdndTypeList,
+        XdndProxy,
         XdndActionCopy,
+        XdndActionMove,
+        XdndActionLink,
+        CLIPBOARD,
+        TARGETS,
         TextUriList: b"text/uri-list",
         UTF8_STRING,
         TEXT,
@@ -65,6 +72,7 @@ x11rb::atom_manager! {
         _NET_WM_STATE_MAXIMIZED_HORZ,
         _NET_WM_STATE_FULLSCREEN,
         _NET_WM_STATE_HIDDEN,
+        _NET_WM_STATE_DEMANDS_ATTENTION,
         _NET_WM_STATE_FOCUSED,
         _NET_ACTIVE_WINDOW,
         _NET_WM_SYNC_REQUEST,
@@ -81,6 +89,8 @@ x11rb::atom_manager! {
         _GTK_FRAME_EXTENTS,
         _GTK_EDGE_CONSTRAINTS,
         _NET_CLIENT_LIST_STACKING,
+        _KDE_NET_WM_APPMENU_SERVICE_NAME,
+        _KDE_NET_WM_APPMENU_OBJECT_PATH,
     }
 }
 
@@ -754,8 +764,8 @@ impl Drop for X11Window {
 }
 
 enum WmHintPropertyState {
-    // Remove = 0,
-    // Add = 1,
+    Remove = 0,
+    Add = 1,
     Toggle = 2,
 }
 
@@ -802,33 +812,6 @@ impl X11Window {
         Ok(Self(ptr))
     }
 
-    fn set_wm_hints<C: Display + Send + Sync + 'static, F: FnOnce() -> C>(
-        &self,
-        failure_context: F,
-        wm_hint_property_state: WmHintPropertyState,
-        prop1: u32,
-        prop2: u32,
-    ) -> anyhow::Result<()> {
-        let state = self.0.state.borrow();
-        let message = ClientMessageEvent::new(
-            32,
-            self.0.x_window,
-            state.atoms._NET_WM_STATE,
-            [wm_hint_property_state as u32, prop1, prop2, 1, 0],
-        );
-        check_reply(
-            failure_context,
-            self.0.xcb.send_event(
-                false,
-                state.x_root_window,
-                xproto::EventMask::SUBSTRUCTURE_REDIRECT | xproto::EventMask::SUBSTRUCTURE_NOTIFY,
-                message,
-            ),
-        )?;
-        xcb_flush(&self.0.xcb);
-        Ok(())
-    }
-
     fn get_root_position(
         &self,
         position: Point<Pixels>,
@@ -885,6 +868,77 @@ impl X11Window {
 }
 
 impl X11WindowStatePtr {
+    fn set_wm_hints<C: Display + Send + Sync + 'static, F: FnOnce() -> C>(
+        &self,
+        failure_context: F,
+        wm_hint_property_state: WmHintPropertyState,
+        prop1: u32,
+        prop2: u32,
+    ) -> anyhow::Result<()> {
+        let state = self.state.borrow();
+        let message = ClientMessageEvent::new(
+            32,
+            self.x_window,
+            state.atoms._NET_WM_STATE,
+            [wm_hint_property_state as u32, prop1, prop2, 1, 0],
+        );
+        check_reply(
+            failure_context,
+            self.xcb.send_event(
+                false,
+                state.x_root_window,
+                xproto::EventMask::SUBSTRUCTURE_REDIRECT | xproto::EventMask::SUBSTRUCTURE_NOTIFY,
+                message,
+            ),
+        )?;
+        xcb_flush(&self.xcb);
+        Ok(())
+    }
+
+    /// Sets or clears the urgency hint of the window, which window managers use to draw the
+    /// user's attention to it.
+    pub fn set_demands_attention(&self, demands_attention: bool) {
+        let state = if demands_attention {
+            WmHintPropertyState::Add
+        } else {
+            WmHintPropertyState::Remove
+        };
+        let atom = self.state.borrow().atoms._NET_WM_STATE_DEMANDS_ATTENTION;
+        self.set_wm_hints(
+            || "X11 SendEvent to request attention failed.",
+            state,
+            atom,
+            xproto::AtomEnum::NONE.into(),
+        )
+        .log_err();
+    }
+
+    pub fn activate(&self) {
+        let data = [1, xproto::Time::CURRENT_TIME.into(), 0, 0, 0];
+        let message = xproto::ClientMessageEvent::new(
+            32,
+            self.x_window,
+            self.state.borrow().atoms._NET_ACTIVE_WINDOW,
+            data,
+        );
+        self.xcb
+            .send_event(
+                false,
+                self.state.borrow().x_root_window,
+                xproto::EventMask::SUBSTRUCTURE_REDIRECT | xproto::EventMask::SUBSTRUCTURE_NOTIFY,
+                message,
+            )
+            .log_err();
+        self.xcb
+            .set_input_focus(
+                xproto::InputFocus::POINTER_ROOT,
+                self.x_window,
+                xproto::Time::CURRENT_TIME,
+            )
+            .log_err();
+        xcb_flush(&self.xcb);
+    }
+
     pub fn should_close(&self) -> bool {
         let mut cb = self.callbacks.borrow_mut();
         if let Some(mut should_close) = cb.should_close.take() {
@@ -899,7 +953,22 @@ impl X11WindowStatePtr {
     pub fn property_notify(&self, event: xproto::PropertyNotifyEvent) -> anyhow::Result<()> {
         let mut state = self.state.borrow_mut();
         if event.atom == state.atoms._NET_WM_STATE {
+            let window_state = |state: &X11WindowState| {
+                (
+                    state.fullscreen,
+                    state.maximized_vertical,
+                    state.maximized_horizontal,
+                )
+            };
+            let previous_window_state = window_state(&state);
             self.set_wm_properties(state)?;
+            // The window bounds change when the window gets maximized or fullscreen, even when
+            // its geometry doesn't
+            if window_state(&self.state.borrow()) != previous_window_state
+                && let Some(ref mut fun) = self.callbacks.borrow_mut().moved
+            {
+                fun();
+            }
         } else if event.atom == state.atoms._GTK_EDGE_CONSTRAINTS {
             self.set_edge_constraints(state)?;
         }
@@ -1287,32 +1356,27 @@ impl PlatformWindow for X11Window {
         None
     }
 
+    fn start_drag(
+        &self,
+        item: ClipboardItem,
+        actions: &[DragAction],
+    ) -> Option<futures::channel::oneshot::Receiver<DragResult>> {
+        let client = self.0.state.borrow().client.clone();
+        client.start_drag(self.0.x_window, item, actions)
+    }
+
+    fn set_pointer_constraint(&self, constraint: Option<PointerConstraint>) {
+        let client = self.0.state.borrow().client.clone();
+        client.set_pointer_constraint(self.0.x_window, constraint);
+    }
+
+    fn inhibit_idle(&self, reason: &str) -> Subscription {
+        let window_identifier = format!("x11:{:x}", self.0.x_window);
+        idle_inhibit::inhibit_idle(&self.0.state.borrow().executor, window_identifier, reason)
+    }
+
     fn activate(&self) {
-        let data = [1, xproto::Time::CURRENT_TIME.into(), 0, 0, 0];
-        let message = xproto::ClientMessageEvent::new(
-            32,
-            self.0.x_window,
-            self.0.state.borrow().atoms._NET_ACTIVE_WINDOW,
-            data,
-        );
-        self.0
-            .xcb
-            .send_event(
-                false,
-                self.0.state.borrow().x_root_window,
-                xproto::EventMask::SUBSTRUCTURE_REDIRECT | xproto::EventMask::SUBSTRUCTURE_NOTIFY,
-                message,
-            )
-            .log_err();
-        self.0
-            .xcb
-            .set_input_focus(
-                xproto::InputFocus::POINTER_ROOT,
-                self.0.x_window,
-                xproto::Time::CURRENT_TIME,
-            )
-            .log_err();
-        xcb_flush(&self.0.xcb);
+        self.0.activate();
     }
 
     fn is_active(&self) -> bool {
@@ -1407,24 +1471,26 @@ impl PlatformWindow for X11Window {
 
     fn zoom(&self) {
         let state = self.0.state.borrow();
-        self.set_wm_hints(
-            || "X11 SendEvent to maximize a window failed.",
-            WmHintPropertyState::Toggle,
-            state.atoms._NET_WM_STATE_MAXIMIZED_VERT,
-            state.atoms._NET_WM_STATE_MAXIMIZED_HORZ,
-        )
-        .log_err();
+        self.0
+            .set_wm_hints(
+                || "X11 SendEvent to maximize a window failed.",
+                WmHintPropertyState::Toggle,
+                state.atoms._NET_WM_STATE_MAXIMIZED_VERT,
+                state.atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+            )
+            .log_err();
     }
 
     fn toggle_fullscreen(&self) {
         let state = self.0.state.borrow();
-        self.set_wm_hints(
-            || "X11 SendEvent to fullscreen a window failed.",
-            WmHintPropertyState::Toggle,
-            state.atoms._NET_WM_STATE_FULLSCREEN,
-            xproto::AtomEnum::NONE.into(),
-        )
-        .log_err();
+        self.0
+            .set_wm_hints(
+                || "X11 SendEvent to fullscreen a window failed.",
+                WmHintPropertyState::Toggle,
+                state.atoms._NET_WM_STATE_FULLSCREEN,
+                xproto::AtomEnum::NONE.into(),
+            )
+            .log_err();
     }
 
     fn is_fullscreen(&self) -> bool {
@@ -9,6 +9,11 @@ use smol::stream::StreamExt;
 
 use crate::{BackgroundExecutor, WindowAppearance};
 
+pub mod app_menu;
+pub mod global_shortcuts;
+pub mod idle_inhibit;
+pub mod launcher_entry;
+pub mod notifications;
 pub mod status_notifier;
 
 pub enum Event {
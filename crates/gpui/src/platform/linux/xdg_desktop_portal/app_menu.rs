@@ -0,0 +1,144 @@
+//! Exports the application menu over [DBusMenu], so that global menu bars can show it.
+//!
+//! The menu is served from its own session bus connection. On X11 windows announce it through
+//! the `_KDE_NET_WM_APPMENU_*` properties and the `com.canonical.AppMenu.Registrar` service,
+//! on Wayland through the `org_kde_kwin_appmenu` protocol.
+//!
+//! [DBusMenu]: https://github.com/AyatanaIndicators/libdbusmenu/blob/master/libdbusmenu-glib/dbus-menu.xml
+
+use calloop::channel::{self, Channel};
+use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
+use zbus::object_server::InterfaceRef;
+use zbus::zvariant::ObjectPath;
+
+use super::status_notifier::dbusmenu::{DBusMenuEvents, DBusMenuInterface, Menu};
+
+const APP_MENU_PATH: &str = "/AppMenu";
+
+const APP_MENU_REGISTRAR_DESTINATION: &str = "com.canonical.AppMenu.Registrar";
+const APP_MENU_REGISTRAR_PATH: &str = "/com/canonical/AppMenu/Registrar";
+const APP_MENU_REGISTRAR_INTERFACE: &str = "com.canonical.AppMenu.Registrar";
+
+pub struct AppMenu {
+    handle: AppMenuHandle,
+    channel: Channel<DBusMenuEvents>,
+}
+
+/// Updates the exported menu and announces it for windows.
+#[derive(Clone)]
+pub struct AppMenuHandle {
+    conn: zbus::Connection,
+    menu: InterfaceRef<DBusMenuInterface>,
+}
+
+impl AppMenu {
+    pub async fn new(menu: Menu) -> zbus::Result<Self> {
+        // Create an internal channel for emitting events to calloop
+        let (sender, channel) = channel::channel();
+        let iface = DBusMenuInterface {
+            menu,
+            revision: 0,
+            on_event: Some(Box::new(move |event| {
+                // Ignore send errors (receiver dropped) on purpose
+                let _ = sender.send(event);
+            })),
+        };
+
+        let conn = zbus::connection::Builder::session()?
+            .serve_at(APP_MENU_PATH, iface)?
+            .build()
+            .await?;
+        let menu = conn
+            .object_server()
+            .interface::<_, DBusMenuInterface>(APP_MENU_PATH)
+            .await?;
+
+        Ok(Self {
+            handle: AppMenuHandle { conn, menu },
+            channel,
+        })
+    }
+
+    pub fn handle(&self) -> AppMenuHandle {
+        self.handle.clone()
+    }
+}
+
+impl AppMenuHandle {
+    /// The unique bus name the menu is served from.
+    pub fn service_name(&self) -> String {
+        self.conn
+            .unique_name()
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn object_path(&self) -> &'static str {
+        APP_MENU_PATH
+    }
+
+    /// Replaces the menu, without announcing it again.
+    pub async fn update(&self, menu: Menu) -> zbus::Result<()> {
+        DBusMenuInterface::replace(&self.menu, menu).await
+    }
+
+    /// Associates the menu with an X11 window through the AppMenu registrar, used by
+    /// Unity-style global menus which don't read the window properties.
+    pub async fn register_window(&self, window_id: u32) -> zbus::Result<()> {
+        let path = ObjectPath::try_from(APP_MENU_PATH)?;
+        self.conn
+            .call_method(
+                Some(APP_MENU_REGISTRAR_DESTINATION),
+                APP_MENU_REGISTRAR_PATH,
+                Some(APP_MENU_REGISTRAR_INTERFACE),
+                "RegisterWindow",
+                &(window_id, path),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl EventSource for AppMenu {
+    type Event = DBusMenuEvents;
+    type Metadata = ();
+    type Ret = ();
+    type Error = anyhow::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.channel.process_events(readiness, token, |evt, _| {
+            if let calloop::channel::Event::Msg(msg) = evt {
+                (callback)(msg, &mut ())
+            }
+        })?;
+        Ok(PostAction::Continue)
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.channel.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.channel.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.channel.unregister(poll)
+    }
+}
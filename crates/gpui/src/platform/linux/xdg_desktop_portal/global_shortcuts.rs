@@ -0,0 +1,193 @@
+//! Registers global hotkeys through the [GlobalShortcuts portal], for sessions where the client
+//! can't grab keys itself, like on Wayland.
+//!
+//! [GlobalShortcuts portal]: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.GlobalShortcuts.html
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use ashpd::desktop::Session;
+use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use util::{ResultExt as _, TryFutureExt as _};
+
+use crate::platform::linux::platform::keysym_name;
+use crate::{ForegroundExecutor, GlobalHotkeyId, Keystroke};
+
+type ActivatedCallback = Rc<RefCell<Option<Box<dyn FnMut(GlobalHotkeyId)>>>>;
+
+enum ShortcutRequest {
+    Register(GlobalHotkeyId, NewShortcut),
+    Unregister(GlobalHotkeyId),
+}
+
+/// The global hotkeys registered by the app, shared by all Linux clients.
+#[derive(Default)]
+pub(crate) struct LinuxGlobalShortcuts {
+    shortcuts: BTreeMap<GlobalHotkeyId, NewShortcut>,
+    requests: Option<UnboundedSender<ShortcutRequest>>,
+    callback: ActivatedCallback,
+}
+
+impl LinuxGlobalShortcuts {
+    pub(crate) fn register(
+        &mut self,
+        id: GlobalHotkeyId,
+        keystroke: &Keystroke,
+        name: &str,
+        executor: &ForegroundExecutor,
+    ) {
+        let trigger = shortcut_trigger(keystroke);
+        let shortcut = NewShortcut::new(id.0.to_string(), name).preferred_trigger(trigger.as_str());
+        self.shortcuts.insert(id, shortcut.clone());
+        self.send(ShortcutRequest::Register(id, shortcut), executor);
+    }
+
+    pub(crate) fn unregister(&mut self, id: GlobalHotkeyId, executor: &ForegroundExecutor) {
+        if self.shortcuts.remove(&id).is_some() {
+            self.send(ShortcutRequest::Unregister(id), executor);
+        }
+    }
+
+    pub(crate) fn on_activated(&mut self, callback: Box<dyn FnMut(GlobalHotkeyId)>) {
+        *self.callback.borrow_mut() = Some(callback);
+    }
+
+    /// Returns the callback for hotkeys the client detects itself, e.g. with a key grab, to be
+    /// invoked with [`activate`] once the client state is no longer borrowed.
+    pub(crate) fn callback(&self) -> ActivatedCallback {
+        self.callback.clone()
+    }
+
+    fn send(&mut self, request: ShortcutRequest, executor: &ForegroundExecutor) {
+        if let Some(requests) = &self.requests
+            && !requests.is_closed()
+        {
+            requests.unbounded_send(request).log_err();
+            return;
+        }
+
+        // The portal is only used once the first hotkey is registered. If it failed, it's used
+        // again with all the hotkeys registered so far.
+        self.requests = None;
+        if self.shortcuts.is_empty() {
+            return;
+        }
+        let (sender, receiver) = unbounded();
+        executor
+            .spawn(run_global_shortcuts(receiver, self.callback.clone()).log_err())
+            .detach();
+        for (id, shortcut) in &self.shortcuts {
+            sender
+                .unbounded_send(ShortcutRequest::Register(*id, shortcut.clone()))
+                .log_err();
+        }
+        self.requests = Some(sender);
+    }
+}
+
+pub(crate) fn activate(callback: &ActivatedCallback, id: GlobalHotkeyId) {
+    let taken = callback.borrow_mut().take();
+    if let Some(mut taken) = taken {
+        taken(id);
+        callback.borrow_mut().get_or_insert(taken);
+    }
+}
+
+/// Renders the keystroke as a trigger of the [XDG shortcuts] specification, e.g.
+/// `CTRL+SHIFT+space`.
+///
+/// [XDG shortcuts]: https://specifications.freedesktop.org/shortcuts-spec/latest/
+fn shortcut_trigger(keystroke: &Keystroke) -> String {
+    let modifiers = keystroke.modifiers;
+    let mut trigger = [
+        (modifiers.control, "CTRL+"),
+        (modifiers.alt, "ALT+"),
+        (modifiers.shift, "SHIFT+"),
+        (modifiers.platform, "LOGO+"),
+    ]
+    .into_iter()
+    .filter_map(|(pressed, name)| pressed.then_some(name))
+    .collect::<String>();
+    match keystroke.key.as_str() {
+        key if key.len() == 1 && key.chars().all(|c| c.is_ascii_alphabetic()) => {
+            trigger.push_str(key)
+        }
+        key => trigger.push_str(&keysym_name(key)),
+    }
+    trigger
+}
+
+async fn run_global_shortcuts(
+    mut requests: UnboundedReceiver<ShortcutRequest>,
+    callback: ActivatedCallback,
+) -> anyhow::Result<()> {
+    let portal = GlobalShortcuts::new().await?;
+    let mut activations = Box::pin(portal.receive_activated().await?.fuse());
+    let mut registered = BTreeSet::new();
+    let mut session: Option<Session<'_, GlobalShortcuts<'_>>> = None;
+
+    loop {
+        futures::select_biased! {
+            request = requests.next() => {
+                let Some(mut request) = request else {
+                    break;
+                };
+                // Bind the hotkeys registered together at once, e.g. while the app starts, so
+                // that the user confirms them all at once.
+                let mut new_shortcuts = BTreeMap::new();
+                loop {
+                    match request {
+                        ShortcutRequest::Register(id, shortcut) => {
+                            registered.insert(id);
+                            new_shortcuts.insert(id, shortcut);
+                        }
+                        ShortcutRequest::Unregister(id) => {
+                            registered.remove(&id);
+                            new_shortcuts.remove(&id);
+                        }
+                    }
+                    match requests.try_next() {
+                        Ok(Some(next)) => request = next,
+                        _ => break,
+                    }
+                }
+                if new_shortcuts.is_empty() {
+                    continue;
+                }
+
+                // The portal can't unbind shortcuts, so the session is kept for the lifetime of
+                // the app and only the new hotkeys are bound to it.
+                if session.is_none() {
+                    session = Some(portal.create_session().await?);
+                }
+                if let Some(session) = &session {
+                    let new_shortcuts = new_shortcuts.into_values().collect::<Vec<_>>();
+                    portal
+                        .bind_shortcuts(session, &new_shortcuts, None)
+                        .await
+                        .and_then(|request| request.response())
+                        .log_err();
+                }
+            },
+            activation = activations.next() => {
+                let Some(activation) = activation else {
+                    break;
+                };
+                // Unregistered hotkeys stay bound, but are no longer activated.
+                if let Ok(id) = activation.shortcut_id().parse()
+                    && registered.contains(&GlobalHotkeyId(id))
+                {
+                    activate(&callback, GlobalHotkeyId(id));
+                }
+            },
+        }
+    }
+
+    if let Some(session) = session {
+        session.close().await.log_err();
+    }
+    Ok(())
+}
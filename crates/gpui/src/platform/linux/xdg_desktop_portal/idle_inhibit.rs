@@ -0,0 +1,67 @@
+//! Inhibits the idle actions of the session, like blanking the screen or starting the
+//! screensaver, through the [ScreenSaver] D-Bus interface, or the [Inhibit portal] when it isn't
+//! available.
+//!
+//! [ScreenSaver]: https://specifications.freedesktop.org/idle-inhibit-spec/latest/
+//! [Inhibit portal]: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Inhibit.html
+
+use std::collections::HashMap;
+
+use futures::future;
+use util::ResultExt as _;
+use zbus::zvariant::Value;
+
+use crate::platform::linux::freedesktop;
+use crate::{ForegroundExecutor, Subscription};
+
+const SCREENSAVER_INTERFACE: &str = "org.freedesktop.ScreenSaver";
+const SCREENSAVER_PATH: &str = "/org/freedesktop/ScreenSaver";
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const INHIBIT_PORTAL_INTERFACE: &str = "org.freedesktop.portal.Inhibit";
+// The flag of the Inhibit portal for idling
+const INHIBIT_IDLE_FLAG: u32 = 8;
+
+/// Inhibits idle actions until the returned subscription is dropped. The window identifier is
+/// in the format of the portals, e.g. `x11:<window id in hex>`, or empty.
+pub fn inhibit_idle(
+    executor: &ForegroundExecutor,
+    window_identifier: String,
+    reason: &str,
+) -> Subscription {
+    let reason = reason.to_string();
+    let task = executor.spawn(async move {
+        let _connection = inhibit(&window_identifier, &reason).await.log_err();
+        // Both services release the inhibitions of a client once it disconnects, so the
+        // connection is held until the task is dropped
+        future::pending::<()>().await;
+    });
+    Subscription::new(move || drop(task))
+}
+
+async fn inhibit(window_identifier: &str, reason: &str) -> zbus::Result<zbus::Connection> {
+    let connection = zbus::Connection::session().await?;
+    let inhibited = connection
+        .call_method(
+            Some(SCREENSAVER_INTERFACE),
+            SCREENSAVER_PATH,
+            Some(SCREENSAVER_INTERFACE),
+            "Inhibit",
+            &(freedesktop::desktop_file_id(), reason),
+        )
+        .await;
+    if let Err(error) = inhibited {
+        log::info!("inhibiting idle through the portal: {error}");
+        let options = HashMap::from([("reason", Value::from(reason))]);
+        connection
+            .call_method(
+                Some(PORTAL_DESTINATION),
+                PORTAL_PATH,
+                Some(INHIBIT_PORTAL_INTERFACE),
+                "Inhibit",
+                &(window_identifier, INHIBIT_IDLE_FLAG, options),
+            )
+            .await?;
+    }
+    Ok(connection)
+}
@@ -0,0 +1,238 @@
+//! Exposes the launcher entry of the application through the Unity [LauncherEntry] API, which
+//! docks and task managers use to show a quicklist, a badge count, a progress bar and an urgency
+//! hint on the application's icon.
+//!
+//! [LauncherEntry]: https://wiki.ubuntu.com/Unity/LauncherAPI
+
+use std::collections::HashMap;
+
+use calloop::channel::{self, Channel};
+use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
+use zbus::interface;
+use zbus::object_server::{InterfaceRef, SignalContext};
+use zbus::zvariant::Value;
+
+use super::status_notifier::dbusmenu::{DBusMenuEvents, DBusMenuInterface, Menu};
+
+const LAUNCHER_ENTRY_PATH: &str = "/com/canonical/unity/launcherentry";
+const QUICKLIST_PATH: &str = "/LauncherEntry/Quicklist";
+
+/// The state shown on the application's icon, besides the quicklist.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LauncherEntryProperties {
+    pub count: Option<u64>,
+    // Between 0 and 1
+    pub progress: Option<f64>,
+    pub urgent: bool,
+}
+
+pub struct LauncherEntryInterface {
+    // The desktop entry of the application, as `application://<desktop file id>`
+    app_uri: String,
+    has_quicklist: bool,
+    properties: LauncherEntryProperties,
+}
+
+impl LauncherEntryInterface {
+    fn properties(&self) -> HashMap<String, Value<'static>> {
+        let quicklist = if self.has_quicklist {
+            QUICKLIST_PATH
+        } else {
+            ""
+        };
+        let LauncherEntryProperties {
+            count,
+            progress,
+            urgent,
+        } = self.properties;
+        HashMap::from([
+            ("quicklist".to_string(), Value::from(quicklist)),
+            (
+                "count".to_string(),
+                Value::from(count.map_or(0, |count| count.min(i64::MAX as u64) as i64)),
+            ),
+            ("count-visible".to_string(), Value::from(count.is_some())),
+            ("progress".to_string(), Value::from(progress.unwrap_or(0.))),
+            (
+                "progress-visible".to_string(),
+                Value::from(progress.is_some()),
+            ),
+            ("urgent".to_string(), Value::from(urgent)),
+        ])
+    }
+}
+
+#[interface(name = "com.canonical.Unity.LauncherEntry")]
+impl LauncherEntryInterface {
+    pub async fn query(&self) -> (String, HashMap<String, Value<'static>>) {
+        (self.app_uri.clone(), self.properties())
+    }
+
+    #[zbus(signal)]
+    pub async fn update(
+        cx: &SignalContext<'_>,
+        app_uri: &str,
+        properties: HashMap<String, Value<'static>>,
+    ) -> zbus::Result<()>;
+}
+
+pub struct LauncherEntry {
+    handle: LauncherEntryHandle,
+    channel: Channel<DBusMenuEvents>,
+}
+
+/// Updates the properties of the launcher entry, notifying docks of the changes.
+#[derive(Clone)]
+pub struct LauncherEntryHandle {
+    entry: InterfaceRef<LauncherEntryInterface>,
+    quicklist: InterfaceRef<DBusMenuInterface>,
+}
+
+impl LauncherEntry {
+    pub async fn new(desktop_file_id: &str) -> zbus::Result<Self> {
+        // Create an internal channel for emitting events to calloop
+        let (sender, channel) = channel::channel();
+        let quicklist = DBusMenuInterface {
+            menu: Menu::default(),
+            revision: 0,
+            on_event: Some(Box::new(move |event| {
+                // Ignore send errors (receiver dropped) on purpose
+                let _ = sender.send(event);
+            })),
+        };
+        let entry = LauncherEntryInterface {
+            app_uri: format!("application://{desktop_file_id}"),
+            has_quicklist: false,
+            properties: LauncherEntryProperties::default(),
+        };
+
+        let conn = zbus::connection::Builder::session()?
+            .serve_at(LAUNCHER_ENTRY_PATH, entry)?
+            .serve_at(QUICKLIST_PATH, quicklist)?
+            .build()
+            .await?;
+        let entry = conn
+            .object_server()
+            .interface::<_, LauncherEntryInterface>(LAUNCHER_ENTRY_PATH)
+            .await?;
+        let quicklist = conn
+            .object_server()
+            .interface::<_, DBusMenuInterface>(QUICKLIST_PATH)
+            .await?;
+
+        Ok(Self {
+            handle: LauncherEntryHandle { entry, quicklist },
+            channel,
+        })
+    }
+
+    pub fn handle(&self) -> LauncherEntryHandle {
+        self.handle.clone()
+    }
+}
+
+impl LauncherEntryHandle {
+    /// Replaces the quicklist shown in the context menu of the launcher entry.
+    pub async fn set_quicklist(&self, menu: Menu) -> zbus::Result<()> {
+        let has_quicklist = !menu.children.is_empty();
+        DBusMenuInterface::replace(&self.quicklist, menu).await?;
+        let mut entry = self.entry.get_mut().await;
+        entry.has_quicklist = has_quicklist;
+        self.notify(&entry).await
+    }
+
+    /// Replaces the badge count, progress and urgency hint of the launcher entry.
+    pub async fn set_properties(&self, properties: LauncherEntryProperties) -> zbus::Result<()> {
+        let mut entry = self.entry.get_mut().await;
+        entry.properties = properties;
+        self.notify(&entry).await
+    }
+
+    async fn notify(&self, entry: &LauncherEntryInterface) -> zbus::Result<()> {
+        LauncherEntryInterface::update(
+            self.entry.signal_context(),
+            &entry.app_uri,
+            entry.properties(),
+        )
+        .await
+    }
+}
+
+impl EventSource for LauncherEntry {
+    type Event = DBusMenuEvents;
+    type Metadata = ();
+    type Ret = ();
+    type Error = anyhow::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.channel.process_events(readiness, token, |evt, _| {
+            if let calloop::channel::Event::Msg(msg) = evt {
+                (callback)(msg, &mut ())
+            }
+        })?;
+        Ok(PostAction::Continue)
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.channel.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.channel.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.channel.unregister(poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties() {
+        let mut entry = LauncherEntryInterface {
+            app_uri: "application://dev.zed.Zed.desktop".to_string(),
+            has_quicklist: false,
+            properties: LauncherEntryProperties::default(),
+        };
+        let properties = entry.properties();
+        assert_eq!(properties["quicklist"], Value::from(""));
+        assert_eq!(properties["count"], Value::from(0i64));
+        assert_eq!(properties["count-visible"], Value::from(false));
+        assert_eq!(properties["progress"], Value::from(0.));
+        assert_eq!(properties["progress-visible"], Value::from(false));
+        assert_eq!(properties["urgent"], Value::from(false));
+
+        entry.has_quicklist = true;
+        entry.properties = LauncherEntryProperties {
+            count: Some(u64::MAX),
+            progress: Some(0.25),
+            urgent: true,
+        };
+        let properties = entry.properties();
+        assert_eq!(properties["quicklist"], Value::from(QUICKLIST_PATH));
+        assert_eq!(properties["count"], Value::from(i64::MAX));
+        assert_eq!(properties["count-visible"], Value::from(true));
+        assert_eq!(properties["progress"], Value::from(0.25));
+        assert_eq!(properties["progress-visible"], Value::from(true));
+        assert_eq!(properties["urgent"], Value::from(true));
+    }
+}
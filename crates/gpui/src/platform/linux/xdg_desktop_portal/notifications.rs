@@ -0,0 +1,280 @@
+//! Posts notifications through the [Desktop Notifications] D-Bus interface.
+//!
+//! [Desktop Notifications]: https://specifications.freedesktop.org/notification-spec/latest/
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::iter;
+use std::rc::Rc;
+
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use util::{ResultExt as _, TryFutureExt as _};
+use zbus::export::futures_util::Stream;
+use zbus::zvariant::{StructureBuilder, Value};
+
+use crate::{
+    ForegroundExecutor, Notification, NotificationDismissReason, NotificationEvent, NotificationId,
+    NotificationUrgency,
+};
+
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+const NOTIFICATIONS_DESTINATION: &str = "org.freedesktop.Notifications";
+
+/// Lets the notification server pick how long the notification is shown.
+const DEFAULT_EXPIRE_TIMEOUT: i32 = -1;
+
+pub struct Notifications(zbus::Proxy<'static>);
+
+#[derive(Debug)]
+pub enum NotificationSignal {
+    ActionInvoked(u32, String),
+    NotificationClosed(u32, u32),
+}
+
+impl Notifications {
+    pub async fn new() -> zbus::Result<Self> {
+        let conn = zbus::Connection::session().await?;
+        let proxy: zbus::Proxy = zbus::ProxyBuilder::new(&conn)
+            .interface(NOTIFICATIONS_INTERFACE)?
+            .path(NOTIFICATIONS_PATH)?
+            .destination(NOTIFICATIONS_DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy))
+    }
+
+    /// Posts the notification, returning the id assigned to it by the server.
+    pub async fn notify(&self, app_name: &str, notification: &Notification) -> zbus::Result<u32> {
+        let actions = notification
+            .actions
+            .iter()
+            .flat_map(|action| [action.id.to_string(), action.label.to_string()])
+            .collect::<Vec<_>>();
+
+        let mut hints: HashMap<&str, Value> = HashMap::new();
+        let urgency: u8 = match notification.urgency {
+            NotificationUrgency::Low => 0,
+            NotificationUrgency::Normal => 1,
+            NotificationUrgency::Critical => 2,
+        };
+        hints.insert("urgency", Value::from(urgency));
+        if let Some(icon) = &notification.icon_data {
+            // The image is rendered as BGRA, but the server expects RGBA.
+            let mut bytes = icon.data.to_vec();
+            for pixel in bytes.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            let image = StructureBuilder::new()
+                .add_field(icon.width as i32)
+                .add_field(icon.height as i32)
+                .add_field(icon.width as i32 * 4)
+                .add_field(true)
+                .add_field(8i32)
+                .add_field(4i32)
+                .add_field(bytes)
+                .build();
+            hints.insert("image-data", Value::from(image));
+        }
+
+        self.0
+            .call(
+                "Notify",
+                &(
+                    app_name,
+                    0u32,
+                    "",
+                    notification.title.as_str(),
+                    notification.body.as_ref().map_or("", |body| body.as_str()),
+                    actions,
+                    hints,
+                    DEFAULT_EXPIRE_TIMEOUT,
+                ),
+            )
+            .await
+    }
+
+    pub async fn close_notification(&self, id: u32) -> zbus::Result<()> {
+        self.0.call("CloseNotification", &(id,)).await
+    }
+
+    pub async fn receive_signals(&self) -> zbus::Result<impl Stream<Item = NotificationSignal>> {
+        let stream = self.0.receive_all_signals().await?;
+        Ok(stream.filter_map(|msg| {
+            let header = msg.header();
+            let signal = match header.member().map(|member| member.as_str()) {
+                Some("ActionInvoked") => msg
+                    .body()
+                    .deserialize::<(u32, String)>()
+                    .ok()
+                    .map(|(id, action)| NotificationSignal::ActionInvoked(id, action)),
+                Some("NotificationClosed") => msg
+                    .body()
+                    .deserialize::<(u32, u32)>()
+                    .ok()
+                    .map(|(id, reason)| NotificationSignal::NotificationClosed(id, reason)),
+                _ => None,
+            };
+            core::future::ready(signal)
+        }))
+    }
+}
+
+type EventCallback = Rc<RefCell<Option<Box<dyn FnMut(NotificationId, NotificationEvent)>>>>;
+
+enum NotificationRequest {
+    Show(NotificationId, Notification),
+    Close(NotificationId),
+}
+
+/// The notifications posted by the app, shared by all Linux clients.
+#[derive(Default)]
+pub(crate) struct LinuxNotifications {
+    requests: Option<UnboundedSender<NotificationRequest>>,
+    event_callback: EventCallback,
+}
+
+impl LinuxNotifications {
+    pub(crate) fn show(
+        &mut self,
+        id: NotificationId,
+        notification: Notification,
+        executor: &ForegroundExecutor,
+    ) {
+        self.send(NotificationRequest::Show(id, notification), executor);
+    }
+
+    pub(crate) fn close(&mut self, id: NotificationId, executor: &ForegroundExecutor) {
+        self.send(NotificationRequest::Close(id), executor);
+    }
+
+    pub(crate) fn on_event(&mut self, callback: Box<dyn FnMut(NotificationId, NotificationEvent)>) {
+        *self.event_callback.borrow_mut() = Some(callback);
+    }
+
+    fn send(&mut self, request: NotificationRequest, executor: &ForegroundExecutor) {
+        // The connection ends if the server can't be reached, in which case it's made again.
+        if self
+            .requests
+            .as_ref()
+            .is_some_and(|requests| requests.is_closed())
+        {
+            self.requests = None;
+        }
+        // Notifications of an ended connection have already been reported as dismissed.
+        if self.requests.is_none() && matches!(request, NotificationRequest::Close(_)) {
+            return;
+        }
+        let requests = self.requests.get_or_insert_with(|| {
+            // The connection to the server is only made once the first notification is posted.
+            let (sender, receiver) = unbounded();
+            executor
+                .spawn(run_notifications(receiver, self.event_callback.clone()).log_err())
+                .detach();
+            sender
+        });
+        requests.unbounded_send(request).log_err();
+    }
+}
+
+async fn run_notifications(
+    mut requests: UnboundedReceiver<NotificationRequest>,
+    event_callback: EventCallback,
+) -> anyhow::Result<()> {
+    let mut server_ids = HashMap::new();
+    let result = serve_notifications(&mut requests, &mut server_ids, &event_callback).await;
+
+    // Notifications that couldn't be posted, or whose dismissal can no longer be received, are
+    // reported as dismissed so that the app releases their callbacks.
+    requests.close();
+    let unposted =
+        iter::from_fn(|| requests.try_next().ok().flatten()).filter_map(|request| match request {
+            NotificationRequest::Show(id, _) => Some(id),
+            NotificationRequest::Close(_) => None,
+        });
+    for id in server_ids.into_keys().chain(unposted).collect::<Vec<_>>() {
+        let event = NotificationEvent::Dismissed(NotificationDismissReason::Undefined);
+        emit_event(&event_callback, id, event);
+    }
+
+    result
+}
+
+async fn serve_notifications(
+    requests: &mut UnboundedReceiver<NotificationRequest>,
+    server_ids: &mut HashMap<NotificationId, u32>,
+    event_callback: &EventCallback,
+) -> anyhow::Result<()> {
+    let app_name = std::env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    let notifications = Notifications::new().await?;
+    // Subscribe before posting anything, so that no signal can be missed.
+    let mut signals = Box::pin(notifications.receive_signals().await?.fuse());
+
+    loop {
+        futures::select_biased! {
+            request = requests.next() => match request {
+                Some(NotificationRequest::Show(id, notification)) => {
+                    match notifications.notify(&app_name, &notification).await.log_err() {
+                        Some(server_id) => {
+                            server_ids.insert(id, server_id);
+                        }
+                        None => {
+                            let reason = NotificationDismissReason::Undefined;
+                            emit_event(event_callback, id, NotificationEvent::Dismissed(reason));
+                        }
+                    }
+                }
+                Some(NotificationRequest::Close(id)) => {
+                    if let Some(server_id) = server_ids.get(&id) {
+                        notifications.close_notification(*server_id).await.log_err();
+                    }
+                }
+                None => break,
+            },
+            signal = signals.next() => {
+                let Some(signal) = signal else {
+                    break;
+                };
+                let (server_id, event) = match signal {
+                    NotificationSignal::ActionInvoked(server_id, action) => {
+                        (server_id, NotificationEvent::ActionInvoked(action.into()))
+                    }
+                    NotificationSignal::NotificationClosed(server_id, reason) => {
+                        let reason = match reason {
+                            1 => NotificationDismissReason::Expired,
+                            2 => NotificationDismissReason::DismissedByUser,
+                            3 => NotificationDismissReason::Closed,
+                            _ => NotificationDismissReason::Undefined,
+                        };
+                        (server_id, NotificationEvent::Dismissed(reason))
+                    }
+                };
+                // Signals are broadcast, so they may belong to notifications of other apps.
+                let Some(id) = server_ids
+                    .iter()
+                    .find_map(|(id, posted)| (*posted == server_id).then_some(*id))
+                else {
+                    continue;
+                };
+                if let NotificationEvent::Dismissed(_) = event {
+                    server_ids.remove(&id);
+                }
+                emit_event(event_callback, id, event);
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn emit_event(event_callback: &EventCallback, id: NotificationId, event: NotificationEvent) {
+    let callback = event_callback.borrow_mut().take();
+    if let Some(mut callback) = callback {
+        callback(id, event);
+        event_callback.borrow_mut().get_or_insert(callback);
+    }
+}
@@ -4,7 +4,7 @@ use calloop::channel::Sender;
 use serde::Serialize;
 use zbus::{
     interface,
-    object_server::SignalContext,
+    object_server::{InterfaceRef, SignalContext},
     zvariant::{Structure, StructureBuilder, Type, Value},
 };
 
@@ -53,9 +53,24 @@ pub struct Submenu {
     pub id: i32,
     pub icon_name: Option<String>,
     pub label: Option<String>,
+    // Key combinations, e.g. [["Control", "S"]]
+    pub shortcut: Option<Vec<Vec<String>>>,
+    pub separator: bool,
     pub children: Vec<Submenu>,
 }
 
+impl Submenu {
+    fn find(menus: &[Submenu], id: i32) -> Option<&Submenu> {
+        menus.iter().find_map(|menu| {
+            if menu.id == id {
+                Some(menu)
+            } else {
+                Self::find(&menu.children, id)
+            }
+        })
+    }
+}
+
 impl<'a> From<Submenu> for DBusMenuLayoutItem<'a> {
     fn from(value: Submenu) -> Self {
         let mut menu = DBusMenuLayoutItem {
@@ -69,6 +84,14 @@ impl<'a> From<Submenu> for DBusMenuLayoutItem<'a> {
         if let Some(label) = value.label {
             menu.properties.insert("label".into(), Value::from(label));
         }
+        if let Some(shortcut) = value.shortcut {
+            menu.properties
+                .insert("shortcut".into(), Value::from(shortcut));
+        }
+        if value.separator {
+            menu.properties
+                .insert("type".into(), Value::from("separator"));
+        }
         if !value.children.is_empty() {
             menu.properties
                 .insert("children-display".into(), Value::from("submenu"));
@@ -88,8 +111,22 @@ pub struct Menu {
 #[derive(Default)]
 pub struct DBusMenuInterface {
     pub menu: Menu,
-    // Forward DBus menu events to the StatusNotifierItem event stream
-    pub event_sender: Option<Sender<super::item::StatusNotifierItemEvents>>,
+    // Incremented whenever the menu is replaced, so that hosts know to fetch the layout again
+    pub revision: u32,
+    // Forward DBus menu events to the event stream of the menu's owner
+    pub on_event: Option<Box<dyn Fn(DBusMenuEvents) + Send + Sync>>,
+}
+
+impl DBusMenuInterface {
+    /// Replaces the menu, notifying hosts that they need to fetch the layout again.
+    pub async fn replace(iface_ref: &InterfaceRef<Self>, menu: Menu) -> zbus::Result<()> {
+        let cx = iface_ref.signal_context();
+        let mut iface = iface_ref.get_mut().await;
+        iface.menu = menu;
+        iface.revision += 1;
+        let revision = iface.revision;
+        iface.layout_updated(cx, revision, 0).await
+    }
 }
 
 #[interface(name = "com.canonical.dbusmenu")]
@@ -102,6 +139,15 @@ impl DBusMenuInterface {
         recursion_depth: i32,
         property_names: Vec<String>,
     ) -> (u32, DBusMenuLayoutItem) {
+        if parent_id != 0 {
+            // Hosts may fetch the layout of a submenu before showing it
+            let submenu = Submenu::find(&self.menu.children, parent_id)
+                .cloned()
+                .map(DBusMenuLayoutItem::from)
+                .unwrap_or_default();
+            return (self.revision, submenu);
+        }
+
         let mut main_menu = DBusMenuLayoutItem::default();
         if !self.menu.children.is_empty() {
             main_menu
@@ -112,17 +158,14 @@ impl DBusMenuInterface {
                 main_menu.children.push(Value::from(submenu));
             }
         }
-        (0, main_menu)
+        (self.revision, main_menu)
     }
 
     // Minimal event handling: translate a "clicked" event into a MenuClick with the same id
     pub async fn event(&self, id: i32, event_id: String, _event_data: Value<'_>, _timestamp: u32) {
         if event_id == "clicked" {
-            if let Some(sender) = &self.event_sender {
-                // Ignore send errors (receiver dropped) on purpose
-                let _ = sender.send(super::item::StatusNotifierItemEvents::MenuEvent(
-                    DBusMenuEvents::MenuClick(id),
-                ));
+            if let Some(on_event) = &self.on_event {
+                on_event(DBusMenuEvents::MenuClick(id));
             }
         }
     }
@@ -155,7 +198,11 @@ impl DBusMenu {
     ) -> zbus::Result<Self> {
         let iface = DBusMenuInterface {
             menu,
-            event_sender: Some(event_sender),
+            revision: 0,
+            on_event: Some(Box::new(move |event| {
+                // Ignore send errors (receiver dropped) on purpose
+                let _ = event_sender.send(super::item::StatusNotifierItemEvents::MenuEvent(event));
+            })),
         };
         let conn = zbus::connection::Builder::session()?
             .serve_at(DBUS_MENU_PATH, iface)?
@@ -13,13 +13,13 @@ pub struct StatusNotifierHost(zbus::Connection);
 
 impl StatusNotifierHost {
     pub async fn new(id: String) -> zbus::Result<Self> {
-        let watcher = StatusNotifierWatcher::new().await?;
         let name = format!("org.freedesktop.StatusNotifierHost-{}", id);
         let conn = zbus::connection::Builder::session()?
             .name(name.clone())?
             .serve_at(STATUS_NOTIFIER_HOST_PATH, StatusNotifierHostInterface)?
             .build()
             .await?;
+        let watcher = StatusNotifierWatcher::new(&conn).await?;
         watcher.register_status_notifier_host(name).await?;
         Ok(Self(conn))
     }
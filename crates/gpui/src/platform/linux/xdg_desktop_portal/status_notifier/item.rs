@@ -40,7 +40,7 @@ impl Display for Category {
 }
 
 #[allow(dead_code)]
-#[derive(Type, Default, Debug)]
+#[derive(Type, Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Status {
     #[default]
     Active,
@@ -115,43 +115,42 @@ impl ToolTip {
 impl From<ToolTip> for Structure<'_> {
     fn from(value: ToolTip) -> Self {
         StructureBuilder::new()
-            .add_field::<String>(value.icon.clone().name_or_default())
-            .add_field::<Vec<Pixmap>>(value.icon.pixmaps_or_default())
+            .add_field::<String>(value.icon.name)
+            .add_field::<Vec<Pixmap>>(value.icon.pixmaps)
             .add_field(value.title)
             .add_field(value.description)
             .build()
     }
 }
 
-#[derive(Clone, Debug, Type)]
+/// An icon from the icon theme, with pixmaps the host falls back to if it can't find the name.
+#[derive(Clone, Debug, Default, Type)]
 #[zvariant(signature = "(sv)")]
-pub enum Icon {
-    Name(String),
-    Pixmaps(Vec<Pixmap>),
+pub struct Icon {
+    pub name: String,
+    pub pixmaps: Vec<Pixmap>,
 }
 
 impl Icon {
-    fn name_or_default(self) -> String {
-        if let Self::Name(name) = self {
-            name
-        } else {
-            String::default()
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.pixmaps.is_empty()
+    }
+
+    /// A fully transparent icon of the same size, shown while a blinking icon is hidden.
+    fn transparent(&self) -> Self {
+        let (width, height) = self
+            .pixmaps
+            .first()
+            .map_or((16, 16), |pixmap| (pixmap.width, pixmap.height));
+        Self {
+            name: String::new(),
+            pixmaps: vec![Pixmap {
+                width,
+                height,
+                bytes: vec![0; (width * height * 4) as usize],
+            }],
         }
     }
-
-    fn pixmaps_or_default(self) -> Vec<Pixmap> {
-        if let Self::Pixmaps(pixmaps) = self {
-            pixmaps
-        } else {
-            Vec::default()
-        }
-    }
-}
-
-impl Default for Icon {
-    fn default() -> Self {
-        Self::Name(String::default())
-    }
 }
 
 #[derive(Default, Debug, Clone, Type)]
@@ -193,6 +192,21 @@ impl StatusNotifierItemOptions {
         self.tooltip = tooltip;
         self
     }
+
+    pub fn overlay(mut self, overlay: Icon) -> Self {
+        self.overlay = overlay;
+        self
+    }
+
+    pub fn attention(mut self, attention: Attention) -> Self {
+        self.attention = attention;
+        self
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
 }
 
 #[derive(Default)]
@@ -209,6 +223,20 @@ struct StatusNotifierItemInterface {
     callbacks: Callbacks,
     options: StatusNotifierItemOptions,
     menu: Option<OwnedObjectPath>,
+    // Set while a blinking attention icon is in its hidden phase
+    attention_hidden: bool,
+}
+
+impl StatusNotifierItemInterface {
+    fn attention_icon(&self) -> Icon {
+        if !self.attention_hidden {
+            self.options.attention.icon.clone()
+        } else if self.options.attention.icon.is_empty() {
+            self.options.icon.transparent()
+        } else {
+            self.options.attention.icon.transparent()
+        }
+    }
 }
 
 #[interface(name = "org.kde.StatusNotifierItem")]
@@ -245,32 +273,32 @@ impl StatusNotifierItemInterface {
 
     #[zbus(property, name = "IconName")]
     pub async fn icon_name(&self) -> String {
-        self.options.icon.clone().name_or_default()
+        self.options.icon.name.clone()
     }
 
     #[zbus(property, name = "IconPixmap")]
     pub async fn icon_pixmap(&self) -> Vec<Pixmap> {
-        self.options.icon.clone().pixmaps_or_default()
+        self.options.icon.pixmaps.clone()
     }
 
     #[zbus(property, name = "OverlayIconName")]
     pub async fn overlay_icon_name(&self) -> String {
-        self.options.overlay.clone().name_or_default()
+        self.options.overlay.name.clone()
     }
 
     #[zbus(property, name = "OverlayIconPixmap")]
     pub async fn overlay_icon_pixmap(&self) -> Vec<Pixmap> {
-        self.options.overlay.clone().pixmaps_or_default()
+        self.options.overlay.pixmaps.clone()
     }
 
     #[zbus(property, name = "AttentionIconName")]
     pub async fn attention_icon_name(&self) -> String {
-        self.options.attention.icon.clone().name_or_default()
+        self.attention_icon().name
     }
 
     #[zbus(property, name = "AttentionIconPixmap")]
     pub async fn attention_icon_pixmap(&self) -> Vec<Pixmap> {
-        self.options.attention.icon.clone().pixmaps_or_default()
+        self.attention_icon().pixmaps
     }
 
     #[zbus(property, name = "AttentionMovieName")]
@@ -355,10 +383,18 @@ impl StatusNotifierItemInterface {
 pub struct StatusNotifierItem {
     conn: zbus::Connection,
     iface_ref: InterfaceRef<StatusNotifierItemInterface>,
+    menu_ref: InterfaceRef<DBusMenuInterface>,
     channel: Channel<StatusNotifierItemEvents>,
     sender: Sender<StatusNotifierItemEvents>,
 }
 
+/// Updates the properties of a registered item in place, notifying the host of the changes.
+#[derive(Clone)]
+pub struct StatusNotifierItemHandle {
+    item: InterfaceRef<StatusNotifierItemInterface>,
+    menu: InterfaceRef<DBusMenuInterface>,
+}
+
 #[derive(Debug, Clone)]
 pub enum StatusNotifierItemEvents {
     Activate(i32, i32),
@@ -369,21 +405,18 @@ pub enum StatusNotifierItemEvents {
 }
 
 impl StatusNotifierItem {
-    pub async fn new(id: i32, options: StatusNotifierItemOptions, menu: Option<super::dbusmenu::Menu>) -> zbus::Result<Self> {
-        let watcher = StatusNotifierWatcher::new().await?;
-        let menu_path = if menu.is_some() {
-            Some(
-                OwnedObjectPath::try_from(DBUS_MENU_PATH)
-                    .map_err(|e| zbus::Error::Failure(e.to_string()))?,
-            )
-        } else {
-            None
-        };
+    pub async fn new(id: u64, options: StatusNotifierItemOptions, menu: Option<super::dbusmenu::Menu>) -> zbus::Result<Self> {
+        // Every item of the process gets its own object paths, so that several can be shown at once
+        let item_path = format!("{STATUS_NOTIFIER_ITEM_PATH}/{id}");
+        let dbus_menu_path = format!("{DBUS_MENU_PATH}/{id}");
+        let menu_path = OwnedObjectPath::try_from(dbus_menu_path.as_str())
+            .map_err(|e| zbus::Error::Failure(e.to_string()))?;
         let iface = StatusNotifierItemInterface {
             id: id.to_string(),
             options,
             callbacks: Default::default(),
-            menu: menu_path,
+            menu: menu.is_some().then_some(menu_path),
+            attention_hidden: false,
         };
         let name = format!(
             "org.freedesktop.StatusNotifierItem-{}-{}",
@@ -392,24 +425,40 @@ impl StatusNotifierItem {
         );
 
         let conn = zbus::connection::Builder::session()?
-            .name(name.clone())?
-            .serve_at(STATUS_NOTIFIER_ITEM_PATH, iface)?
+            .name(name)?
+            .serve_at(item_path.as_str(), iface)?
             .build()
             .await?;
         // Create an internal channel for emitting events to calloop
         let (sender, channel) = channel::channel();
 
-        if let Some(menu) = menu {
-            // Host the DBusMenu interface on the same object server
-            let iface = DBusMenuInterface { menu, event_sender: Some(sender.clone()) };
-            conn.object_server().at(DBUS_MENU_PATH, iface).await?;
-        }
-        watcher.register_status_notifier_item(name).await?;
+        // Host the DBusMenu interface on the same object server, even without a menu so that
+        // one can be added when the item is updated
+        let iface = DBusMenuInterface {
+            menu: menu.unwrap_or_default(),
+            revision: 0,
+            on_event: Some(Box::new({
+                let sender = sender.clone();
+                move |event| {
+                    // Ignore send errors (receiver dropped) on purpose
+                    let _ = sender.send(StatusNotifierItemEvents::MenuEvent(event));
+                }
+            })),
+        };
+        conn.object_server().at(dbus_menu_path.as_str(), iface).await?;
+        // Register by object path from the connection serving the item, the watcher
+        // identifies the item by the sender of the call and this path.
+        let watcher = StatusNotifierWatcher::new(&conn).await?;
+        watcher.register_status_notifier_item(item_path.as_str()).await?;
         let iface_ref = conn
             .object_server()
-            .interface::<_, StatusNotifierItemInterface>(STATUS_NOTIFIER_ITEM_PATH)
+            .interface::<_, StatusNotifierItemInterface>(item_path.as_str())
+            .await?;
+        let menu_ref = conn
+            .object_server()
+            .interface::<_, DBusMenuInterface>(dbus_menu_path.as_str())
             .await?;
-        let this = Self { conn, iface_ref, channel, sender };
+        let this = Self { conn, iface_ref, menu_ref, channel, sender };
 
         // Hook up interface callbacks to forward into our event stream
         this.on_activate(Box::new({
@@ -465,65 +514,113 @@ impl StatusNotifierItem {
         iface.callbacks.on_provide_xdg_activation_token = Some(fun);
     }
 
+    pub fn handle(&self) -> StatusNotifierItemHandle {
+        StatusNotifierItemHandle {
+            item: self.iface_ref.clone(),
+            menu: self.menu_ref.clone(),
+        }
+    }
+}
+
+impl StatusNotifierItemHandle {
+    /// Replaces the properties and menu of the item, without registering it again.
+    pub async fn update(
+        &self,
+        options: StatusNotifierItemOptions,
+        menu: Option<Menu>,
+    ) -> zbus::Result<()> {
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
+        let status = options.status.to_string();
+        let menu_path = menu
+            .is_some()
+            .then(|| OwnedObjectPath::from(self.menu.signal_context().path().clone()));
+        let menu_changed = iface.menu != menu_path;
+        iface.options = options;
+        iface.menu = menu_path;
+        iface.attention_hidden = false;
+        iface.new_title(cx).await?;
+        iface.new_icon(cx).await?;
+        iface.new_attention_icon(cx).await?;
+        iface.new_overlay_icon(cx).await?;
+        iface.new_tooltip(cx).await?;
+        iface.new_status(cx, status).await?;
+        if menu_changed {
+            iface.menu_changed(cx).await?;
+        }
+        drop(iface);
+
+        DBusMenuInterface::replace(&self.menu, menu.unwrap_or_default()).await
+    }
+
+    /// Hides or shows the attention icon, used to make it blink.
+    pub async fn set_attention_hidden(&self, hidden: bool) -> zbus::Result<()> {
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
+        iface.attention_hidden = hidden;
+        iface.new_attention_icon(cx).await?;
+        Ok(())
+    }
+
     pub async fn set_title(&self, name: impl Into<String>) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.title = name.into();
         iface.new_title(cx).await?;
         Ok(())
     }
 
     pub async fn set_icon(&self, icon: Icon) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.icon = icon;
         iface.new_icon(cx).await?;
         Ok(())
     }
 
     pub async fn set_overlay(&self, overlay: Icon) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.overlay = overlay;
-        iface.new_icon(cx).await?;
+        iface.new_overlay_icon(cx).await?;
         Ok(())
     }
 
     pub async fn set_attention(&self, attention: Attention) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.attention = attention;
-        iface.new_icon(cx).await?;
+        iface.new_attention_icon(cx).await?;
         Ok(())
     }
 
     pub async fn set_tooltip(&self, tooltip: ToolTip) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.tooltip = tooltip;
         iface.new_tooltip(cx).await?;
         Ok(())
     }
 
     pub async fn set_tooltip_title(&self, title: String) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.tooltip.title = title;
         iface.new_tooltip(cx).await?;
         Ok(())
     }
 
     pub async fn set_tooltip_description(&self, description: String) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         iface.options.tooltip.description = description;
         iface.new_tooltip(cx).await?;
         Ok(())
     }
 
     pub async fn set_status(&self, status: Status) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         let status_str = status.to_string();
         iface.options.status = status;
         iface.new_status(cx, status_str).await?;
@@ -531,8 +628,8 @@ impl StatusNotifierItem {
     }
 
     pub async fn set_category(&self, category: Category) -> zbus::Result<()> {
-        let cx = self.iface_ref.signal_context();
-        let mut iface = self.iface_ref.get_mut().await;
+        let cx = self.item.signal_context();
+        let mut iface = self.item.get_mut().await;
         let category_str = category.to_string();
         iface.options.category = category;
         iface.new_status(cx, category_str).await?;
@@ -13,9 +13,8 @@ const STATUS_NOTIFIER_WATCHER_DESTINATION: &str = "org.kde.StatusNotifierWatcher
 
 #[allow(dead_code)]
 impl<'a> StatusNotifierWatcher<'a> {
-    pub async fn new() -> zbus::Result<Self> {
-        let conn = zbus::Connection::session().await?;
-        let proxy: zbus::Proxy = zbus::ProxyBuilder::new(&conn)
+    pub async fn new(conn: &zbus::Connection) -> zbus::Result<Self> {
+        let proxy: zbus::Proxy = zbus::ProxyBuilder::new(conn)
             .interface(STATUS_NOTIFIER_WATCHER_INTERFACE)?
             .path(STATUS_NOTIFIER_WATCHER_PATH)?
             .destination(STATUS_NOTIFIER_WATCHER_DESTINATION)?
@@ -9,7 +9,8 @@ use crate::{
     CursorStyle, ForegroundExecutor, Image, ImageFormat, KeyContext, Keymap, MacDispatcher,
     MacDisplay, MacTray, MacWindow, Menu, MenuItem, OsMenu, OwnedMenu, PathPromptOptions, Platform,
     PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
-    PlatformWindow, Result, SystemMenuType, Task, Tray, WindowAppearance, WindowParams, hash,
+    PlatformWindow, Result, SystemMenuType, Task, Tray, TrayId, WindowAppearance, WindowParams,
+    hash,
 };
 use anyhow::{Context as _, anyhow};
 use block::ConcreteBlock;
@@ -952,7 +953,7 @@ impl Platform for MacPlatform {
         }
     }
 
-    fn set_tray(&self, mut tray: Tray, menu: Option<Vec<MenuItem>>, keymap: &Keymap) {
+    fn set_tray(&self, _id: TrayId, mut tray: Tray, menu: Option<Vec<MenuItem>>, keymap: &Keymap) {
         let mut state = self.0.lock();
 
         if let Some(menu) = menu {
@@ -1086,7 +1087,9 @@ impl Platform for MacPlatform {
                         ClipboardEntry::Image(image) => {
                             self.write_image_to_clipboard(image);
                         }
-                        ClipboardEntry::ExternalPaths(_) => {}
+                        ClipboardEntry::ExternalPaths(_)
+                        | ClipboardEntry::Html(_)
+                        | ClipboardEntry::Custom { .. } => {}
                     },
                     None => {
                         // Writing an empty list of entries just clears the clipboard.
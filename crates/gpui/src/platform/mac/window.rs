@@ -2384,8 +2384,14 @@ extern "C" fn dragging_entered(this: &Object, _: Sel, dragging_info: id) -> NSDr
     let window_state = unsafe { get_window_state(this) };
     let position = drag_event_position(&window_state, dragging_info);
     let paths = external_paths_from_event(dragging_info);
-    if let Some(event) =
-        paths.map(|paths| PlatformInput::FileDrop(FileDropEvent::Entered { position, paths }))
+    let event = paths.map(|paths| {
+        PlatformInput::FileDrop(FileDropEvent::Entered {
+            position,
+            paths,
+            data: Default::default(),
+        })
+    });
+    if let Some(event) = event
         && send_new_event(&window_state, event)
     {
         window_state.lock().external_files_dragged = true;
@@ -1,16 +1,17 @@
 use crate::{
     AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DevicePixels,
-    DummyKeyboardMapper, ForegroundExecutor, Keymap, NoopTextSystem, Platform, PlatformDisplay,
-    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PromptButton,
-    ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream, SourceMetadata, Task,
-    TestDisplay, TestWindow, Tray, WindowAppearance, WindowParams, size,
+    DummyKeyboardMapper, ForegroundExecutor, GlobalHotkeyId, Keymap, Keystroke, NoopTextSystem,
+    Notification, NotificationDismissReason, NotificationEvent, NotificationId, Platform,
+    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
+    PromptButton, ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream, SourceMetadata,
+    Task, TestDisplay, TestWindow, Tray, TrayEvent, TrayId, WindowAppearance, WindowParams, size,
 };
 use anyhow::Result;
 use collections::VecDeque;
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
     sync::Arc,
@@ -32,9 +33,21 @@ pub(crate) struct TestPlatform {
     current_clipboard_item: Mutex<Option<ClipboardItem>>,
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     current_primary_item: Mutex<Option<ClipboardItem>>,
+    clipboard_change: RefCell<Option<Box<dyn FnMut()>>>,
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    primary_selection_change: RefCell<Option<Box<dyn FnMut()>>>,
     pub(crate) prompts: RefCell<TestPrompts>,
     screen_capture_sources: RefCell<Vec<TestScreenCaptureSource>>,
     pub opened_url: RefCell<Option<String>>,
+    pub(crate) notifications: RefCell<Vec<(NotificationId, Notification)>>,
+    notification_event: RefCell<Option<Box<dyn FnMut(NotificationId, NotificationEvent)>>>,
+    pub(crate) trays: RefCell<Vec<(TrayId, Tray)>>,
+    tray_event: RefCell<Option<Box<dyn FnMut(TrayId, TrayEvent)>>>,
+    pub(crate) global_hotkeys: RefCell<Vec<(GlobalHotkeyId, Keystroke)>>,
+    global_hotkey: RefCell<Option<Box<dyn FnMut(GlobalHotkeyId)>>>,
+    pub(crate) badge_count: Cell<Option<u64>>,
+    pub(crate) progress: Cell<Option<f32>>,
+    pub(crate) user_attention_requested: Cell<bool>,
     pub text_system: Arc<dyn PlatformTextSystem>,
     pub expect_restart: RefCell<Option<oneshot::Sender<Option<PathBuf>>>>,
     #[cfg(target_os = "windows")]
@@ -117,8 +130,20 @@ impl TestPlatform {
             current_clipboard_item: Mutex::new(None),
             #[cfg(any(target_os = "linux", target_os = "freebsd"))]
             current_primary_item: Mutex::new(None),
+            clipboard_change: Default::default(),
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            primary_selection_change: Default::default(),
             weak: weak.clone(),
             opened_url: Default::default(),
+            notifications: Default::default(),
+            notification_event: Default::default(),
+            trays: Default::default(),
+            tray_event: Default::default(),
+            global_hotkeys: Default::default(),
+            global_hotkey: Default::default(),
+            badge_count: Default::default(),
+            progress: Default::default(),
+            user_attention_requested: Default::default(),
             #[cfg(target_os = "windows")]
             bitmap_factory,
             text_system,
@@ -221,6 +246,91 @@ impl TestPlatform {
     pub(crate) fn did_prompt_for_new_path(&self) -> bool {
         !self.prompts.borrow().new_path.is_empty()
     }
+
+    #[track_caller]
+    pub(crate) fn simulate_notification_event(&self, id: NotificationId, event: NotificationEvent) {
+        let mut notifications = self.notifications.borrow_mut();
+        let Some(ix) = notifications.iter().position(|(posted, _)| *posted == id) else {
+            panic!("no posted notification with id {:?}", id)
+        };
+        if let NotificationEvent::ActionInvoked(action) = &event
+            && !notifications[ix].1.actions.iter().any(|a| &a.id == action)
+        {
+            panic!(
+                "NOTIFICATION: {}\n{:?}\nCannot invoke action {}",
+                notifications[ix].1.title,
+                notifications[ix]
+                    .1
+                    .actions
+                    .iter()
+                    .map(|a| &a.id)
+                    .collect::<Vec<_>>(),
+                action
+            )
+        }
+        if let NotificationEvent::Dismissed(_) = &event {
+            notifications.remove(ix);
+        }
+        drop(notifications);
+
+        let callback = self.notification_event.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(id, event);
+            self.notification_event.borrow_mut().get_or_insert(callback);
+        }
+    }
+
+    // Notifies on the next tick like a platform would, as the app is borrowed while writing
+    fn notify_selection_change(&self, handler: fn(&Self) -> &RefCell<Option<Box<dyn FnMut()>>>) {
+        let this = self.weak.clone();
+        self.foreground_executor
+            .spawn(async move {
+                if let Some(this) = this.upgrade() {
+                    let callback = handler(&this).borrow_mut().take();
+                    if let Some(mut callback) = callback {
+                        callback();
+                        handler(&this).borrow_mut().get_or_insert(callback);
+                    }
+                }
+            })
+            .detach();
+    }
+
+    #[track_caller]
+    pub(crate) fn simulate_tray_event(&self, id: TrayId, event: TrayEvent) {
+        if !self
+            .trays
+            .borrow()
+            .iter()
+            .any(|(tray_id, _)| *tray_id == id)
+        {
+            panic!("Cannot simulate {event:?} on tray icon {id:?}, it is not shown");
+        }
+
+        let callback = self.tray_event.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(id, event);
+            self.tray_event.borrow_mut().get_or_insert(callback);
+        }
+    }
+
+    #[track_caller]
+    pub(crate) fn simulate_global_hotkey(&self, keystroke: &Keystroke) {
+        let Some(id) = self
+            .global_hotkeys
+            .borrow()
+            .iter()
+            .find_map(|(id, registered)| (registered == keystroke).then_some(*id))
+        else {
+            panic!("Cannot simulate global hotkey {keystroke}, it is not registered");
+        };
+
+        let callback = self.global_hotkey.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(id);
+            self.global_hotkey.borrow_mut().get_or_insert(callback);
+        }
+    }
 }
 
 impl Platform for TestPlatform {
@@ -373,10 +483,100 @@ impl Platform for TestPlatform {
 
     fn set_menus(&self, _menus: Vec<crate::Menu>, _keymap: &Keymap) {}
     fn set_dock_menu(&self, _menu: Vec<crate::MenuItem>, _keymap: &Keymap) {}
-    fn set_tray(&self, _tray: Tray, _menu: Option<Vec<crate::MenuItem>>, _keymap: &Keymap) {}
+    fn set_tray(
+        &self,
+        id: TrayId,
+        tray: Tray,
+        _menu: Option<Vec<crate::MenuItem>>,
+        _keymap: &Keymap,
+    ) {
+        let mut trays = self.trays.borrow_mut();
+        if let Some((_, shown)) = trays.iter_mut().find(|(tray_id, _)| *tray_id == id) {
+            *shown = tray;
+        } else {
+            trays.push((id, tray));
+        }
+    }
+
+    fn remove_tray(&self, id: TrayId) {
+        self.trays
+            .borrow_mut()
+            .retain(|(tray_id, _)| *tray_id != id);
+    }
+
+    fn on_tray_event(&self, callback: Box<dyn FnMut(TrayId, TrayEvent)>) {
+        *self.tray_event.borrow_mut() = Some(callback);
+    }
+
+    fn register_global_hotkey(
+        &self,
+        id: GlobalHotkeyId,
+        keystroke: &Keystroke,
+        _name: &str,
+    ) -> Result<()> {
+        self.global_hotkeys
+            .borrow_mut()
+            .push((id, keystroke.clone()));
+        Ok(())
+    }
+
+    fn unregister_global_hotkey(&self, id: GlobalHotkeyId) {
+        self.global_hotkeys
+            .borrow_mut()
+            .retain(|(registered, _)| *registered != id);
+    }
+
+    fn on_global_hotkey(&self, callback: Box<dyn FnMut(GlobalHotkeyId)>) {
+        *self.global_hotkey.borrow_mut() = Some(callback);
+    }
+
+    fn show_notification(&self, id: NotificationId, notification: Notification) -> bool {
+        self.notifications.borrow_mut().push((id, notification));
+        true
+    }
+
+    fn close_notification(&self, id: NotificationId) {
+        let this = self.weak.clone();
+        self.foreground_executor
+            .spawn(async move {
+                if let Some(this) = this.upgrade()
+                    && this
+                        .notifications
+                        .borrow()
+                        .iter()
+                        .any(|(posted, _)| *posted == id)
+                {
+                    this.simulate_notification_event(
+                        id,
+                        NotificationEvent::Dismissed(NotificationDismissReason::Closed),
+                    );
+                }
+            })
+            .detach();
+    }
+
+    fn on_notification_event(&self, callback: Box<dyn FnMut(NotificationId, NotificationEvent)>) {
+        *self.notification_event.borrow_mut() = Some(callback);
+    }
 
     fn add_recent_document(&self, _paths: &Path) {}
 
+    fn set_badge_count(&self, count: Option<u64>) {
+        self.badge_count.set(count);
+    }
+
+    fn set_progress(&self, progress: Option<f32>) {
+        self.progress.set(progress);
+    }
+
+    fn request_user_attention(&self, urgent: bool) {
+        self.user_attention_requested.set(urgent);
+    }
+
+    fn window_state_path(&self) -> Option<PathBuf> {
+        None
+    }
+
     fn on_app_menu_action(&self, _callback: Box<dyn FnMut(&dyn crate::Action)>) {}
 
     fn on_will_open_app_menu(&self, _callback: Box<dyn FnMut()>) {}
@@ -402,10 +602,12 @@ impl Platform for TestPlatform {
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     fn write_to_primary(&self, item: ClipboardItem) {
         *self.current_primary_item.lock() = Some(item);
+        self.notify_selection_change(|this| &this.primary_selection_change);
     }
 
     fn write_to_clipboard(&self, item: ClipboardItem) {
         *self.current_clipboard_item.lock() = Some(item);
+        self.notify_selection_change(|this| &this.clipboard_change);
     }
 
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -417,6 +619,23 @@ impl Platform for TestPlatform {
         self.current_clipboard_item.lock().clone()
     }
 
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.current_clipboard_item
+            .lock()
+            .as_ref()?
+            .custom(mime)
+            .map(<[u8]>::to_vec)
+    }
+
+    fn on_clipboard_change(&self, callback: Box<dyn FnMut()>) {
+        *self.clipboard_change.borrow_mut() = Some(callback);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn on_primary_selection_change(&self, callback: Box<dyn FnMut()>) {
+        *self.primary_selection_change.borrow_mut() = Some(callback);
+    }
+
     fn write_credentials(&self, _url: &str, _username: &str, _password: &[u8]) -> Task<Result<()>> {
         Task::ready(Ok(()))
     }
@@ -466,3 +685,75 @@ impl PlatformKeyboardLayout for TestKeyboardLayout {
         "zed.keyboard.example"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClipboardEntry, ClipboardItem, Empty, TestAppContext};
+    use std::{cell::Cell, rc::Rc};
+
+    #[gpui::test]
+    fn test_observe_clipboard(cx: &mut TestAppContext) {
+        let changes = Rc::new(Cell::new(0));
+        let subscription = cx.update(|cx| {
+            let changes = changes.clone();
+            cx.observe_clipboard(move |cx| {
+                assert_eq!(
+                    cx.read_from_clipboard(),
+                    Some(ClipboardItem::new_string("copied".into()))
+                );
+                changes.set(changes.get() + 1);
+            })
+        });
+
+        cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string("copied".into())));
+        cx.run_until_parked();
+        assert_eq!(changes.get(), 1);
+
+        drop(subscription);
+        cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string("copied".into())));
+        cx.run_until_parked();
+        assert_eq!(changes.get(), 1);
+    }
+
+    #[gpui::test]
+    fn test_rich_clipboard_entries(cx: &mut TestAppContext) {
+        let item = ClipboardItem::new_string("bold".into())
+            .with_entry(ClipboardEntry::Html("<b>bold</b>".into()))
+            .with_entry(ClipboardEntry::Custom {
+                mime: "application/x-gpui-test".into(),
+                bytes: vec![1, 2, 3],
+            });
+        cx.update(|cx| cx.write_to_clipboard(item.clone()));
+
+        let read = cx.update(|cx| cx.read_from_clipboard()).unwrap();
+        assert_eq!(read, item);
+        assert_eq!(read.text().as_deref(), Some("bold"));
+        assert_eq!(read.html(), Some("<b>bold</b>"));
+        assert_eq!(
+            read.custom("application/x-gpui-test"),
+            Some([1, 2, 3].as_slice())
+        );
+        assert_eq!(read.custom("text/rtf"), None);
+        assert_eq!(
+            cx.update(|cx| cx.read_custom_from_clipboard("application/x-gpui-test")),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            cx.update(|cx| cx.read_custom_from_clipboard("text/rtf")),
+            None
+        );
+    }
+
+    #[gpui::test]
+    fn test_inhibit_idle(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| Empty);
+        let video = cx.update(|window, _| window.inhibit_idle("Playing video"));
+        let presentation = cx.update(|window, _| window.inhibit_idle("Presenting"));
+        assert_eq!(cx.idle_inhibit_reasons(), ["Playing video", "Presenting"]);
+
+        drop(video);
+        assert_eq!(cx.idle_inhibit_reasons(), ["Presenting"]);
+        drop(presentation);
+        assert!(cx.idle_inhibit_reasons().is_empty());
+    }
+}
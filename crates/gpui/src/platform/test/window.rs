@@ -1,10 +1,12 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
+    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, ClipboardItem,
+    DispatchEventResult, DragAction, DragResult, GpuSpecs, Pixels, PlatformAtlas, PlatformDisplay,
+    PlatformInput, PlatformInputHandler, PlatformWindow, Point, PromptButton, RequestFrameOptions,
+    Size, Subscription, TestPlatform, TileId, WindowAppearance, WindowBackgroundAppearance,
+    WindowBounds, WindowControlArea, WindowParams,
 };
 use collections::HashMap;
+use futures::channel::oneshot;
 use parking_lot::Mutex;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use std::{
@@ -29,6 +31,8 @@ pub(crate) struct TestWindowState {
     moved_callback: Option<Box<dyn FnMut()>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    pub(crate) native_drag: Option<(ClipboardItem, Vec<DragAction>, oneshot::Sender<DragResult>)>,
+    pub(crate) idle_inhibit_reasons: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -74,6 +78,8 @@ impl TestWindow {
             moved_callback: None,
             input_handler: None,
             is_fullscreen: false,
+            native_drag: None,
+            idle_inhibit_reasons: Vec::new(),
         })))
     }
 
@@ -182,6 +188,30 @@ impl PlatformWindow for TestWindow {
         )
     }
 
+    fn start_drag(
+        &self,
+        item: ClipboardItem,
+        actions: &[DragAction],
+    ) -> Option<oneshot::Receiver<DragResult>> {
+        let (tx, rx) = oneshot::channel();
+        self.0.lock().native_drag = Some((item, actions.to_vec(), tx));
+        Some(rx)
+    }
+
+    fn inhibit_idle(&self, reason: &str) -> Subscription {
+        self.0.lock().idle_inhibit_reasons.push(reason.to_string());
+        let state = Rc::downgrade(&self.0);
+        let reason = reason.to_string();
+        Subscription::new(move || {
+            if let Some(state) = state.upgrade() {
+                let mut state = state.lock();
+                if let Some(ix) = state.idle_inhibit_reasons.iter().position(|r| *r == reason) {
+                    state.idle_inhibit_reasons.remove(ix);
+                }
+            }
+        })
+    }
+
     fn activate(&self) {
         self.0
             .lock()
@@ -156,7 +156,9 @@ fn write_to_clipboard_inner(item: ClipboardItem) -> Result<()> {
             ClipboardEntry::Image(image) => {
                 write_image_to_clipboard(image)?;
             }
-            ClipboardEntry::ExternalPaths(_) => {}
+            ClipboardEntry::ExternalPaths(_)
+            | ClipboardEntry::Html(_)
+            | ClipboardEntry::Custom { .. } => {}
         },
         None => {
             // Writing an empty list of entries just clears the clipboard.
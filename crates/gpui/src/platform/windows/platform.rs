@@ -566,7 +566,13 @@ impl Platform for WindowsPlatform {
         self.set_dock_menus(menus);
     }
 
-    fn set_tray(&self, mut tray: Tray, menus: Option<Vec<MenuItem>>, _keymap: &Keymap) {
+    fn set_tray(
+        &self,
+        _id: TrayId,
+        mut tray: Tray,
+        menus: Option<Vec<MenuItem>>,
+        _keymap: &Keymap,
+    ) {
         let mut action_map = HashMap::new();
         let tray_menu = menus
             .as_ref()
@@ -960,6 +960,7 @@ impl IDropTarget_Impl for WindowsDragDropHandler_Impl {
                         scale_factor,
                     ),
                     paths: ExternalPaths(paths),
+                    data: Default::default(),
                 });
                 self.handle_drag_drop(input);
             } else {
@@ -1,10 +1,14 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range};
 
 use ::sum_tree::SumTree;
 use collections::FxHashMap;
+use smallvec::SmallVec;
 use sum_tree::Bias;
 
-use crate::{FocusHandle, FocusId};
+use crate::{Bounds, Direction, FocusHandle, FocusId, Pixels};
+
+/// The elements to focus instead of the nearest one when moving focus in a direction.
+pub(crate) type FocusNeighbors = SmallVec<[(Direction, FocusHandle); 1]>;
 
 /// Represents a collection of focus handles using the tab-index APIs.
 #[derive(Debug)]
@@ -13,15 +17,32 @@ pub(crate) struct TabStopMap {
     pub(crate) insertion_history: Vec<TabStopOperation>,
     by_id: FxHashMap<FocusId, TabStopNode>,
     order: SumTree<TabStopNode>,
+    /// The insertion indices of the groups being inserted into, which unlike paths tell apart
+    /// groups with the same tab index.
+    current_groups: SmallVec<[usize; 6]>,
+    placements: FxHashMap<FocusId, (TabStopPlacement, SmallVec<[usize; 6]>)>,
+    /// The insertion indices spanned by the focus trap that `next` and `prev` are confined to,
+    /// which is the last one to begin.
+    trap: Option<Range<usize>>,
+    trap_ids: SmallVec<[FocusId; 2]>,
 }
 
 #[derive(Debug, Clone)]
 pub enum TabStopOperation {
     Insert(FocusHandle),
+    Place(FocusId, TabStopPlacement),
     Group(TabIndex),
+    Trap(Option<FocusId>),
     GroupEnd,
 }
 
+/// Where a focus handle was painted, for moving focus spatially.
+#[derive(Debug, Clone)]
+pub struct TabStopPlacement {
+    bounds: Bounds<Pixels>,
+    neighbors: FocusNeighbors,
+}
+
 impl TabStopOperation {
     fn focus_handle(&self) -> Option<&FocusHandle> {
         match self {
@@ -70,6 +91,10 @@ impl Default for TabStopMap {
             insertion_history: Vec::new(),
             by_id: FxHashMap::default(),
             order: SumTree::new(()),
+            current_groups: SmallVec::new(),
+            placements: FxHashMap::default(),
+            trap: None,
+            trap_ids: SmallVec::new(),
         }
     }
 }
@@ -89,15 +114,46 @@ impl TabStopMap {
         self.order.insert_or_replace(order, ());
     }
 
+    /// Records the visible bounds of an inserted focus handle, and the neighbors to focus from it
+    /// in place of the nearest tab stop in their direction.
+    pub fn place(&mut self, focus_id: FocusId, bounds: Bounds<Pixels>, neighbors: FocusNeighbors) {
+        let placement = TabStopPlacement { bounds, neighbors };
+        self.insertion_history
+            .push(TabStopOperation::Place(focus_id, placement.clone()));
+        self.placements
+            .insert(focus_id, (placement, self.current_groups.clone()));
+    }
+
     pub fn begin_group(&mut self, tab_index: isize) {
         self.insertion_history
             .push(TabStopOperation::Group(tab_index));
         self.current_path.0.push(tab_index);
+        self.current_groups.push(self.insertion_history.len() - 1);
+    }
+
+    /// Begins a group that confines `next` and `prev` to its tab stops, ended with `end_group`.
+    /// The trap is identified across frames by the given focus id, if any.
+    pub fn begin_trap(&mut self, trap_id: Option<FocusId>) {
+        self.insertion_history.push(TabStopOperation::Trap(trap_id));
+        self.current_path.0.push(0);
+        self.current_groups.push(self.insertion_history.len() - 1);
+        self.trap_ids.extend(trap_id);
     }
 
     pub fn end_group(&mut self) {
         self.insertion_history.push(TabStopOperation::GroupEnd);
         self.current_path.0.pop();
+        if let Some(start) = self.current_groups.pop()
+            && matches!(self.insertion_history[start], TabStopOperation::Trap(_))
+            && self.trap.as_ref().is_none_or(|trap| trap.start < start)
+        {
+            self.trap = Some(start..self.insertion_history.len());
+        }
+    }
+
+    /// The ids of the focus traps that were inserted, in order.
+    pub fn trap_ids(&self) -> &[FocusId] {
+        &self.trap_ids
     }
 
     pub fn clear(&mut self) {
@@ -109,6 +165,10 @@ impl TabStopMap {
     }
 
     pub fn next(&self, focused_id: Option<&FocusId>) -> Option<FocusHandle> {
+        if let Some(trap) = &self.trap {
+            return self.next_in_trap(trap, focused_id, false);
+        }
+
         let Some(focused_id) = focused_id else {
             let first = self.order.first()?;
             if first.tab_stop {
@@ -146,6 +206,10 @@ impl TabStopMap {
     }
 
     pub fn prev(&self, focused_id: Option<&FocusId>) -> Option<FocusHandle> {
+        if let Some(trap) = &self.trap {
+            return self.next_in_trap(trap, focused_id, true);
+        }
+
         let Some(focused_id) = focused_id else {
             let last = self.order.last()?;
             if last.tab_stop {
@@ -169,6 +233,91 @@ impl TabStopMap {
         }
     }
 
+    /// Returns the tab stop to focus when moving from the focused one in the given direction: its
+    /// neighbor in that direction if it has one, or else the nearest tab stop in that direction
+    /// within its innermost tab group. Like `next` and `prev`, it doesn't leave the focus trap.
+    pub fn in_direction(
+        &self,
+        focused_id: Option<&FocusId>,
+        direction: Direction,
+    ) -> Option<FocusHandle> {
+        let Some((focused_id, (placement, groups))) = focused_id
+            .filter(|id| {
+                self.tab_node_for_focus_id(id)
+                    .is_some_and(|node| self.in_trap(node))
+            })
+            .and_then(|id| Some((id, self.placements.get(id)?)))
+        else {
+            return self.next(None);
+        };
+        if let Some((_, neighbor)) = placement
+            .neighbors
+            .iter()
+            .find(|(neighbor_direction, _)| *neighbor_direction == direction)
+            .filter(|(_, neighbor)| {
+                self.tab_node_for_focus_id(&neighbor.id)
+                    .is_none_or(|node| self.in_trap(node))
+            })
+        {
+            return Some(neighbor.clone());
+        }
+
+        self.order
+            .iter()
+            .filter(|candidate| candidate.tab_stop && self.in_trap(candidate))
+            .filter_map(|candidate| {
+                let focus_handle = self.focus_handle_for_order(candidate)?;
+                let (candidate_placement, candidate_groups) =
+                    self.placements.get(&focus_handle.id)?;
+                if focus_handle.id == *focused_id || !candidate_groups.starts_with(groups) {
+                    return None;
+                }
+                let distance =
+                    spatial_distance(placement.bounds, candidate_placement.bounds, direction)?;
+                Some((distance, focus_handle))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, focus_handle)| focus_handle)
+    }
+
+    fn in_trap(&self, node: &TabStopNode) -> bool {
+        self.trap
+            .as_ref()
+            .is_none_or(|trap| trap.contains(&node.node_insertion_index))
+    }
+
+    /// Cycles through the tab stops in the trap, starting from its first or last one when focus
+    /// is outside of it.
+    fn next_in_trap(
+        &self,
+        trap: &Range<usize>,
+        focused_id: Option<&FocusId>,
+        reverse: bool,
+    ) -> Option<FocusHandle> {
+        let tab_stops = self
+            .order
+            .iter()
+            .filter(|node| node.tab_stop && trap.contains(&node.node_insertion_index))
+            .collect::<Vec<_>>();
+        let focused = focused_id
+            .and_then(|focused_id| self.tab_node_for_focus_id(focused_id))
+            .filter(|node| trap.contains(&node.node_insertion_index));
+        let next = match focused {
+            Some(focused) if reverse => tab_stops
+                .iter()
+                .rev()
+                .find(|node| **node < focused)
+                .or(tab_stops.last()),
+            Some(focused) => tab_stops
+                .iter()
+                .find(|node| **node > focused)
+                .or(tab_stops.first()),
+            None if reverse => tab_stops.last(),
+            None => tab_stops.first(),
+        };
+        self.focus_handle_for_order(next?)
+    }
+
     fn prev_inner(&self, node: &TabStopNode) -> Option<&TabStopNode> {
         let mut cursor = self.order.cursor::<TabStopNode>(());
         cursor.seek(&node, Bias::Left);
@@ -186,7 +335,11 @@ impl TabStopMap {
         for node in nodes {
             match node {
                 TabStopOperation::Insert(focus_handle) => self.insert(focus_handle),
+                TabStopOperation::Place(focus_id, placement) => {
+                    self.place(*focus_id, placement.bounds, placement.neighbors.clone())
+                }
                 TabStopOperation::Group(tab_index) => self.begin_group(*tab_index),
+                TabStopOperation::Trap(trap_id) => self.begin_trap(*trap_id),
                 TabStopOperation::GroupEnd => self.end_group(),
             }
         }
@@ -213,6 +366,54 @@ impl TabStopMap {
     }
 }
 
+/// How far the target is from the origin in the given direction, or `None` if it isn't in that
+/// direction. Targets that are out of line with the origin count as further away.
+fn spatial_distance(
+    origin: Bounds<Pixels>,
+    target: Bounds<Pixels>,
+    direction: Direction,
+) -> Option<f32> {
+    if target.is_empty() {
+        return None;
+    }
+
+    // The distance between the facing edges, and between the ranges the bounds span across.
+    let (distance, origin_range, target_range) = match direction {
+        Direction::Up => (
+            origin.top() - target.bottom(),
+            (origin.left(), origin.right()),
+            (target.left(), target.right()),
+        ),
+        Direction::Down => (
+            target.top() - origin.bottom(),
+            (origin.left(), origin.right()),
+            (target.left(), target.right()),
+        ),
+        Direction::Left => (
+            origin.left() - target.right(),
+            (origin.top(), origin.bottom()),
+            (target.top(), target.bottom()),
+        ),
+        Direction::Right => (
+            target.left() - origin.right(),
+            (origin.top(), origin.bottom()),
+            (target.top(), target.bottom()),
+        ),
+    };
+    // Allow for elements that overlap by a subpixel after rounding.
+    let distance = f32::from(distance);
+    if distance < -1. {
+        return None;
+    }
+    let misalignment = f32::from(target_range.0 - origin_range.1)
+        .max(f32::from(origin_range.0 - target_range.1))
+        .max(0.);
+    // Among aligned targets, prefer the one most centered on the origin.
+    let center_offset =
+        f32::from((target_range.0 + target_range.1 - origin_range.0 - origin_range.1) / 2.).abs();
+    Some(distance.max(0.) + 2. * misalignment + 0.1 * center_offset)
+}
+
 mod sum_tree_impl {
     use sum_tree::SeekTarget;
 
@@ -312,7 +513,11 @@ mod sum_tree_impl {
 mod tests {
     use itertools::Itertools as _;
 
-    use crate::{FocusHandle, FocusId, FocusMap, TabStopMap};
+    use crate::{
+        Bounds, Direction, FocusHandle, FocusId, FocusMap, FocusNeighbors, TabStopMap, point, px,
+        size,
+    };
+    use smallvec::smallvec;
     use std::sync::Arc;
 
     #[test]
@@ -404,6 +609,149 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_in_direction() {
+        let focus_map = Arc::new(FocusMap::default());
+        let mut tab_index_map = TabStopMap::default();
+        let cell = |x: f32, y: f32| Bounds::new(point(px(x), px(y)), size(px(90.), px(40.)));
+
+        // A 2x2 grid in a tab group, next to a sidebar.
+        let [a, b, c, d, sidebar] =
+            std::array::from_fn(|_| FocusHandle::new(&focus_map).tab_stop(true));
+        tab_index_map.begin_group(0);
+        for (handle, bounds) in [
+            (&a, cell(0., 0.)),
+            (&b, cell(100., 0.)),
+            (&c, cell(0., 50.)),
+            (&d, cell(100., 50.)),
+        ] {
+            tab_index_map.insert(handle);
+            tab_index_map.place(handle.id, bounds, FocusNeighbors::new());
+        }
+        tab_index_map.end_group();
+        tab_index_map.insert(&sidebar);
+        tab_index_map.place(
+            sidebar.id,
+            Bounds::new(point(px(250.), px(30.)), size(px(90.), px(200.))),
+            FocusNeighbors::new(),
+        );
+
+        let in_direction = |map: &TabStopMap, handle: &FocusHandle, direction| {
+            map.in_direction(Some(&handle.id), direction)
+                .map(|handle| handle.id)
+        };
+        assert_eq!(
+            in_direction(&tab_index_map, &a, Direction::Right),
+            Some(b.id)
+        );
+        assert_eq!(
+            in_direction(&tab_index_map, &a, Direction::Down),
+            Some(c.id)
+        );
+        assert_eq!(in_direction(&tab_index_map, &d, Direction::Up), Some(b.id));
+        assert_eq!(
+            in_direction(&tab_index_map, &d, Direction::Left),
+            Some(c.id)
+        );
+        assert_eq!(in_direction(&tab_index_map, &a, Direction::Up), None);
+        // Focus stays within the grid's tab group.
+        assert_eq!(in_direction(&tab_index_map, &d, Direction::Right), None);
+        // The sidebar is aligned with both rows, but closer to the center of the bottom one.
+        assert_eq!(
+            in_direction(&tab_index_map, &sidebar, Direction::Left),
+            Some(d.id)
+        );
+        // Without focus, the first tab stop is focused like with `next`.
+        assert_eq!(
+            tab_index_map.in_direction(None, Direction::Down),
+            tab_index_map.next(None)
+        );
+
+        // Neighbors override the nearest element, even outside the tab group.
+        tab_index_map.place(
+            d.id,
+            cell(100., 50.),
+            smallvec![(Direction::Right, sidebar.clone())],
+        );
+        assert_eq!(
+            in_direction(&tab_index_map, &d, Direction::Right),
+            Some(sidebar.id)
+        );
+        assert_eq!(in_direction(&tab_index_map, &d, Direction::Up), Some(b.id));
+    }
+
+    #[test]
+    fn test_focus_trap() {
+        let focus_map = Arc::new(FocusMap::default());
+        let mut tab_index_map = TabStopMap::default();
+
+        // A modal painted after the content behind it, containing its own nested trap.
+        let [behind, modal, first, last, nested, after] =
+            std::array::from_fn(|_| FocusHandle::new(&focus_map).tab_stop(true));
+        tab_index_map.insert(&behind);
+        tab_index_map.begin_trap(Some(modal.id));
+        tab_index_map.insert(&first);
+        tab_index_map.insert(&last);
+        tab_index_map.end_group();
+        tab_index_map.insert(&after);
+
+        let next = |map: &TabStopMap, handle: Option<&FocusHandle>| {
+            map.next(handle.map(|handle| &handle.id))
+                .map(|handle| handle.id)
+        };
+        let prev = |map: &TabStopMap, handle: Option<&FocusHandle>| {
+            map.prev(handle.map(|handle| &handle.id))
+                .map(|handle| handle.id)
+        };
+        assert_eq!(tab_index_map.trap_ids(), &[modal.id]);
+        assert_eq!(next(&tab_index_map, Some(&first)), Some(last.id));
+        assert_eq!(next(&tab_index_map, Some(&last)), Some(first.id));
+        assert_eq!(prev(&tab_index_map, Some(&first)), Some(last.id));
+        // Focus outside of the trap is moved into it.
+        assert_eq!(next(&tab_index_map, Some(&behind)), Some(first.id));
+        assert_eq!(prev(&tab_index_map, Some(&after)), Some(last.id));
+        assert_eq!(next(&tab_index_map, None), Some(first.id));
+
+        // Moving in a direction doesn't leave the trap either, even to a neighbor.
+        let cell = |x: f32| Bounds::new(point(px(x), px(0.)), size(px(90.), px(40.)));
+        for (handle, x) in [(&behind, 0.), (&first, 100.), (&last, 200.), (&after, 300.)] {
+            tab_index_map.place(handle.id, cell(x), FocusNeighbors::new());
+        }
+        let in_direction = |map: &TabStopMap, handle: &FocusHandle, direction| {
+            map.in_direction(Some(&handle.id), direction)
+                .map(|handle| handle.id)
+        };
+        assert_eq!(
+            in_direction(&tab_index_map, &first, Direction::Right),
+            Some(last.id)
+        );
+        assert_eq!(in_direction(&tab_index_map, &last, Direction::Right), None);
+        assert_eq!(in_direction(&tab_index_map, &first, Direction::Left), None);
+        assert_eq!(
+            in_direction(&tab_index_map, &behind, Direction::Left),
+            Some(first.id)
+        );
+        tab_index_map.place(
+            last.id,
+            cell(200.),
+            smallvec![(Direction::Right, after.clone())],
+        );
+        assert_eq!(in_direction(&tab_index_map, &last, Direction::Right), None);
+
+        // The innermost trap takes precedence over the one it is nested in.
+        let mut tab_index_map = TabStopMap::default();
+        tab_index_map.begin_trap(Some(modal.id));
+        tab_index_map.insert(&first);
+        tab_index_map.begin_trap(None);
+        tab_index_map.insert(&nested);
+        tab_index_map.end_group();
+        tab_index_map.insert(&last);
+        tab_index_map.end_group();
+        assert_eq!(tab_index_map.trap_ids(), &[modal.id]);
+        assert_eq!(next(&tab_index_map, Some(&first)), Some(nested.id));
+        assert_eq!(next(&tab_index_map, Some(&nested)), Some(nested.id));
+    }
+
     #[test]
     fn test_tab_non_stop_filtering() {
         let focus_map = Arc::new(FocusMap::default());
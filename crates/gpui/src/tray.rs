@@ -1,6 +1,57 @@
-use crate::{App, MenuItem, SharedString};
-use anyhow::Result;
-use std::rc::Rc;
+use crate::{App, Axis, DevicePixels, ImageFormat, MenuItem, MouseButton, Point, SharedString};
+use anyhow::{Context as _, Result};
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering::SeqCst},
+};
+
+/// A unique identifier for a tray icon shown by the application.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TrayId(pub u64);
+
+impl TrayId {
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, SeqCst))
+    }
+}
+
+/// A handle to a tray icon added with [`App::add_tray`].
+///
+/// The tray icon is shown until it is removed with [`TrayHandle::remove`], dropping the handle
+/// leaves it in place.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TrayHandle {
+    pub(crate) id: TrayId,
+}
+
+impl TrayHandle {
+    /// The identifier of the tray icon.
+    pub fn id(&self) -> TrayId {
+        self.id
+    }
+
+    /// Updates the tray icon, rebuilding its icon and menu.
+    ///
+    /// This will fail if the tray icon has been removed.
+    pub fn update(&self, cx: &mut App, update: impl FnOnce(&mut Tray)) -> Result<()> {
+        let tray = cx
+            .trays
+            .get_mut(&self.id)
+            .context("tray icon was removed")?;
+        update(tray);
+        cx.refresh_tray(self.id);
+        Ok(())
+    }
+
+    /// Removes the tray icon.
+    ///
+    /// Tray icons can currently only be removed on Linux. On macOS and Windows this stops
+    /// updates and callbacks, but the icon stays shown until the application exits.
+    pub fn remove(self, cx: &mut App) {
+        cx.remove_tray(self.id);
+    }
+}
 
 /// System tray icon.
 #[derive(Clone)]
@@ -12,6 +63,23 @@ pub struct Tray {
     /// Tray icon image.
     pub icon: Option<Rc<gpui::Image>>,
     pub(crate) icon_data: Option<TrayIconData>,
+    /// Name of an icon from the desktop's icon theme, which adapts to the panel's size and colors.
+    ///
+    /// Supported by StatusNotifierItem hosts on Linux, which fall back to `icon` if the theme
+    /// doesn't have it.
+    pub icon_name: Option<SharedString>,
+    /// Icon shown instead of the regular one while the status is [`TrayStatus::NeedsAttention`].
+    pub attention_icon: Option<Rc<gpui::Image>>,
+    pub(crate) attention_icon_data: Option<TrayIconData>,
+    /// Themed name of the attention icon, see [`Tray::icon_name`].
+    pub attention_icon_name: Option<SharedString>,
+    /// Icon drawn on top of the regular one, e.g. a badge with an unread count.
+    pub overlay_icon: Option<Rc<gpui::Image>>,
+    pub(crate) overlay_icon_data: Option<TrayIconData>,
+    /// Themed name of the overlay icon, see [`Tray::icon_name`].
+    pub overlay_icon_name: Option<SharedString>,
+    /// How prominently the tray icon is shown.
+    pub status: TrayStatus,
 
     /// Whether the icon should be treated as a template image on platforms that support it
     /// (e.g. macOS menu bar).
@@ -22,23 +90,82 @@ pub struct Tray {
 
     /// Function to build the context menu.
     pub menu_builder: Option<Rc<dyn Fn(&mut App) -> Vec<MenuItem>>>,
+    /// Called when the tray icon is clicked.
+    pub on_click: Option<Rc<dyn Fn(&TrayClickEvent, &mut App)>>,
+    /// Called when the tray icon is double-clicked, after the callback for the second click.
+    pub on_double_click: Option<Rc<dyn Fn(&TrayClickEvent, &mut App)>>,
+    /// Called when the mouse wheel is scrolled over the tray icon.
+    pub on_scroll: Option<Rc<dyn Fn(&TrayScrollEvent, &mut App)>>,
     /// Visibility of the tray icon.
     pub visible: bool,
 }
 
+/// How prominently the tray icon is shown, the platform decides how this is presented.
+///
+/// Currently only supported on Linux.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TrayStatus {
+    /// The icon is not relevant right now, and may be hidden by the panel.
+    Passive,
+    /// The default status.
+    #[default]
+    Active,
+    /// The application needs the user's attention, the attention icon is shown if one is set.
+    NeedsAttention {
+        /// Whether the icon blinks until the status changes.
+        blink: bool,
+    },
+}
+
+/// A click on the tray icon.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrayClickEvent {
+    /// The mouse button that was clicked.
+    pub button: MouseButton,
+    /// The position of the click on the screen, if the platform reports it.
+    pub position: Option<Point<DevicePixels>>,
+}
+
+/// A scroll of the mouse wheel over the tray icon.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrayScrollEvent {
+    /// The distance scrolled, as reported by the platform.
+    pub delta: i32,
+    /// The axis along which the wheel was scrolled.
+    pub axis: Axis,
+}
+
+/// An interaction with the tray icon, reported by the platform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrayEvent {
+    /// The tray icon was clicked.
+    Click(TrayClickEvent),
+    /// The tray icon was double-clicked.
+    DoubleClick(TrayClickEvent),
+    /// The mouse wheel was scrolled over the tray icon.
+    Scroll(TrayScrollEvent),
+}
+
 impl Tray {
     pub(crate) fn render_icon(&mut self, cx: &App) -> Result<()> {
-        if let Some(icon) = &self.icon {
+        fn render(icon: &Option<Rc<gpui::Image>>, cx: &App) -> Result<Option<TrayIconData>> {
+            let Some(icon) = icon else {
+                return Ok(None);
+            };
             let image = icon.to_image_data(cx.svg_renderer())?;
             let bytes = image.as_bytes(0).unwrap_or_default();
             let size = image.size(0);
 
-            self.icon_data = Some(TrayIconData {
+            Ok(Some(TrayIconData {
                 data: Rc::new(bytes.to_vec()),
                 width: size.width.0 as u32,
                 height: size.height.0 as u32,
-            })
+            }))
         }
+
+        self.icon_data = render(&self.icon, cx)?;
+        self.attention_icon_data = render(&self.attention_icon, cx)?;
+        self.overlay_icon_data = render(&self.overlay_icon, cx)?;
         Ok(())
     }
 }
@@ -59,8 +186,19 @@ impl Tray {
             title: None,
             icon: None,
             icon_data: None,
+            icon_name: None,
+            attention_icon: None,
+            attention_icon_data: None,
+            attention_icon_name: None,
+            overlay_icon: None,
+            overlay_icon_data: None,
+            overlay_icon_name: None,
+            status: TrayStatus::default(),
             icon_is_template: false,
             menu_builder: None,
+            on_click: None,
+            on_double_click: None,
+            on_scroll: None,
             visible: true,
         }
     }
@@ -83,6 +221,48 @@ impl Tray {
         self
     }
 
+    /// Set the name of the themed icon, defaults to None.
+    pub fn icon_name(mut self, name: impl Into<SharedString>) -> Self {
+        self.icon_name = Some(name.into());
+        self
+    }
+
+    /// Set the icon shown while the tray icon needs attention, defaults to None.
+    pub fn attention_icon(mut self, icon: impl Into<gpui::Image>) -> Self {
+        self.attention_icon = Some(Rc::new(icon.into()));
+        self
+    }
+
+    /// Set the name of the themed icon shown while the tray icon needs attention, defaults to None.
+    pub fn attention_icon_name(mut self, name: impl Into<SharedString>) -> Self {
+        self.attention_icon_name = Some(name.into());
+        self
+    }
+
+    /// Set the icon drawn on top of the tray icon, defaults to None.
+    pub fn overlay_icon(mut self, icon: impl Into<gpui::Image>) -> Self {
+        self.overlay_icon = Some(Rc::new(icon.into()));
+        self
+    }
+
+    /// Set the name of the themed icon drawn on top of the tray icon, defaults to None.
+    pub fn overlay_icon_name(mut self, name: impl Into<SharedString>) -> Self {
+        self.overlay_icon_name = Some(name.into());
+        self
+    }
+
+    /// Set the overlay icon to a badge showing the given count, or remove it if the count is 0.
+    pub fn badge(mut self, count: usize) -> Self {
+        self.overlay_icon = (count > 0).then(|| Rc::new(badge_image(count)));
+        self
+    }
+
+    /// Set the status, defaults to [`TrayStatus::Active`].
+    pub fn status(mut self, status: TrayStatus) -> Self {
+        self.status = status;
+        self
+    }
+
     /// On supported platforms (e.g. macOS), mark the tray icon image as a template.
     ///
     /// When set to `true`, the system may automatically adjust the icon for light/dark mode.
@@ -101,9 +281,172 @@ impl Tray {
         self
     }
 
+    /// Set the callback invoked when the tray icon is clicked.
+    pub fn on_click(mut self, callback: impl Fn(&TrayClickEvent, &mut App) + 'static) -> Self {
+        self.on_click = Some(Rc::new(callback));
+        self
+    }
+
+    /// Set the callback invoked when the tray icon is double-clicked.
+    pub fn on_double_click(
+        mut self,
+        callback: impl Fn(&TrayClickEvent, &mut App) + 'static,
+    ) -> Self {
+        self.on_double_click = Some(Rc::new(callback));
+        self
+    }
+
+    /// Set the callback invoked when the mouse wheel is scrolled over the tray icon.
+    pub fn on_scroll(mut self, callback: impl Fn(&TrayScrollEvent, &mut App) + 'static) -> Self {
+        self.on_scroll = Some(Rc::new(callback));
+        self
+    }
+
     /// Set visibility of the tray icon, default is true.
     pub fn visible(mut self, visible: bool) -> Self {
         self.visible = visible;
         self
     }
 }
+
+/// Renders a red badge with the count, counts above 99 are shown as "99+".
+fn badge_image(count: usize) -> gpui::Image {
+    let text = if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    };
+    let font_size = match text.len() {
+        1 => 20,
+        2 => 16,
+        _ => 12,
+    };
+    let svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="32" height="32" viewBox="0 0 32 32">
+<circle cx="16" cy="16" r="16" fill="#e01b24"/>
+<text x="16" y="16" dy="0.35em" font-family="sans-serif" font-size="{font_size}" font-weight="bold" fill="#ffffff" text-anchor="middle">{text}</text>
+</svg>"##
+    );
+    gpui::Image::from_bytes(ImageFormat::Svg, svg.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        App, Axis, MouseButton, TestAppContext, Tray, TrayClickEvent, TrayEvent, TrayScrollEvent,
+    };
+
+    #[gpui::test]
+    fn test_tray_events(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+
+        cx.update(|cx| {
+            cx.set_tray(
+                Tray::new()
+                    .on_click({
+                        let events = events.clone();
+                        move |event, _| events.borrow_mut().push(TrayEvent::Click(event.clone()))
+                    })
+                    .on_double_click({
+                        let events = events.clone();
+                        move |event, _| {
+                            events
+                                .borrow_mut()
+                                .push(TrayEvent::DoubleClick(event.clone()))
+                        }
+                    })
+                    .on_scroll({
+                        let events = events.clone();
+                        move |event, _| events.borrow_mut().push(TrayEvent::Scroll(event.clone()))
+                    }),
+            )
+        });
+
+        let tray = cx.trays()[0].0;
+        let click = TrayClickEvent {
+            button: MouseButton::Left,
+            position: None,
+        };
+        let scroll = TrayScrollEvent {
+            delta: -120,
+            axis: Axis::Vertical,
+        };
+        cx.simulate_tray_event(tray, TrayEvent::Click(click.clone()));
+        cx.simulate_tray_event(tray, TrayEvent::DoubleClick(click.clone()));
+        cx.simulate_tray_event(tray, TrayEvent::Scroll(scroll.clone()));
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                TrayEvent::Click(click.clone()),
+                TrayEvent::DoubleClick(click),
+                TrayEvent::Scroll(scroll)
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_multiple_trays(cx: &mut TestAppContext) {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let on_click = |name: &'static str| {
+            let clicks = clicks.clone();
+            move |_: &TrayClickEvent, _: &mut App| clicks.borrow_mut().push(name)
+        };
+
+        let (first, second) = cx.update(|cx| {
+            (
+                cx.add_tray(Tray::new().title("First").on_click(on_click("first"))),
+                cx.add_tray(Tray::new().title("Second").on_click(on_click("second"))),
+            )
+        });
+        let titles = |cx: &TestAppContext| {
+            cx.trays()
+                .into_iter()
+                .map(|(id, tray)| (id, tray.title))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            titles(cx),
+            [
+                (first.id(), Some("First".into())),
+                (second.id(), Some("Second".into()))
+            ]
+        );
+
+        let click = TrayEvent::Click(TrayClickEvent {
+            button: MouseButton::Left,
+            position: None,
+        });
+        cx.simulate_tray_event(second.id(), click.clone());
+        cx.simulate_tray_event(first.id(), click);
+        assert_eq!(*clicks.borrow(), ["second", "first"]);
+
+        cx.update(|cx| first.update(cx, |tray| tray.title = Some("Updated".into())))
+            .unwrap();
+        assert_eq!(
+            titles(cx),
+            [
+                (first.id(), Some("Updated".into())),
+                (second.id(), Some("Second".into()))
+            ]
+        );
+
+        cx.update(|cx| second.clone().remove(cx));
+        assert_eq!(titles(cx), [(first.id(), Some("Updated".into()))]);
+        assert!(cx.update(|cx| second.update(cx, |_| {})).is_err());
+    }
+
+    #[gpui::test]
+    fn test_tray_badge(cx: &mut TestAppContext) {
+        let mut tray = Tray::new().badge(120);
+        cx.update(|cx| tray.render_icon(cx)).unwrap();
+        let badge = tray.overlay_icon_data.as_ref().unwrap();
+        assert_eq!(badge.width, badge.height);
+        assert_eq!(badge.data.len(), (badge.width * badge.height * 4) as usize);
+
+        let tray = tray.badge(0);
+        assert!(tray.overlay_icon.is_none());
+    }
+}
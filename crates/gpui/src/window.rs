@@ -3,21 +3,22 @@ use crate::Inspector;
 use crate::{
     Action, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App, AppContext, Arena, Asset,
     AsyncWindowContext, AvailableSpace, Background, BorderStyle, Bounds, BoxShadow, Capslock,
-    Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
-    DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
-    FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
-    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformWindow, Point, PolychromeSprite, Priority, PromptButton,
-    PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams,
-    Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y,
-    ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle, Style, SubscriberSet,
-    Subscription, SystemWindowTab, SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task,
-    TextStyle, TextStyleRefinement, TransformationMatrix, Underline, UnderlineStyle,
-    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
-    WindowOptions, WindowParams, WindowTextSystem, point, prelude::*, px, rems, size,
-    transparent_black,
+    Context, Corners, CursorStyle, DISPATCH_TRACE_LOG_TARGET, Decorations, DevicePixels, Direction,
+    DispatchActionListener, DispatchListenerKind, DispatchNodeId, DispatchTrace,
+    DispatchTraceListener, DispatchTree, DisplayId, DragResult, Edges, Effect, Entity, EntityId,
+    EventEmitter, ExternalDrop, FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs,
+    Hsla, InputHandler, IsZero, KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke,
+    KeystrokeEvent, LayoutId, LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite,
+    MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent, NativeDrag, Path, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PointerConstraint,
+    PolychromeSprite, Priority, PromptButton, PromptLevel, Quad, Render, RenderGlyphParams,
+    RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR,
+    SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow, SharedString, Size,
+    StrikethroughStyle, Style, SubscriberSet, Subscription, SystemWindowTab,
+    SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement,
+    TransformationMatrix, Underline, UnderlineStyle, WindowAppearance, WindowBackgroundAppearance,
+    WindowBounds, WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
+    point, prelude::*, px, rems, size, transparent_black,
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::{FxHashMap, FxHashSet};
@@ -55,9 +56,11 @@ use util::{ResultExt, measure};
 use uuid::Uuid;
 
 mod prompts;
+mod restore;
 
 use crate::util::atomic_incr_if_not_zero;
 pub use prompts::*;
+pub(crate) use restore::*;
 
 pub(crate) const DEFAULT_WINDOW_SIZE: Size<Pixels> = size(px(1536.), px(864.));
 
@@ -378,7 +381,7 @@ impl FocusHandle {
             .dispatch_tree
             .focusable_node_id(self.id)
         {
-            window.dispatch_action_on_node(node_id, action, cx)
+            window.dispatch_action_on_node(node_id, action, None, cx)
         }
     }
 }
@@ -829,6 +832,13 @@ enum InputModality {
     Keyboard,
 }
 
+/// Keeps the screen awake and the screensaver from starting while it's held, see
+/// [`Window::inhibit_idle`]. Dropping it releases the inhibition.
+#[must_use = "the inhibition is released when the guard is dropped"]
+pub struct IdleInhibitGuard {
+    _release: Subscription,
+}
+
 /// Holds the state for a specific window.
 pub struct Window {
     pub(crate) handle: AnyWindowHandle,
@@ -836,6 +846,7 @@ pub struct Window {
     pub(crate) removed: bool,
     pub(crate) platform_window: Box<dyn PlatformWindow>,
     display_id: Option<DisplayId>,
+    restore_key: Option<SharedString>,
     sprite_atlas: Arc<dyn PlatformAtlas>,
     text_system: Arc<WindowTextSystem>,
     rem_size: Pixels,
@@ -878,12 +889,18 @@ pub struct Window {
     pub(crate) needs_present: Rc<Cell<bool>>,
     pub(crate) last_input_timestamp: Rc<Cell<Instant>>,
     last_input_modality: InputModality,
+    pointer_constraint: Option<PointerConstraint>,
     pub(crate) refreshing: bool,
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) focus: Option<FocusId>,
     focus_enabled: bool,
+    /// The painted focus traps, in the order they appeared, and what was focused when each did.
+    focus_traps: Vec<(FocusId, Option<WeakFocusHandle>)>,
     pending_input: Option<PendingInput>,
     pending_modifier: ModifierState,
+    dispatch_tracing: bool,
+    dispatch_traces: Vec<DispatchTrace>,
+    dispatch_trace: Option<DispatchTrace>,
     pub(crate) pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
     pub(crate) client_inset: Option<Pixels>,
@@ -908,11 +925,26 @@ pub(crate) enum DrawPhase {
 #[derive(Default, Debug)]
 struct PendingInput {
     keystrokes: SmallVec<[Keystroke; 1]>,
+    bindings: Vec<KeyBinding>,
     focus: Option<FocusId>,
     timer: Option<Task<()>>,
+    timeout: Option<Duration>,
     needs_timeout: bool,
 }
 
+/// A multi-stroke key binding in progress, see [`Window::pending_input`].
+#[derive(Clone, Debug)]
+pub struct PendingKeystrokes {
+    /// The keystrokes typed so far.
+    pub keystrokes: Vec<Keystroke>,
+    /// The bindings that could still complete the keystrokes, in precedence order.
+    pub bindings: Vec<KeyBinding>,
+    /// How long the keystrokes wait for the rest of a binding before they're dispatched on their
+    /// own. This is `None` if they neither match a binding nor insert text on their own, in which
+    /// case they're pending until the next keystroke.
+    pub timeout: Option<Duration>,
+}
+
 pub(crate) struct ElementStateBox {
     pub(crate) inner: Box<dyn Any>,
     #[cfg(debug_assertions)]
@@ -1006,8 +1038,18 @@ impl Window {
             window_decorations,
             #[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
             tabbing_identifier,
+            restore_key,
         } = options;
 
+        let saved_state = restore_key
+            .as_ref()
+            .and_then(|key| cx.window_state_store.get(key));
+        let restored =
+            saved_state.and_then(|state| state.restore(&cx.displays(), cx.primary_display()));
+        let (window_bounds, display_id) = match restored {
+            Some((window_bounds, display_id)) => (Some(window_bounds), Some(display_id)),
+            None => (window_bounds, display_id),
+        };
         let window_bounds = window_bounds.unwrap_or_else(|| default_bounds(display_id, cx));
         let mut platform_window = cx.platform.open_window(
             handle,
@@ -1026,6 +1068,16 @@ impl Window {
                 tabbing_identifier,
             },
         )?;
+        if let Some(state) = saved_state
+            && restored.is_some()
+            && let Some(size) = state.rescaled_size(
+                window_bounds,
+                platform_window.scale_factor(),
+                platform_window.display().as_deref(),
+            )
+        {
+            platform_window.resize(size);
+        }
 
         let tab_bar_visible = platform_window.tab_bar_visible();
         SystemWindowTabController::init_visible(cx, tab_bar_visible);
@@ -1264,6 +1316,7 @@ impl Window {
             removed: false,
             platform_window,
             display_id,
+            restore_key,
             sprite_atlas,
             text_system,
             rem_size: px(16.),
@@ -1301,12 +1354,17 @@ impl Window {
             needs_present,
             last_input_timestamp,
             last_input_modality: InputModality::Mouse,
+            pointer_constraint: None,
             refreshing: false,
             activation_observers: SubscriberSet::new(),
             focus: None,
             focus_enabled: true,
+            focus_traps: Vec::new(),
             pending_input: None,
             pending_modifier: ModifierState::default(),
+            dispatch_tracing: false,
+            dispatch_traces: Vec::new(),
+            dispatch_trace: None,
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
             client_inset: None,
@@ -1484,6 +1542,24 @@ impl Window {
         }
     }
 
+    /// Move focus to the nearest tab stop in the given direction, based on where the tab stops
+    /// were painted, without leaving the innermost tab group containing the focused element.
+    /// Elements can name the neighbor to move to instead with
+    /// [`InteractiveElement::focus_neighbor`].
+    pub fn focus_in_direction(&mut self, direction: Direction) {
+        if !self.focus_enabled {
+            return;
+        }
+
+        if let Some(handle) = self
+            .rendered_frame
+            .tab_stops
+            .in_direction(self.focus.as_ref(), direction)
+        {
+            self.focus(&handle)
+        }
+    }
+
     /// Accessor for the text system.
     pub fn text_system(&self) -> &Arc<WindowTextSystem> {
         &self.text_system
@@ -1535,7 +1611,7 @@ impl Window {
             window
                 .update(cx, |_, window, cx| {
                     let node_id = window.focus_node_id_in_rendered_frame(focus_id);
-                    window.dispatch_action_on_node(node_id, action.as_ref(), cx);
+                    window.dispatch_action_on_node(node_id, action.as_ref(), None, cx);
                 })
                 .log_err();
         })
@@ -1752,6 +1828,12 @@ impl Window {
         self.viewport_size = self.platform_window.content_size();
         self.display_id = self.platform_window.display().map(|display| display.id());
 
+        if let Some(key) = self.restore_key.clone() {
+            let state = SavedWindowState::of(self.platform_window.as_ref());
+            let executor = cx.background_executor.clone();
+            cx.window_state_store.save(key, state, &executor);
+        }
+
         self.refresh();
 
         self.bounds_observers
@@ -1861,6 +1943,15 @@ impl Window {
         self.platform_window.set_app_id(app_id);
     }
 
+    /// Keep the screen from dimming, locking or starting the screensaver until the returned guard
+    /// is dropped, e.g. while playing a video or a presentation. The reason may be shown to the
+    /// user by the system. Some platforms only inhibit idling while the window is visible.
+    pub fn inhibit_idle(&self, reason: impl Into<SharedString>) -> IdleInhibitGuard {
+        IdleInhibitGuard {
+            _release: self.platform_window.inhibit_idle(&reason.into()),
+        }
+    }
+
     /// Sets the window background appearance.
     pub fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
         self.platform_window
@@ -1974,6 +2065,40 @@ impl Window {
         self.mouse_position
     }
 
+    /// Lock the pointer in place until [`Window::release_pointer`] is called, e.g. to orbit a
+    /// viewport or scrub a value for as long as the mouse is dragged. While the pointer is locked
+    /// or confined, the window receives a [`RawMouseMotionEvent`](crate::RawMouseMotionEvent)
+    /// for every motion of the mouse. The cursor stays visible, use [`CursorStyle::None`] to
+    /// hide it.
+    pub fn lock_pointer(&mut self) {
+        self.set_pointer_constraint(Some(PointerConstraint::Locked));
+    }
+
+    /// Confine the pointer to the given bounds of the window until [`Window::release_pointer`]
+    /// is called.
+    pub fn confine_pointer(&mut self, bounds: Bounds<Pixels>) {
+        self.set_pointer_constraint(Some(PointerConstraint::Confined(bounds)));
+    }
+
+    /// Release the pointer locked or confined by [`Window::lock_pointer`] or
+    /// [`Window::confine_pointer`].
+    pub fn release_pointer(&mut self) {
+        self.set_pointer_constraint(None);
+    }
+
+    /// The constraint requested for the pointer, if any. The platform may lift it while the
+    /// window isn't focused.
+    pub fn pointer_constraint(&self) -> Option<PointerConstraint> {
+        self.pointer_constraint
+    }
+
+    fn set_pointer_constraint(&mut self, constraint: Option<PointerConstraint>) {
+        if self.pointer_constraint != constraint {
+            self.pointer_constraint = constraint;
+            self.platform_window.set_pointer_constraint(constraint);
+        }
+    }
+
     /// The current state of the keyboard's modifiers
     pub fn modifiers(&self) -> Modifiers {
         self.modifiers
@@ -2025,10 +2150,12 @@ impl Window {
         self.next_frame.finish(&mut self.rendered_frame);
 
         self.invalidator.set_phase(DrawPhase::Focus);
+        let previous_focus = self.rendered_frame.focus;
         let previous_focus_path = self.rendered_frame.focus_path();
         let previous_window_active = self.rendered_frame.window_active;
         mem::swap(&mut self.rendered_frame, &mut self.next_frame);
         self.next_frame.clear();
+        self.update_focus_traps(previous_focus, cx);
         let current_focus_path = self.rendered_frame.focus_path();
         let current_window_active = self.rendered_frame.window_active;
 
@@ -2068,6 +2195,45 @@ impl Window {
         ArenaClearNeeded
     }
 
+    /// Restores focus when the focus traps it was moved into are no longer painted, and remembers
+    /// what to restore it to for those that were painted for the first time.
+    fn update_focus_traps(&mut self, previous_focus: Option<FocusId>, cx: &App) {
+        let trap_ids = self.rendered_frame.tab_stops.trap_ids();
+        if let Some(dismissed_ix) = self
+            .focus_traps
+            .iter()
+            .position(|(trap_id, _)| !trap_ids.contains(trap_id))
+        {
+            // When several traps are dismissed at once, focus returns to where it was before the
+            // first of them appeared.
+            let (_, restored) = self.focus_traps.remove(dismissed_ix);
+            self.focus_traps
+                .retain(|(trap_id, _)| trap_ids.contains(trap_id));
+            if self.focus_enabled
+                && self.rendered_frame.focus.is_none()
+                && let Some(restored) = restored.and_then(|restored| restored.upgrade())
+                && self
+                    .rendered_frame
+                    .dispatch_tree
+                    .focusable_node_id(restored.id)
+                    .is_some()
+            {
+                self.focus = Some(restored.id);
+                self.rendered_frame.focus = Some(restored.id);
+                self.clear_pending_keystrokes();
+            }
+        }
+
+        for trap_id in self.rendered_frame.tab_stops.trap_ids() {
+            if !self.focus_traps.iter().any(|(id, _)| id == trap_id) {
+                let restored = previous_focus
+                    .and_then(|id| FocusHandle::for_id(id, &cx.focus_handles))
+                    .map(|handle| handle.downgrade());
+                self.focus_traps.push((*trap_id, restored));
+            }
+        }
+    }
+
     fn record_entities_accessed(&mut self, cx: &mut App) {
         let mut entities_ref = cx.entities.accessed_entities.borrow_mut();
         let mut entities = mem::take(entities_ref.deref_mut());
@@ -3709,6 +3875,10 @@ impl Window {
                 self.modifiers = mouse_exited.modifiers;
                 PlatformInput::MouseExited(mouse_exited)
             }
+            PlatformInput::RawMouseMotion(raw_mouse_motion) => {
+                self.modifiers = raw_mouse_motion.modifiers;
+                PlatformInput::RawMouseMotion(raw_mouse_motion)
+            }
             PlatformInput::ModifiersChanged(modifiers_changed) => {
                 self.modifiers = modifiers_changed.modifiers;
                 self.capslock = modifiers_changed.capslock;
@@ -3719,15 +3889,24 @@ impl Window {
                 self.modifiers = scroll_wheel.modifiers;
                 PlatformInput::ScrollWheel(scroll_wheel)
             }
-            // Translate dragging and dropping of external files from the operating system
+            // Translate dragging and dropping of external data from the operating system
             // to internal drag and drop events.
             PlatformInput::FileDrop(file_drop) => match file_drop {
-                FileDropEvent::Entered { position, paths } => {
+                FileDropEvent::Entered {
+                    position,
+                    paths,
+                    data,
+                } => {
                     self.mouse_position = position;
-                    if cx.active_drag.is_none() {
+                    let data = ExternalDrop { paths, ..data };
+                    if cx
+                        .active_drag
+                        .as_ref()
+                        .is_none_or(|drag| drag.value.is::<ExternalDrop>())
+                    {
                         cx.active_drag = Some(AnyDrag {
-                            value: Arc::new(paths.clone()),
-                            view: cx.new(|_| paths).into(),
+                            value: Arc::new(data.clone()),
+                            view: cx.new(|_| data).into(),
                             cursor_offset: position,
                             cursor_style: None,
                         });
@@ -3768,6 +3947,7 @@ impl Window {
             self.dispatch_mouse_event(any_mouse_event, cx);
         } else if let Some(any_key_event) = event.keyboard_event() {
             self.dispatch_key_event(any_key_event, cx);
+            self.end_dispatch_trace();
         }
 
         DispatchEventResult {
@@ -3836,6 +4016,7 @@ impl Window {
 
         let node_id = self.focus_node_id_in_rendered_frame(self.focus);
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
+        self.begin_dispatch_trace(&dispatch_path, cx);
 
         let mut keystroke: Option<Keystroke> = None;
 
@@ -3889,12 +4070,22 @@ impl Window {
             currently_pending = PendingInput::default();
         }
 
+        if let Some(trace) = &mut self.dispatch_trace {
+            trace.input = currently_pending.keystrokes.to_vec();
+            trace.input.push(keystroke.clone());
+        }
+
         let match_result = self.rendered_frame.dispatch_tree.dispatch_key(
             currently_pending.keystrokes,
             keystroke,
             &dispatch_path,
         );
 
+        if let Some(trace) = &mut self.dispatch_trace {
+            trace.bindings = match_result.bindings.to_vec();
+            trace.pending = !match_result.pending.is_empty();
+        }
+
         if !match_result.to_replay.is_empty() {
             self.replay_pending_input(match_result.to_replay, cx);
             cx.propagate_event = true;
@@ -3902,6 +4093,10 @@ impl Window {
 
         if !match_result.pending.is_empty() {
             currently_pending.timer.take();
+            currently_pending.bindings = cx
+                .keymap
+                .borrow()
+                .pending_bindings_for_input(&match_result.pending, &match_result.context_stack);
             currently_pending.keystrokes = match_result.pending;
             currently_pending.focus = self.focus;
 
@@ -3919,8 +4114,16 @@ impl Window {
                 match_result.pending_has_binding || text_input_requires_timeout;
 
             if currently_pending.needs_timeout {
+                // Wait as long as the most patient of the bindings that could complete the input
+                let timeout = currently_pending
+                    .bindings
+                    .iter()
+                    .map(|binding| binding.pending_timeout.unwrap_or(cx.pending_input_timeout))
+                    .max()
+                    .unwrap_or(cx.pending_input_timeout);
+                currently_pending.timeout = Some(timeout);
                 currently_pending.timer = Some(self.spawn(cx, async move |cx| {
-                    cx.background_executor.timer(Duration::from_secs(1)).await;
+                    cx.background_executor.timer(timeout).await;
                     cx.update(move |window, cx| {
                         let Some(currently_pending) = window
                             .pending_input
@@ -3946,6 +4149,7 @@ impl Window {
                 }));
             } else {
                 currently_pending.timer = None;
+                currently_pending.timeout = None;
             }
             self.pending_input = Some(currently_pending);
             self.pending_input_changed(cx);
@@ -3971,7 +4175,12 @@ impl Window {
 
         if !skip_bindings {
             for binding in match_result.bindings {
-                self.dispatch_action_on_node(node_id, binding.action.as_ref(), cx);
+                self.dispatch_action_on_node(
+                    node_id,
+                    binding.action.as_ref(),
+                    binding.action_input.as_ref(),
+                    cx,
+                );
                 if !cx.propagate_event {
                     self.dispatch_keystroke_observers(
                         event,
@@ -4027,6 +4236,12 @@ impl Window {
 
             for key_listener in node.key_listeners.clone() {
                 key_listener(event, DispatchPhase::Capture, self, cx);
+                self.trace_listener(
+                    DispatchListenerKind::Key,
+                    DispatchPhase::Capture,
+                    Some(*node_id),
+                    cx,
+                );
                 if !cx.propagate_event {
                     return;
                 }
@@ -4039,6 +4254,12 @@ impl Window {
             let node = self.rendered_frame.dispatch_tree.node(*node_id);
             for key_listener in node.key_listeners.clone() {
                 key_listener(event, DispatchPhase::Bubble, self, cx);
+                self.trace_listener(
+                    DispatchListenerKind::Key,
+                    DispatchPhase::Bubble,
+                    Some(*node_id),
+                    cx,
+                );
                 if !cx.propagate_event {
                     return;
                 }
@@ -4059,6 +4280,12 @@ impl Window {
             let node = self.rendered_frame.dispatch_tree.node(*node_id);
             for listener in node.modifiers_changed_listeners.clone() {
                 listener(event, self, cx);
+                self.trace_listener(
+                    DispatchListenerKind::ModifiersChanged,
+                    DispatchPhase::Bubble,
+                    Some(*node_id),
+                    cx,
+                );
                 if !cx.propagate_event {
                     return;
                 }
@@ -4066,6 +4293,69 @@ impl Window {
         }
     }
 
+    /// Record how keystrokes and actions are dispatched in this window, to be inspected with
+    /// [`Self::take_dispatch_traces`]. Traces are also logged to the [`DISPATCH_TRACE_LOG_TARGET`]
+    /// whenever it's enabled at the trace level, regardless of this setting.
+    pub fn set_dispatch_tracing(&mut self, enabled: bool) {
+        self.dispatch_tracing = enabled;
+        if !enabled {
+            self.dispatch_traces.clear();
+        }
+    }
+
+    /// Returns the dispatch traces recorded since they were last taken, oldest first.
+    pub fn take_dispatch_traces(&mut self) -> Vec<DispatchTrace> {
+        mem::take(&mut self.dispatch_traces)
+    }
+
+    /// Starts a trace of a dispatch along the given path, unless one is in progress already or
+    /// tracing is disabled. Returns whether a trace was started.
+    fn begin_dispatch_trace(&mut self, dispatch_path: &[DispatchNodeId], cx: &mut App) -> bool {
+        if self.dispatch_trace.is_some()
+            || !(self.dispatch_tracing
+                || log::log_enabled!(target: DISPATCH_TRACE_LOG_TARGET, log::Level::Trace))
+        {
+            return false;
+        }
+
+        cx.propagation_stopped_at = None;
+        self.dispatch_trace = Some(DispatchTrace {
+            path: self.rendered_frame.dispatch_tree.trace_path(dispatch_path),
+            ..Default::default()
+        });
+        true
+    }
+
+    fn end_dispatch_trace(&mut self) {
+        let Some(trace) = self.dispatch_trace.take() else {
+            return;
+        };
+        log::trace!(target: DISPATCH_TRACE_LOG_TARGET, "{trace:?}");
+        if self.dispatch_tracing {
+            self.dispatch_traces.push(trace);
+        }
+    }
+
+    fn trace_listener(
+        &mut self,
+        kind: DispatchListenerKind,
+        phase: DispatchPhase,
+        node_id: Option<DispatchNodeId>,
+        cx: &mut App,
+    ) {
+        let stopped_at = cx.propagation_stopped_at.take();
+        if let Some(trace) = &mut self.dispatch_trace {
+            let stopped_propagation = !cx.propagate_event;
+            trace.listeners.push(DispatchTraceListener {
+                kind,
+                phase,
+                node: node_id.map(DispatchNodeId::index),
+                stopped_propagation,
+                stopped_at: stopped_at.filter(|_| stopped_propagation),
+            });
+        }
+    }
+
     /// Determine whether a potential multi-stroke key binding is in progress on this window.
     pub fn has_pending_keystrokes(&self) -> bool {
         self.pending_input.is_some()
@@ -4082,6 +4372,19 @@ impl Window {
             .map(|pending_input| pending_input.keystrokes.as_slice())
     }
 
+    /// Returns the multi-stroke key binding in progress, with the bindings that could still
+    /// complete it, e.g. to show them in a popup while the user decides on the next keystroke.
+    /// Use [`Context::observe_pending_input`] to be notified when it changes.
+    pub fn pending_input(&self) -> Option<PendingKeystrokes> {
+        self.pending_input
+            .as_ref()
+            .map(|pending_input| PendingKeystrokes {
+                keystrokes: pending_input.keystrokes.to_vec(),
+                bindings: pending_input.bindings.clone(),
+                timeout: pending_input.timeout,
+            })
+    }
+
     fn replay_pending_input(&mut self, replays: SmallVec<[Replay; 1]>, cx: &mut App) {
         let node_id = self.focus_node_id_in_rendered_frame(self.focus);
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
@@ -4095,7 +4398,12 @@ impl Window {
 
             cx.propagate_event = true;
             for binding in replay.bindings {
-                self.dispatch_action_on_node(node_id, binding.action.as_ref(), cx);
+                self.dispatch_action_on_node(
+                    node_id,
+                    binding.action.as_ref(),
+                    binding.action_input.as_ref(),
+                    cx,
+                );
                 if !cx.propagate_event {
                     self.dispatch_keystroke_observers(
                         &event,
@@ -4130,13 +4438,46 @@ impl Window {
             .unwrap_or_else(|| self.rendered_frame.dispatch_tree.root_node_id())
     }
 
+    /// Dispatches the action on the focused element right away, rather than once effects are
+    /// flushed like [`Self::dispatch_action`].
+    pub(crate) fn dispatch_action_on_focus(&mut self, action: &dyn Action, cx: &mut App) {
+        // Earlier actions may have changed what's focused or rendered.
+        if self.invalidator.is_dirty() {
+            self.draw(cx).clear();
+        }
+        let node_id = self.focus_node_id_in_rendered_frame(self.focus);
+        self.dispatch_action_on_node(node_id, action, None, cx);
+    }
+
     fn dispatch_action_on_node(
         &mut self,
         node_id: DispatchNodeId,
         action: &dyn Action,
+        action_input: Option<&SharedString>,
         cx: &mut App,
     ) {
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
+        if cx.is_recording_actions() {
+            let context_stack = self
+                .rendered_frame
+                .dispatch_tree
+                .context_stack_for_path(&dispatch_path);
+            cx.record_action(action, action_input, context_stack);
+        }
+        let traced = self.begin_dispatch_trace(&dispatch_path, cx);
+        self.dispatch_action_on_path(&dispatch_path, action, cx);
+        if traced {
+            self.end_dispatch_trace();
+        }
+    }
+
+    fn dispatch_action_on_path(
+        &mut self,
+        dispatch_path: &[DispatchNodeId],
+        action: &dyn Action,
+        cx: &mut App,
+    ) {
+        let kind = DispatchListenerKind::Action(action.name());
 
         // Capture phase for global actions.
         cx.propagate_event = true;
@@ -4146,6 +4487,7 @@ impl Window {
         {
             for listener in &global_listeners {
                 listener(action.as_any(), DispatchPhase::Capture, cx);
+                self.trace_listener(kind, DispatchPhase::Capture, None, cx);
                 if !cx.propagate_event {
                     break;
                 }
@@ -4166,7 +4508,7 @@ impl Window {
         }
 
         // Capture phase for window actions.
-        for node_id in &dispatch_path {
+        for node_id in dispatch_path {
             let node = self.rendered_frame.dispatch_tree.node(*node_id);
             for DispatchActionListener {
                 action_type,
@@ -4176,6 +4518,7 @@ impl Window {
                 let any_action = action.as_any();
                 if action_type == any_action.type_id() {
                     listener(any_action, DispatchPhase::Capture, self, cx);
+                    self.trace_listener(kind, DispatchPhase::Capture, Some(*node_id), cx);
 
                     if !cx.propagate_event {
                         return;
@@ -4196,6 +4539,7 @@ impl Window {
                 if action_type == any_action.type_id() {
                     cx.propagate_event = false; // Actions stop propagation by default during the bubble phase
                     listener(any_action, DispatchPhase::Bubble, self, cx);
+                    self.trace_listener(kind, DispatchPhase::Bubble, Some(*node_id), cx);
 
                     if !cx.propagate_event {
                         return;
@@ -4213,6 +4557,7 @@ impl Window {
                 cx.propagate_event = false; // Actions stop propagation by default during the bubble phase
 
                 listener(action.as_any(), DispatchPhase::Bubble, cx);
+                self.trace_listener(kind, DispatchPhase::Bubble, None, cx);
                 if !cx.propagate_event {
                     break;
                 }
@@ -4276,6 +4621,30 @@ impl Window {
         });
     }
 
+    /// Start a drag of data out of the window, to be dropped into another application. This must
+    /// be called while the left mouse button is held, use
+    /// [`crate::StatefulInteractiveElement::on_native_drag`] to start it from a drag gesture.
+    /// The [`NativeDrag::on_finish`] callback receives the result, the drag is cancelled right
+    /// away on platforms without support for native drags.
+    pub fn start_native_drag(&mut self, drag: NativeDrag, cx: &mut App) {
+        let NativeDrag {
+            item,
+            actions,
+            on_finish,
+        } = drag;
+        let receiver = self.platform_window.start_drag(item, &actions);
+        self.spawn(cx, async move |cx| {
+            let result = match receiver {
+                Some(receiver) => receiver.await.unwrap_or(DragResult::Cancelled),
+                None => DragResult::Cancelled,
+            };
+            if let Some(on_finish) = on_finish {
+                cx.update(|window, cx| on_finish(result, window, cx)).ok();
+            }
+        })
+        .detach();
+    }
+
     /// Present a platform dialog.
     /// The provided message will be presented, along with buttons for each answer.
     /// When a button is clicked, the returned Receiver will receive the index of the clicked button.
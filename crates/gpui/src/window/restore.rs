@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+use uuid::Uuid;
+
+use crate::{
+    BackgroundExecutor, Bounds, DisplayId, Pixels, PlatformDisplay, PlatformWindow, SharedString,
+    Size, Task, WindowBounds, point,
+};
+
+// Coalesces the bounds changes of a window being dragged or resized into a single write
+const WRITE_DELAY: Duration = Duration::from_millis(500);
+
+/// The geometry of a window opened with a [`crate::WindowOptions::restore_key`], as persisted
+/// across launches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SavedWindowState {
+    pub bounds: WindowBounds,
+    /// The [`PlatformDisplay::uuid`] of the display the window was on.
+    pub display: Option<Uuid>,
+    pub scale_factor: f32,
+}
+
+impl SavedWindowState {
+    pub fn of(platform_window: &dyn PlatformWindow) -> Self {
+        Self {
+            bounds: platform_window.window_bounds(),
+            display: platform_window
+                .display()
+                .and_then(|display| display.uuid().ok()),
+            scale_factor: platform_window.scale_factor(),
+        }
+    }
+
+    /// Returns the bounds and the display to reopen the window with, clamped into the visible
+    /// bounds of the display it was on, or None when that display is gone.
+    pub fn restore(
+        &self,
+        displays: &[Rc<dyn PlatformDisplay>],
+        primary_display: Option<Rc<dyn PlatformDisplay>>,
+    ) -> Option<(WindowBounds, DisplayId)> {
+        let display = match self.display {
+            Some(uuid) => displays
+                .iter()
+                .find(|display| display.uuid().ok() == Some(uuid))
+                .cloned()?,
+            // The platform doesn't identify displays, so the layout can't be told apart
+            None => primary_display?,
+        };
+
+        let visible_bounds = display.visible_bounds();
+        let clamp = |bounds: Bounds<_>| {
+            let size = bounds.size.min(&visible_bounds.size);
+            let max_origin = point(
+                visible_bounds.right() - size.width,
+                visible_bounds.bottom() - size.height,
+            );
+            Bounds::new(
+                bounds.origin.clamp(&visible_bounds.origin, &max_origin),
+                size,
+            )
+        };
+        let bounds = match self.bounds {
+            WindowBounds::Windowed(bounds) => WindowBounds::Windowed(clamp(bounds)),
+            WindowBounds::Maximized(bounds) => WindowBounds::Maximized(clamp(bounds)),
+            WindowBounds::Fullscreen(bounds) => WindowBounds::Fullscreen(clamp(bounds)),
+        };
+        Some((bounds, display.id()))
+    }
+
+    /// Returns the size to resize the window restored with the given bounds to, so that it keeps
+    /// its size in device pixels when the scale factor of its display changed since it was saved.
+    pub fn rescaled_size(
+        &self,
+        bounds: WindowBounds,
+        scale_factor: f32,
+        display: Option<&dyn PlatformDisplay>,
+    ) -> Option<Size<Pixels>> {
+        // Maximized and fullscreen windows are sized by the platform
+        let WindowBounds::Windowed(bounds) = bounds else {
+            return None;
+        };
+        if scale_factor == self.scale_factor || scale_factor <= 0. {
+            return None;
+        }
+        let size = bounds
+            .size
+            .map(|length| length * (self.scale_factor / scale_factor));
+        Some(match display {
+            Some(display) => size.min(&display.visible_bounds().size),
+            None => size,
+        })
+    }
+}
+
+/// The saved window states of the application, keyed by restore key and kept in a JSON file.
+pub(crate) struct WindowStateStore {
+    path: Option<PathBuf>,
+    // Loaded on first use
+    states: Option<HashMap<SharedString, SavedWindowState>>,
+    write_task: Option<Task<()>>,
+}
+
+impl WindowStateStore {
+    /// Creates a store persisted at the given path, or kept in memory without one.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            states: None,
+            write_task: None,
+        }
+    }
+
+    fn states(&mut self) -> &mut HashMap<SharedString, SavedWindowState> {
+        self.states.get_or_insert_with(|| {
+            let Some(path) = &self.path else {
+                return HashMap::default();
+            };
+            match fs::read_to_string(path) {
+                Ok(contents) => serde_json::from_str(&contents)
+                    .with_context(|| format!("parsing {path:?}"))
+                    .log_err()
+                    .unwrap_or_default(),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => HashMap::default(),
+                Err(error) => {
+                    log::error!("failed to read window state from {path:?}: {error}");
+                    HashMap::default()
+                }
+            }
+        })
+    }
+
+    pub fn get(&mut self, key: &SharedString) -> Option<SavedWindowState> {
+        self.states().get(key).copied()
+    }
+
+    /// Saves the state of a window, writing the file shortly after.
+    pub fn save(
+        &mut self,
+        key: SharedString,
+        state: SavedWindowState,
+        executor: &BackgroundExecutor,
+    ) {
+        if self.states().insert(key, state) == Some(state) {
+            return;
+        }
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let contents = self.serialize();
+        let timer = executor.timer(WRITE_DELAY);
+        // Replacing the task cancels the previous write
+        self.write_task = Some(executor.spawn(async move {
+            timer.await;
+            write(&path, &contents).log_err();
+        }));
+    }
+
+    /// Writes the pending changes synchronously, for when the application quits.
+    pub fn flush(&mut self) {
+        if self.write_task.take().is_some()
+            && let Some(path) = self.path.clone()
+        {
+            let contents = self.serialize();
+            write(&path, &contents).log_err();
+        }
+    }
+
+    fn serialize(&mut self) -> String {
+        serde_json::to_string_pretty(self.states()).unwrap_or_default()
+    }
+}
+
+fn write(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Write atomically, as another instance of the application may read it concurrently
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// The default path of the window state file, in the per-user state directory of the platform
+/// and named after the executable of the application.
+pub(crate) fn default_window_state_path() -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let app_name = exe.file_stem()?;
+    let state_dir = if cfg!(target_os = "macos") {
+        util::paths::home_dir().join("Library/Application Support")
+    } else if cfg!(target_os = "windows") {
+        PathBuf::from(env::var_os("LOCALAPPDATA")?)
+    } else {
+        env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .unwrap_or_else(|| util::paths::home_dir().join(".local/state"))
+    };
+    Some(state_dir.join(app_name).join("window-state.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppContext as _, Empty, TestAppContext, WindowOptions, px, size};
+
+    #[derive(Debug)]
+    struct FakeDisplay {
+        id: DisplayId,
+        uuid: Uuid,
+        bounds: Bounds<Pixels>,
+    }
+
+    impl PlatformDisplay for FakeDisplay {
+        fn id(&self) -> DisplayId {
+            self.id
+        }
+
+        fn uuid(&self) -> anyhow::Result<Uuid> {
+            Ok(self.uuid)
+        }
+
+        fn bounds(&self) -> Bounds<Pixels> {
+            self.bounds
+        }
+    }
+
+    #[test]
+    fn test_restore_window_state() {
+        let left = Uuid::new_v4();
+        let right = Uuid::new_v4();
+        let displays: Vec<Rc<dyn PlatformDisplay>> = vec![
+            Rc::new(FakeDisplay {
+                id: DisplayId(1),
+                uuid: left,
+                bounds: Bounds::new(point(px(0.), px(0.)), size(px(1920.), px(1080.))),
+            }),
+            Rc::new(FakeDisplay {
+                id: DisplayId(2),
+                uuid: right,
+                bounds: Bounds::new(point(px(1920.), px(0.)), size(px(1280.), px(720.))),
+            }),
+        ];
+        let state = |bounds, display| SavedWindowState {
+            bounds,
+            display: Some(display),
+            scale_factor: 1.,
+        };
+        let bounds = Bounds::new(point(px(100.), px(100.)), size(px(800.), px(600.)));
+
+        assert_eq!(
+            state(WindowBounds::Windowed(bounds), left).restore(&displays, None),
+            Some((WindowBounds::Windowed(bounds), DisplayId(1)))
+        );
+
+        // The window was further right before the display got smaller
+        let offscreen = Bounds::new(point(px(3000.), px(500.)), size(px(1600.), px(600.)));
+        assert_eq!(
+            state(WindowBounds::Maximized(offscreen), right).restore(&displays, None),
+            Some((
+                WindowBounds::Maximized(Bounds::new(
+                    point(px(1920.), px(120.)),
+                    size(px(1280.), px(600.))
+                )),
+                DisplayId(2)
+            ))
+        );
+
+        assert_eq!(
+            state(WindowBounds::Windowed(bounds), Uuid::new_v4()).restore(&displays, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rescale_window_state() {
+        let display = FakeDisplay {
+            id: DisplayId(1),
+            uuid: Uuid::new_v4(),
+            bounds: Bounds::new(point(px(0.), px(0.)), size(px(1920.), px(1080.))),
+        };
+        let state = SavedWindowState {
+            bounds: WindowBounds::Windowed(Bounds::new(
+                point(px(100.), px(100.)),
+                size(px(800.), px(600.)),
+            )),
+            display: Some(display.uuid),
+            scale_factor: 2.,
+        };
+
+        assert_eq!(state.rescaled_size(state.bounds, 2., Some(&display)), None);
+        assert_eq!(
+            state.rescaled_size(state.bounds, 4., Some(&display)),
+            Some(size(px(400.), px(300.)))
+        );
+        // Grown past the display
+        assert_eq!(
+            state.rescaled_size(state.bounds, 1., Some(&display)),
+            Some(size(px(1600.), px(1080.)))
+        );
+        let maximized = WindowBounds::Maximized(state.bounds.get_bounds());
+        assert_eq!(state.rescaled_size(maximized, 1., Some(&display)), None);
+    }
+
+    #[gpui::test]
+    fn test_window_restore_key(cx: &mut TestAppContext) {
+        let options = || WindowOptions {
+            window_bounds: Some(WindowBounds::Windowed(Bounds::new(
+                point(px(10.), px(10.)),
+                size(px(400.), px(300.)),
+            ))),
+            restore_key: Some("main".into()),
+            ..Default::default()
+        };
+
+        let window = cx.update(|cx| {
+            cx.open_window(options(), |_, cx| cx.new(|_| Empty))
+                .unwrap()
+        });
+        cx.simulate_window_resize(window.into(), size(px(640.), px(480.)));
+
+        let window = cx.update(|cx| {
+            cx.open_window(options(), |_, cx| cx.new(|_| Empty))
+                .unwrap()
+        });
+        let bounds = window.update(cx, |_, window, _| window.bounds()).unwrap();
+        assert_eq!(
+            bounds,
+            Bounds::new(point(px(10.), px(10.)), size(px(640.), px(480.)))
+        );
+    }
+}
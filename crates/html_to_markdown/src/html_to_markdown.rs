@@ -3,6 +3,7 @@
 mod html_element;
 pub mod markdown;
 mod markdown_writer;
+pub mod readability;
 pub mod structure;
 
 use std::io::Read;
@@ -12,18 +13,51 @@ use html5ever::driver::ParseOpts;
 use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
 use html5ever::tree_builder::TreeBuilderOpts;
-use markup5ever_rcdom::RcDom;
+use markup5ever_rcdom::{Handle, RcDom};
+use url::Url;
 
 pub use crate::html_element::*;
 pub use crate::markdown_writer::*;
 
 /// Converts the provided HTML to Markdown.
-pub fn convert_html_to_markdown(html: impl Read, handlers: &mut [TagHandler]) -> Result<String> {
+///
+/// Relative URLs in links and images are resolved against `base_url`, when provided.
+pub fn convert_html_to_markdown(
+    html: impl Read,
+    base_url: Option<&Url>,
+    handlers: &mut [TagHandler],
+) -> Result<String> {
     let dom = parse_html(html).context("failed to parse HTML")?;
 
-    let markdown_writer = MarkdownWriter::new();
+    convert_node_to_markdown(&dom.document, base_url, handlers)
+}
+
+/// Converts only the main content of the provided HTML to Markdown.
+///
+/// The main content is located with [`readability::find_main_content`], which
+/// discards the navigation, sidebars and other chrome surrounding it.
+pub fn convert_main_content_to_markdown(
+    html: impl Read,
+    base_url: Option<&Url>,
+    handlers: &mut [TagHandler],
+) -> Result<String> {
+    let dom = parse_html(html).context("failed to parse HTML")?;
+    let main_content = readability::find_main_content(&dom.document);
+
+    convert_node_to_markdown(&main_content, base_url, handlers)
+}
+
+fn convert_node_to_markdown(
+    node: &Handle,
+    base_url: Option<&Url>,
+    handlers: &mut [TagHandler],
+) -> Result<String> {
+    let mut markdown_writer = MarkdownWriter::new();
+    if let Some(base_url) = base_url {
+        markdown_writer = markdown_writer.with_base_url(base_url.clone());
+    }
     let markdown = markdown_writer
-        .run(&dom.document, handlers)
+        .run(node, handlers)
         .context("failed to convert HTML to Markdown")?;
 
     Ok(markdown)
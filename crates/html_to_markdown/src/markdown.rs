@@ -274,3 +274,456 @@ impl HandleTag for CodeHandler {
         HandlerOutcome::NoOp
     }
 }
+
+/// Strips the `#` markers of a Markdown heading from the start of the line.
+fn strip_heading_prefix(line: &str) -> &str {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    match line[hashes..].strip_prefix(' ') {
+        Some(text) if (1..=6).contains(&hashes) => text,
+        _ => line,
+    }
+}
+
+/// Returns the given URL in a form that can be used as a Markdown link destination.
+fn link_destination(url: &str) -> String {
+    if url.contains(|char: char| char.is_whitespace() || char == '(' || char == ')') {
+        format!("<{url}>")
+    } else {
+        url.to_string()
+    }
+}
+
+pub struct LinkHandler {
+    /// The Markdown offsets and destinations of the currently open `<a>` tags.
+    open_links: Vec<(usize, Option<String>)>,
+}
+
+impl LinkHandler {
+    pub fn new() -> Self {
+        Self {
+            open_links: Vec::new(),
+        }
+    }
+}
+
+impl Default for LinkHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleTag for LinkHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "a"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        let href = tag
+            .attr("href")
+            .map(|href| href.trim().to_string())
+            .filter(|href| {
+                !href.is_empty() && !href.to_ascii_lowercase().starts_with("javascript:")
+            })
+            .filter(|_| !writer.is_inside("pre"));
+
+        let offset = writer.markdown.len();
+        if href.is_some() {
+            writer.push_str("[");
+        }
+        self.open_links
+            .push((offset, href.map(|href| writer.resolve_url(&href))));
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        let Some((offset, Some(href))) = self.open_links.pop() else {
+            return;
+        };
+
+        // Links without any text would render as an empty `[]`, so we drop them entirely.
+        if writer.markdown[offset + 1..].trim().is_empty() {
+            writer.markdown.truncate(offset);
+            return;
+        }
+
+        // Link text can't span multiple blocks in Markdown, so block content inside of a link,
+        // such as the heading and summary of a card, is collapsed onto its own line.
+        let text = &writer.markdown[offset + 1..];
+        if text.contains('\n') {
+            let text = text
+                .lines()
+                .map(|line| strip_heading_prefix(line.trim()).trim())
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            writer.markdown.truncate(offset);
+            writer.push_blank_line();
+            writer.push_str("[");
+            writer.push_str(&text);
+        }
+
+        writer.push_str("](");
+        writer.push_str(&link_destination(&href));
+        writer.push_str(")");
+    }
+
+    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if !self.open_links.iter().any(|(_, href)| href.is_some()) {
+            return HandlerOutcome::NoOp;
+        }
+
+        // Brackets in the link text would end the link early.
+        let text = text
+            .trim_matches(|char| char == '\n' || char == '\r' || char == '\t')
+            .replace('\n', " ")
+            .replace('[', "\\[")
+            .replace(']', "\\]");
+        writer.push_str(&text);
+        HandlerOutcome::Handled
+    }
+}
+
+pub struct ImageHandler;
+
+impl HandleTag for ImageHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "img"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        let Some(src) = tag.attr("src").filter(|src| !src.trim().is_empty()) else {
+            return StartTagOutcome::Skip;
+        };
+
+        let alt = tag
+            .attr("alt")
+            .unwrap_or_default()
+            .replace('[', "\\[")
+            .replace(']', "\\]");
+
+        writer.push_str("![");
+        writer.push_str(alt.trim());
+        writer.push_str("](");
+        writer.push_str(&link_destination(&writer.resolve_url(src.trim())));
+        if let Some(title) = tag.attr("title").filter(|title| !title.trim().is_empty()) {
+            writer.push_str(" \"");
+            writer.push_str(&title.trim().replace('"', "\\\""));
+            writer.push_str("\"");
+        }
+        writer.push_str(")");
+
+        StartTagOutcome::Skip
+    }
+}
+
+pub struct BlockquoteHandler {
+    /// The Markdown offsets at which the currently open `<blockquote>` tags start.
+    open_blockquotes: Vec<usize>,
+}
+
+impl BlockquoteHandler {
+    pub fn new() -> Self {
+        Self {
+            open_blockquotes: Vec::new(),
+        }
+    }
+}
+
+impl Default for BlockquoteHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleTag for BlockquoteHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "blockquote"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        _tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        writer.push_blank_line();
+        self.open_blockquotes.push(writer.markdown.len());
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        let Some(offset) = self.open_blockquotes.pop() else {
+            return;
+        };
+
+        let contents = writer.markdown.split_off(offset);
+        let mut lines = contents.lines().map(str::trim_end).collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        let first_line = lines.iter().position(|line| !line.trim().is_empty());
+        let Some(first_line) = first_line else {
+            return;
+        };
+
+        let mut previous_line_was_empty = false;
+        for line in &lines[first_line..] {
+            if line.trim().is_empty() {
+                if !previous_line_was_empty {
+                    writer.push_str(">\n");
+                }
+                previous_line_was_empty = true;
+            } else {
+                writer.push_str("> ");
+                writer.push_str(line);
+                writer.push_newline();
+                previous_line_was_empty = false;
+            }
+        }
+        writer.push_newline();
+    }
+}
+
+pub struct DefinitionListHandler;
+
+impl HandleTag for DefinitionListHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        matches!(tag, "dl" | "dt" | "dd")
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        // Whitespace between the terms and definitions would otherwise be left at the ends of lines.
+        let trimmed_len = writer.markdown.trim_end_matches(' ').len();
+        writer.markdown.truncate(trimmed_len);
+
+        match tag.tag() {
+            "dl" => writer.push_blank_line(),
+            "dt" => writer.push_newline(),
+            "dd" => writer.push_str("\n: "),
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if tag.tag() == "dl" {
+            let trimmed_len = writer.markdown.trim_end_matches(' ').len();
+            writer.markdown.truncate(trimmed_len);
+            writer.push_blank_line();
+        }
+    }
+}
+
+pub struct HorizontalRuleHandler;
+
+impl HandleTag for HorizontalRuleHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "hr"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        _tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        writer.push_blank_line();
+        writer.push_str("---");
+        writer.push_blank_line();
+
+        StartTagOutcome::Skip
+    }
+}
+
+/// Preserves `<sup>` and `<sub>` as inline HTML, as Markdown has no syntax for them.
+pub struct SuperscriptSubscriptHandler;
+
+impl HandleTag for SuperscriptSubscriptHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        matches!(tag, "sup" | "sub")
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "sup" => writer.push_str("<sup>"),
+            "sub" => writer.push_str("<sub>"),
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "sup" => writer.push_str("</sup>"),
+            "sub" => writer.push_str("</sub>"),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use url::Url;
+
+    use crate::{TagHandler, convert_html_to_markdown};
+
+    use super::*;
+
+    fn handlers() -> Vec<TagHandler> {
+        vec![
+            Rc::new(RefCell::new(WebpageChromeRemover)),
+            Rc::new(RefCell::new(ParagraphHandler)),
+            Rc::new(RefCell::new(HeadingHandler)),
+            Rc::new(RefCell::new(ListHandler)),
+            Rc::new(RefCell::new(StyledTextHandler)),
+            Rc::new(RefCell::new(CodeHandler)),
+            Rc::new(RefCell::new(LinkHandler::new())),
+            Rc::new(RefCell::new(ImageHandler)),
+            Rc::new(RefCell::new(BlockquoteHandler::new())),
+            Rc::new(RefCell::new(DefinitionListHandler)),
+            Rc::new(RefCell::new(HorizontalRuleHandler)),
+            Rc::new(RefCell::new(SuperscriptSubscriptHandler)),
+        ]
+    }
+
+    fn convert(html: &str, base_url: Option<&str>) -> String {
+        let base_url = base_url.map(|base_url| Url::parse(base_url).unwrap());
+        convert_html_to_markdown(html.as_bytes(), base_url.as_ref(), &mut handlers()).unwrap()
+    }
+
+    #[test]
+    fn test_links_are_resolved_against_the_base_url() {
+        let html = indoc! {r##"
+            <p>See the <a href="../guide/intro.html">introduction</a>, the <a href="https://example.com/faq">FAQ</a> and the <a href="#usage">usage notes</a>.</p>
+        "##};
+        let expected = indoc! {"
+            See the [introduction](https://docs.example.com/guide/intro.html), the [FAQ](https://example.com/faq) and the [usage notes](https://docs.example.com/api/index.html#usage).
+        "}
+        .trim();
+
+        assert_eq!(
+            convert(html, Some("https://docs.example.com/api/index.html")),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_links_without_a_base_url_or_text() {
+        let html = indoc! {r#"
+            <a href="/docs/getting started">The docs</a><a href="/anchor"></a> and <a name="top">this</a>
+        "#};
+        let expected = "[The docs](</docs/getting started>) and this";
+
+        assert_eq!(convert(html, None), expected);
+    }
+
+    #[test]
+    fn test_links_with_block_content_or_javascript_urls() {
+        let html = indoc! {r#"
+            <p>Start here:</p>
+            <a href="/guide"><h3>Guide</h3><p>Read the guide.</p></a>
+            <a href="/picks"><h3>#1 pick [2024]</h3><p>#rustlang</p></a>
+            <p>Or <a href="JavaScript:void(0)">click here</a> and <a href=" JAVASCRIPT:run()">here</a>.</p>
+        "#};
+        let expected = indoc! {"
+            Start here:
+
+            [Guide Read the guide.](/guide)
+
+            [#1 pick \\[2024\\] #rustlang](/picks)
+
+            Or click here and here.
+        "}
+        .trim();
+
+        assert_eq!(convert(html, None), expected);
+    }
+
+    #[test]
+    fn test_images() {
+        let html = indoc! {r#"
+            <p><a href="/"><img src="logo.png" alt="Logo" title="The logo"></a> <img alt="no source"></p>
+        "#};
+        let expected =
+            r#"[![Logo](https://example.com/img/logo.png "The logo")](https://example.com/)"#;
+
+        assert_eq!(convert(html, Some("https://example.com/img/")), expected);
+    }
+
+    #[test]
+    fn test_blockquotes() {
+        let html = indoc! {"
+            <p>Before</p>
+            <blockquote>
+                <p>First paragraph.</p>
+                <p>Second paragraph.</p>
+                <blockquote><p>Nested.</p></blockquote>
+            </blockquote>
+            <p>After</p>
+        "};
+        let expected = indoc! {"
+            Before
+
+            > First paragraph.
+            >
+            > Second paragraph.
+            >
+            > > Nested.
+
+            After
+        "}
+        .trim();
+
+        assert_eq!(convert(html, None), expected);
+    }
+
+    #[test]
+    fn test_definition_lists_and_horizontal_rules() {
+        let html = indoc! {"
+            <dl>
+                <dt>Term</dt>
+                <dd>Definition of the term.</dd>
+                <dt>Other term</dt>
+                <dd>Another definition.</dd>
+            </dl>
+            <hr>
+            E = mc<sup>2</sup> and H<sub>2</sub>O
+        "};
+        let expected = indoc! {"
+            Term
+            : Definition of the term.
+            Other term
+            : Another definition.
+
+            ---
+
+            E = mc<sup>2</sup> and H<sub>2</sub>O
+        "}
+        .trim();
+
+        assert_eq!(convert(html, None), expected);
+    }
+}
@@ -5,6 +5,7 @@ use std::{cell::RefCell, sync::LazyLock};
 use anyhow::Result;
 use markup5ever_rcdom::{Handle, NodeData};
 use regex::Regex;
+use url::Url;
 
 use crate::html_element::HtmlElement;
 
@@ -28,6 +29,8 @@ pub type TagHandler = Rc<RefCell<dyn HandleTag>>;
 
 pub struct MarkdownWriter {
     current_element_stack: VecDeque<HtmlElement>,
+    /// The URL that relative links and image sources are resolved against.
+    base_url: Option<Url>,
     pub(crate) markdown: String,
 }
 
@@ -41,10 +44,32 @@ impl MarkdownWriter {
     pub fn new() -> Self {
         Self {
             current_element_stack: VecDeque::new(),
+            base_url: None,
             markdown: String::new(),
         }
     }
 
+    /// Sets the URL that relative URLs in the document are resolved against.
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    pub fn base_url(&self) -> Option<&Url> {
+        self.base_url.as_ref()
+    }
+
+    /// Resolves the given URL against the base URL, if one was provided.
+    ///
+    /// URLs that cannot be resolved are returned unchanged.
+    pub fn resolve_url(&self, url: &str) -> String {
+        self.base_url
+            .as_ref()
+            .and_then(|base_url| base_url.join(url).ok())
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| url.to_string())
+    }
+
     pub fn current_element_stack(&self) -> &VecDeque<HtmlElement> {
         &self.current_element_stack
     }
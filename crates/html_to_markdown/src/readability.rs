@@ -0,0 +1,424 @@
+//! Locates the main content of an arbitrary webpage.
+//!
+//! This is a simplified take on the scoring used by Mozilla's
+//! [Readability](https://github.com/mozilla/readability): blocks of prose award
+//! points to their ancestors, the points are weighted by semantic tags and
+//! class names, and then penalized by link density. The highest scoring
+//! element is taken to be the main content.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+use markup5ever_rcdom::{Handle, Node, NodeData};
+use regex::Regex;
+
+/// Paragraphs with less text than this are not considered when scoring.
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Candidates with a lower final score than this are ignored.
+const MIN_CANDIDATE_SCORE: f32 = 20.;
+
+// Short tokens that occur inside of other words, such as "toc" in "protocol", are only matched
+// when delimited by whitespace, `-` or `_`.
+fn unlikely_candidates_regex() -> &'static Regex {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?i)-ad-|ai2html|banner|breadcrumbs|combx|comment|community|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|related|remark|replies|rss|shoutbox|sidebar|skyscraper|social|sponsor|supplemental|ad-break|agegate|pagination|pager|popup|yom-remote|cookie|(^|[-_\s])toc([-_\s]|$)",
+        )
+        .expect("Failed to create unlikely_candidates_regex")
+    });
+    &REGEX
+}
+
+fn maybe_candidate_regex() -> &'static Regex {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)and|article|body|column|content|main|shadow")
+            .expect("Failed to create maybe_candidate_regex")
+    });
+    &REGEX
+}
+
+fn positive_regex() -> &'static Regex {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?i)article|body|content|entry|hentry|h-entry|main|page|pagination|post|text|blog|story|documentation|markdown|prose",
+        )
+        .expect("Failed to create positive_regex")
+    });
+    &REGEX
+}
+
+fn negative_regex() -> &'static Regex {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?i)-ad-|hidden|^hid$| hid$| hid |^hid |banner|combx|comment|com-|contact|footer|gdpr|masthead|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|widget|menu|(^|[-_\s])(media|meta|nav|tags|toc)([-_\s]|$)",
+        )
+        .expect("Failed to create negative_regex")
+    });
+    &REGEX
+}
+
+/// Returns the element containing the main content of the given document.
+///
+/// Falls back to the `<body>` (or the document itself) when no element stands
+/// out, so the result can always be passed to [`MarkdownWriter::run`](crate::MarkdownWriter::run).
+pub fn find_main_content(document: &Handle) -> Handle {
+    let mut scores = ContentScores::default();
+    scores.score_node(document);
+
+    scores
+        .best_candidate()
+        .or_else(|| find_element(document, "body"))
+        .unwrap_or_else(|| document.clone())
+}
+
+#[derive(Default)]
+struct ContentScores {
+    /// The candidates for the main content, keyed by node identity.
+    candidates: HashMap<*const Node, (Handle, f32)>,
+}
+
+impl ContentScores {
+    fn score_node(&mut self, node: &Handle) {
+        if let Some(tag) = element_tag(node) {
+            if is_unlikely_candidate(node, &tag) {
+                return;
+            }
+
+            if is_paragraph_like(node, &tag) {
+                self.score_paragraph(node);
+                // The contents of a paragraph are scored as a whole.
+                if tag != "div" && tag != "section" {
+                    return;
+                }
+            }
+        }
+
+        for child in node.children.borrow().iter() {
+            self.score_node(child);
+        }
+    }
+
+    /// Awards points for the given paragraph to its ancestors.
+    fn score_paragraph(&mut self, paragraph: &Handle) {
+        let text = text_content(paragraph);
+        let text_length = text.chars().count();
+        if text_length < MIN_PARAGRAPH_LENGTH {
+            return;
+        }
+
+        let mut score = 1.;
+        score += text.matches([',', '，']).count() as f32;
+        score += (text_length as f32 / 100.).floor().min(3.);
+
+        let mut ancestor = parent(paragraph);
+        let mut level = 0;
+        while let Some(node) = ancestor {
+            if element_tag(&node).is_none() || level > 2 {
+                break;
+            }
+
+            let divider = match level {
+                0 => 1.,
+                1 => 2.,
+                _ => level as f32 * 3.,
+            };
+            let entry = self
+                .candidates
+                .entry(Rc::as_ptr(&node))
+                .or_insert_with(|| (node.clone(), initial_score(&node)));
+            entry.1 += score / divider;
+
+            ancestor = parent(&node);
+            level += 1;
+        }
+    }
+
+    fn best_candidate(&self) -> Option<Handle> {
+        self.candidates
+            .values()
+            .map(|(node, score)| (node, score * (1. - link_density(node))))
+            .filter(|(_, score)| *score >= MIN_CANDIDATE_SCORE)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(node, _)| node.clone())
+    }
+}
+
+/// Returns the score an element starts with, based on its tag and class names.
+fn initial_score(node: &Handle) -> f32 {
+    let tag_score = match element_tag(node).as_deref() {
+        Some("main" | "article") => 25.,
+        Some("div") => 5.,
+        Some("section" | "pre" | "td" | "blockquote") => 3.,
+        Some("address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form") => -3.,
+        Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th") => -5.,
+        _ => 0.,
+    };
+
+    tag_score + class_weight(node)
+}
+
+/// Returns a weight for the element based on its `class` and `id` attributes.
+fn class_weight(node: &Handle) -> f32 {
+    let mut weight = 0.;
+    for name in ["class", "id"] {
+        let Some(value) = attr(node, name) else {
+            continue;
+        };
+
+        if negative_regex().is_match(&value) {
+            weight -= 25.;
+        }
+        if positive_regex().is_match(&value) {
+            weight += 25.;
+        }
+    }
+
+    weight
+}
+
+fn is_unlikely_candidate(node: &Handle, tag: &str) -> bool {
+    if matches!(
+        tag,
+        "head" | "script" | "style" | "noscript" | "nav" | "aside" | "footer" | "form" | "iframe"
+    ) {
+        return true;
+    }
+
+    if matches!(tag, "body" | "main" | "article" | "a") {
+        return false;
+    }
+
+    if attr(node, "role").is_some_and(|role| {
+        matches!(
+            role.as_str(),
+            "navigation" | "banner" | "complementary" | "contentinfo" | "menu" | "menubar"
+        )
+    }) {
+        return true;
+    }
+
+    let match_string = [attr(node, "class"), attr(node, "id")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    unlikely_candidates_regex().is_match(&match_string)
+        && !maybe_candidate_regex().is_match(&match_string)
+}
+
+/// Returns whether the element should be scored as a block of prose.
+fn is_paragraph_like(node: &Handle, tag: &str) -> bool {
+    match tag {
+        "p" | "pre" | "td" | "blockquote" => true,
+        // Text placed directly inside of a `<div>` or `<section>` is treated as a paragraph.
+        "div" | "section" => node
+            .children
+            .borrow()
+            .iter()
+            .any(|child| match &child.data {
+                NodeData::Text { contents } => {
+                    contents.borrow().trim().len() >= MIN_PARAGRAPH_LENGTH
+                }
+                _ => false,
+            }),
+        _ => false,
+    }
+}
+
+/// Returns the proportion of the element's text that is inside of links.
+fn link_density(node: &Handle) -> f32 {
+    let text_length = text_content(node).chars().count();
+    if text_length == 0 {
+        return 0.;
+    }
+
+    let link_length = link_text_length(node);
+    link_length as f32 / text_length as f32
+}
+
+fn link_text_length(node: &Handle) -> usize {
+    if element_tag(node).as_deref() == Some("a") {
+        return text_content(node).chars().count();
+    }
+
+    node.children.borrow().iter().map(link_text_length).sum()
+}
+
+/// Returns the text of the node and its descendants, with whitespace collapsed.
+fn text_content(node: &Handle) -> String {
+    fn collect_text(node: &Handle, text: &mut String) {
+        match &node.data {
+            NodeData::Text { contents } => {
+                for word in contents.borrow().split_whitespace() {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push_str(word);
+                }
+            }
+            NodeData::Element { name, .. }
+                if matches!(name.local.as_ref(), "script" | "style" | "noscript") => {}
+            _ => {
+                for child in node.children.borrow().iter() {
+                    collect_text(child, text);
+                }
+            }
+        }
+    }
+
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text
+}
+
+fn element_tag(node: &Handle) -> Option<String> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(name.local.to_string()),
+        _ => None,
+    }
+}
+
+fn attr(node: &Handle, name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| attr.name.local.as_ref() == name)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+fn parent(node: &Handle) -> Option<Handle> {
+    let parent = node.parent.take();
+    let upgraded = parent.as_ref().and_then(|parent| parent.upgrade());
+    node.parent.set(parent);
+    upgraded
+}
+
+fn find_element(node: &Handle, tag: &str) -> Option<Handle> {
+    if element_tag(node).as_deref() == Some(tag) {
+        return Some(node.clone());
+    }
+
+    node.children
+        .borrow()
+        .iter()
+        .find_map(|child| find_element(child, tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::{TagHandler, convert_main_content_to_markdown, markdown};
+
+    fn handlers() -> Vec<TagHandler> {
+        vec![
+            Rc::new(RefCell::new(markdown::WebpageChromeRemover)),
+            Rc::new(RefCell::new(markdown::ParagraphHandler)),
+            Rc::new(RefCell::new(markdown::HeadingHandler)),
+            Rc::new(RefCell::new(markdown::ListHandler)),
+            Rc::new(RefCell::new(markdown::CodeHandler)),
+        ]
+    }
+
+    #[test]
+    fn test_main_content_is_extracted_from_page_chrome() {
+        let html = indoc! {r#"
+            <html>
+            <body>
+                <div class="site-header">
+                    <a href="/">Home</a> <a href="/blog">Blog</a> <a href="/about">About</a>
+                </div>
+                <div class="layout">
+                    <div class="sidebar">
+                        <ul>
+                            <li><a href="/docs/install">Installation and setup guide</a></li>
+                            <li><a href="/docs/config">Configuration reference, all options</a></li>
+                        </ul>
+                    </div>
+                    <div class="documentation"><h1>Configuration</h1><p>The configuration file lives in your home directory, and is read on every launch of the application.</p><p>Each setting can be overridden per project, by placing a settings file in the root of the project.</p></div>
+                </div>
+                <div class="footer">Copyright, all rights reserved, and some other legal text that is long enough.</div>
+            </body>
+            </html>
+        "#};
+        let expected = indoc! {"
+            # Configuration
+
+            The configuration file lives in your home directory, and is read on every launch of the application.
+
+            Each setting can be overridden per project, by placing a settings file in the root of the project.
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_main_content_to_markdown(html.as_bytes(), None, &mut handlers()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_link_heavy_content_is_penalized() {
+        let html = indoc! {r#"
+            <body>
+                <div>
+                    <p><a href="/a">A link with a long title, that has commas, many commas</a></p>
+                    <p><a href="/b">Another link with a long title, that has commas, many commas</a></p>
+                    <p><a href="/c">Yet another link with a long title, that has commas, many commas</a></p>
+                </div>
+                <article>
+                    <p>Short article, but with real prose in it.</p>
+                </article>
+            </body>
+        "#};
+
+        assert_eq!(
+            convert_main_content_to_markdown(html.as_bytes(), None, &mut handlers()).unwrap(),
+            "Short article, but with real prose in it."
+        );
+    }
+
+    #[test]
+    fn test_class_names_are_matched_on_word_boundaries() {
+        let html = indoc! {r#"
+            <body>
+                <div class="site-nav"><a href="/">Home</a> <a href="/docs">Docs</a></div>
+                <div id="protocol-docs">
+                    <div class="canvas-docs"><p>The protocol is a sequence of messages, each of which is prefixed with its length, its type, and its flags.</p><p>Messages are sent in order, over a single connection, and are acknowledged by the peer, in batches, once handled.</p><p>Unknown messages, such as those from newer versions, are skipped, logged, and counted, but never rejected, by either peer.</p></div>
+                </div>
+            </body>
+        "#};
+        let expected = indoc! {"
+            The protocol is a sequence of messages, each of which is prefixed with its length, its type, and its flags.
+
+            Messages are sent in order, over a single connection, and are acknowledged by the peer, in batches, once handled.
+
+            Unknown messages, such as those from newer versions, are skipped, logged, and counted, but never rejected, by either peer.
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_main_content_to_markdown(html.as_bytes(), None, &mut handlers()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_the_body() {
+        let html = "<body><p>Too short.</p></body>";
+
+        assert_eq!(
+            convert_main_content_to_markdown(html.as_bytes(), None, &mut handlers()).unwrap(),
+            "Too short."
+        );
+    }
+}